@@ -4,6 +4,46 @@ export const API_ROUTE = (part: string) => `${apiUri}/${part}`
 
 export const API = {
 	sessions: () => fetch(API_ROUTE("sessions")),
+	session: (id: string) => fetch(API_ROUTE(`sessions/${id}`)),
+	datasets: () => fetch(API_ROUTE("datasets")),
+	dataset: (id: string) => fetch(API_ROUTE(`datasets/${id}`)),
+	record: (id: string, logrecno: number) => fetch(API_ROUTE(`datasets/${id}/records/${logrecno}`)),
+	geoid: (id: string, geoid: string) => fetch(API_ROUTE(`datasets/${id}/geoids/${geoid}`)),
+	tables: (id: string) => fetch(API_ROUTE(`datasets/${id}/tables`)),
+	table: (id: string, table: string) => fetch(API_ROUTE(`datasets/${id}/tables/${table}`)),
+	plans: () => fetch(API_ROUTE("plans")),
+	plan: (id: number) => fetch(API_ROUTE(`plans/${id}`)),
+	reassign: (id: number, assignments: Record<string, number | null>) => fetch(API_ROUTE(`plans/${id}`), {
+		method: "PATCH",
+		headers: { "Content-Type": "application/json" },
+		body: JSON.stringify({ assignments }),
+	}),
+	districts: (id: number) => fetch(API_ROUTE(`plans/${id}/districts.geojson`)),
+	live: (id: number) => new WebSocket(API_ROUTE(`plans/${id}/live`).replace(/^http/, "ws")),
+	convexHull: (parameters: { polygons?: object[], blocks?: string[], shapefile?: string, dataset?: string }) => fetch(API_ROUTE("computations/convex_hull"), {
+		method: "POST",
+		headers: { "Content-Type": "application/json" },
+		body: JSON.stringify(parameters),
+	}),
 	shapefiles: () => fetch(API_ROUTE("shapefiles")),
-	shapefile: (id: string) => fetch(API_ROUTE(`shapefiles/${id}`)),
+	shapefile: (id: string, zoom?: number, format?: "geojson" | "topojson") => {
+		const query = new URLSearchParams();
+		if (zoom !== undefined) query.set("zoom", `${zoom}`);
+		if (format) query.set("format", format);
+		return fetch(API_ROUTE(`shapefiles/${id}`) + (query.toString() ? `?${query}` : ""));
+	},
+	shapefileFeatures: (id: string, filters: { bbox?: [number, number, number, number], geoids?: string[] }) => {
+		const query = new URLSearchParams();
+		if (filters.bbox) query.set("bbox", filters.bbox.join(","));
+		if (filters.geoids) query.set("geoid", filters.geoids.join(","));
+		return fetch(API_ROUTE(`shapefiles/${id}`) + `?${query}`);
+	},
+	shapefileFeature: (id: string, geoid: string) => fetch(API_ROUTE(`shapefiles/${id}/features/${geoid}`)),
+	locate: (id: string, lat: number, lon: number, options: { plan?: number, dataset?: string } = {}) => {
+		const query = new URLSearchParams({ lat: `${lat}`, lon: `${lon}` });
+		if (options.plan !== undefined) query.set("plan", `${options.plan}`);
+		if (options.dataset) query.set("dataset", options.dataset);
+		return fetch(API_ROUTE(`shapefiles/${id}/locate?${query}`));
+	},
+	tilesUrl: (id: string, dataset?: string) => API_ROUTE(`shapefiles/${id}/tiles/{z}/{x}/{y}.mvt`) + (dataset ? `?dataset=${dataset}` : ""),
 };
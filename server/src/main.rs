@@ -8,22 +8,21 @@ async fn main() -> distringo::Result<()> {
 
 	pretty_env_logger::init_custom_env("DISTRINGO_LOG");
 
-	let settings = {
-		use config::{Config, Environment, File};
+	let settings: config::Config = server::settings()?;
 
-		let mut settings = Config::default();
+	// With --check, stop once the configuration has been found to be valid.
+	let check: bool = std::env::args().skip(1).any(|arg| arg == "--check");
 
-		settings.set_default("server.host", "::")?;
-		settings.set_default("server.port", 2020)?;
-
-		settings.merge(Environment::with_prefix("DISTRINGO"))?;
-
-		settings.merge(File::with_name("config"))?;
+	let mut plan: server::ExecutionPlan = settings.into();
+	if let Err(error) = plan.prepare() {
+		log::error!("{}", error);
+		std::process::exit(1);
+	}
 
-		settings
-	};
+	if check {
+		log::info!("Configuration is valid");
+		return Ok(());
+	}
 
-	let mut plan: server::ExecutionPlan = settings.into();
-	plan.prepare()?;
 	plan.execute().await
 }
@@ -1,7 +1,82 @@
 pub use distringo::Result;
 
+use std::{
+	net::{IpAddr, SocketAddr},
+	time::{Duration, SystemTime},
+};
+
 pub mod routes;
 
+/// The files `config` is read from, depending on which format it is in
+const CONFIGURATION_FILES: &[&str] = &["config.json", "config.yaml", "config.yml"];
+
+/// Read the configuration from the environment and the configuration file
+pub fn settings() -> Result<config::Config> {
+	use config::{Config, Environment, File};
+
+	let mut settings = Config::default();
+
+	settings.set_default("server.host", "::")?;
+	settings.set_default("server.port", 2020)?;
+	settings.set_default("server.reload_interval", 5)?;
+	settings.set_default("server.memory_limit", 0)?;
+	settings.set_default("plans.directory", "data/plans")?;
+
+	settings.merge(Environment::with_prefix("DISTRINGO"))?;
+
+	settings.merge(File::with_name("config"))?;
+
+	Ok(settings)
+}
+
+/// When the configuration file was last changed
+fn configuration_modified() -> Option<SystemTime> {
+	CONFIGURATION_FILES
+		.iter()
+		.filter_map(|file| {
+			std::fs::metadata(file)
+				.and_then(|metadata| metadata.modified())
+				.ok()
+		})
+		.max()
+}
+
+/// Read the configuration again and swap in whatever it now loads
+fn reload() -> Result<()> {
+	let plan = ExecutionPlan::from(settings()?);
+	plan.prepare()?;
+
+	routes::api::reload(&plan.0)
+}
+
+/// Reload whenever the configuration file, or a file loaded from it, changes
+///
+/// Changes are polled for every `interval`.  The server keeps listening where
+/// it started, and on the same plans directory; only datasets, shapefiles and
+/// sessions are reloaded.
+async fn watch(interval: Duration) {
+	let mut modified: Option<SystemTime> = configuration_modified();
+	let mut ticks = tokio::time::interval(interval);
+
+	loop {
+		ticks.tick().await;
+
+		let now: Option<SystemTime> = configuration_modified();
+		if now == modified && !routes::api::stale() {
+			continue;
+		}
+		modified = now;
+
+		log::info!("Reloading configuration");
+
+		match tokio::task::spawn_blocking(reload).await {
+			Ok(Ok(())) => log::info!("Reloaded configuration"),
+			Ok(Err(error)) => log::warn!("Keeping the current configuration: {}", error),
+			Err(error) => log::error!("Reloading failed: {}", error),
+		}
+	}
+}
+
 async fn handle_rejection(
 	err: warp::Rejection,
 ) -> core::result::Result<impl warp::Reply, core::convert::Infallible> {
@@ -10,6 +85,12 @@ async fn handle_rejection(
 			warp::reply::html(include_str!("404.html")),
 			http::StatusCode::NOT_FOUND,
 		))
+	} else if let Some(err) = err.find::<warp::filters::body::BodyDeserializeError>() {
+		log::debug!("rejected request body: {}", err);
+		Ok(warp::reply::with_status(
+			warp::reply::html(include_str!("400.html")),
+			http::StatusCode::BAD_REQUEST,
+		))
 	} else {
 		log::warn!("unhandled rejection: {:?}", err);
 		Ok(warp::reply::with_status(
@@ -28,37 +109,153 @@ impl From<config::Config> for ExecutionPlan {
 }
 
 impl ExecutionPlan {
+	fn host(&self) -> Result<IpAddr> {
+		self
+			.0
+			.get_str("server.host")?
+			.parse()
+			.map_err(|_| distringo::Error::InvalidServerHost)
+	}
+
+	fn port(&self) -> Result<u16> {
+		use core::convert::TryInto;
+
+		self
+			.0
+			.get_int("server.port")?
+			.try_into()
+			.map_err(|_| distringo::Error::InvalidServerPort)
+	}
+
+	/// How often to check for changes to reload, if at all
+	fn reload_interval(&self) -> Result<Option<Duration>> {
+		use core::convert::TryFrom;
+
+		let seconds: u64 = u64::try_from(self.0.get_int("server.reload_interval")?).map_err(|_| {
+			config::ConfigError::Message("the reload interval must not be negative".to_string())
+		})?;
+
+		Ok(Some(Duration::from_secs(seconds)).filter(|interval| !interval.is_zero()))
+	}
+
+	/// Check the whole configuration before anything is loaded from it
+	///
+	/// Every problem found is reported in the one error, not just the first.
 	pub fn prepare(&self) -> Result<()> {
-		// TODO(rye): Instead of doing nothing, perform a dry run of creating the
-		// routes here so as to early-die if something is amiss.
-		//
-		// (Ideally, reduce the contract of execute())
+		let mut report = routes::api::validation::Report::default();
 
-		Ok(())
+		if let Err(error) = self.host() {
+			report.push("server.host", error);
+		}
+
+		if let Err(error) = self.port() {
+			report.push("server.port", error);
+		}
+
+		if let Err(error) = self.reload_interval() {
+			report.push("server.reload_interval", error);
+		}
+
+		routes::api::check(&self.0, &mut report);
+
+		report.into_result()
 	}
 
 	pub async fn execute(&mut self) -> Result<()> {
-		use std::net::{IpAddr, SocketAddr};
+		let socket = SocketAddr::new(self.host()?, self.port()?);
+		let routes = routes::routes(&self.0)?;
 
-		let socket = {
-			use core::convert::TryInto;
+		if let Some(interval) = self.reload_interval()? {
+			tokio::spawn(watch(interval));
+		}
 
-			let config: &config::Config = &self.0;
+		warp::serve(routes).run(socket).await;
 
-			let host: IpAddr = config
-				.get_str("server.host")?
-				.parse()
-				.map_err(|_| distringo::Error::InvalidServerHost)?;
-			let port: u16 = config
-				.get_int("server.port")?
-				.try_into()
-				.map_err(|_| distringo::Error::InvalidServerPort)?;
+		Ok(())
+	}
+}
 
-			SocketAddr::new(host, port)
-		};
+#[cfg(test)]
+mod tests {
+	use super::ExecutionPlan;
 
-		warp::serve(routes::routes(&self.0)?).run(socket).await;
+	fn plan(yaml: &str) -> ExecutionPlan {
+		let mut config = config::Config::default();
+		config
+			.merge(config::File::from_str(yaml, config::FileFormat::Yaml))
+			.unwrap();
 
-		Ok(())
+		config.into()
+	}
+
+	fn problems(plan: &ExecutionPlan) -> Vec<String> {
+		match plan.prepare() {
+			Ok(()) => Vec::new(),
+			Err(distringo::Error::InvalidConfiguration(problems)) => problems,
+			Err(error) => panic!("unexpected error: {:?}", error),
+		}
+	}
+
+	#[test]
+	fn valid_configuration_passes() {
+		let plan = plan(
+			"
+server: { host: '::1', port: 2020, reload_interval: 0 }
+plans: { directory: '../target/plans' }
+",
+		);
+
+		assert_eq!(problems(&plan), Vec::<String>::new());
+	}
+
+	#[test]
+	fn every_problem_is_reported() {
+		let plan = plan(
+			"
+server: { host: 'localhost', port: 70000, reload_interval: -1, memory_limit: -1 }
+plans: { directory: 'Cargo.toml' }
+datasets:
+  missing: { packing_list: '<some missing packing list>' }
+  listed:
+    packing_list: '../src/dataset/t/2010/in2010.pl.prd.packinglist.txt'
+    tables:
+    - p1: { schema: '../schemas/2010/pl94_171/p1.yml' }
+    - p9: { schema: '../schemas/2010/pl94_171/p1.yml' }
+    - p2: { schema: '../schemas/2010/pl94_171/p1.yml' }
+shapefiles:
+  blocks: { type: tabular_block, file: '<some missing file>' }
+  unknown: { type: county, file: '<some missing file>' }
+sessions:
+  session: { datasets: [missing, absent], shapefiles: [blocks, nowhere] }
+",
+		);
+
+		let problems = problems(&plan);
+		let keys: Vec<&str> = problems
+			.iter()
+			.map(|problem| problem.split(':').next().unwrap())
+			.collect();
+
+		for key in &[
+			"server.host",
+			"server.port",
+			"server.reload_interval",
+			"server.memory_limit",
+			"plans.directory",
+			"datasets.missing.packing_list",
+			"datasets.listed.packing_list",
+			"datasets.listed.tables.p9",
+			"datasets.listed.tables.p2.schema",
+			"shapefiles.blocks.file",
+			"shapefiles.unknown",
+			"sessions.session.datasets",
+			"sessions.session.shapefiles",
+		] {
+			assert!(keys.contains(key), "{} not in {:#?}", key, problems);
+		}
+
+		assert!(!keys.contains(&"datasets.listed.tables.p1.schema"));
+		assert!(problems.contains(&"sessions.session.datasets: no dataset named absent".to_string()));
+		assert!(!problems.contains(&"sessions.session.datasets: no dataset named missing".to_string()));
 	}
 }
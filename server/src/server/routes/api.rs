@@ -1,87 +1,499 @@
-use shapefiles::{Shapefile, ShapefileConfiguration};
+use datasets::DatasetConfiguration;
+use plans::{PlanId, PlanStore};
+use resources::{Resources, Store};
+use sessions::SessionConfiguration;
+use shapefiles::ShapefileConfiguration;
 
-use std::collections::HashMap;
+use std::sync::Arc;
 
 use once_cell::sync::OnceCell;
 use warp::Filter;
 
+mod compression;
+pub mod computations;
+mod conditional;
+pub mod datasets;
+pub mod plans;
+pub mod resources;
+mod responses;
+pub mod sessions;
 pub mod shapefiles;
+pub mod validation;
 
 mod cache {
-	use super::{OnceCell, Shapefile, ShapefileConfiguration};
-	use std::collections::HashMap;
-	use std::convert::TryInto;
-
-	static CACHE: OnceCell<HashMap<String, Shapefile>> = OnceCell::new();
-
-	pub(super) fn get_cache(cfg: &config::Config) -> &'static HashMap<String, Shapefile> {
-		let mut cache = HashMap::new();
-
-		if let Ok(configuration) = cfg.get_table("shapefiles") {
-			configuration
-				.iter()
-				.filter_map(|(id, value)| -> Option<(String, Shapefile)> {
-					// TODO(rye): avoid clone by iterating over keys and using remove?
-					let value: config::Value = value.clone();
-					// TODO(rye): handle error a bit better
-					let config: ShapefileConfiguration = value.try_into().expect("invalid configuration");
-					let shapefile: distringo::Result<Shapefile> = config.try_into();
-					if let Ok(shapefile) = shapefile {
-						Some((id.to_string(), shapefile))
-					} else {
-						log::warn!("Error parsing shapefile {}: {:?}", id, shapefile);
-						None
-					}
-				})
-				.for_each(|(id, shp): (String, Shapefile)| {
-					cache.insert(id, shp);
-				});
-		};
+	use super::{OnceCell, PlanStore, Resources, Store};
+
+	static RESOURCES: OnceCell<Store> = OnceCell::new();
+	static PLANS: OnceCell<PlanStore> = OnceCell::new();
+
+	pub(super) fn resources(cfg: &config::Config) -> distringo::Result<&'static Store> {
+		let store = Store::from(Resources::load(cfg, &Resources::default())?);
+
+		RESOURCES
+			.set(store)
+			.ok()
+			.expect("cache already initialized");
+
+		Ok(RESOURCES.get().unwrap())
+	}
+
+	pub(super) fn loaded() -> Option<&'static Store> {
+		RESOURCES.get()
+	}
+
+	pub(super) fn plans(cfg: &config::Config) -> distringo::Result<&'static PlanStore> {
+		let store = PlanStore::open(cfg.get_str("plans.directory")?)?;
 
-		CACHE.set(cache).expect("cache already initialized");
+		PLANS.set(store).expect("cache already initialized");
 
-		CACHE.get().unwrap()
+		Ok(PLANS.get().unwrap())
 	}
 }
 
 pub fn shapefiles(
-	cfg: &config::Config,
+	store: &'static Store,
+	plan_store: &'static PlanStore,
 ) -> distringo::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
-	let loaded_shapefiles: &'static HashMap<String, Shapefile> = cache::get_cache(cfg);
-
 	// GET /api/v0/shapefiles
 	let shapefiles_index = warp::get()
 		.and(warp::path::end())
-		.map(move || shapefiles::index(loaded_shapefiles));
+		.and(resources::current(store))
+		.map(|resources: Arc<Resources>| shapefiles::index(resources.shapefiles()));
 
 	// GET /api/v0/shapefiles/:id
+	//
+	// Bodies are kept compressed, so byte ranges are of whichever
+	// coding was negotiated.
 	let shapefiles_show = warp::get()
 		.and(warp::path!(String))
-		.map(move |id: String| shapefiles::show(loaded_shapefiles, &id))
-		.with(warp::compression::gzip());
+		.and(warp::query())
+		.and(conditional::conditions())
+		.and(compression::accepted())
+		.and(resources::current(store))
+		.and_then(
+			|id: String, parameters, conditions, encoding, resources: Arc<Resources>| {
+				resources::blocking(move || {
+					shapefiles::show(
+						resources.shapefiles(),
+						&id,
+						parameters,
+						&conditions,
+						encoding,
+					)
+				})
+			},
+		);
+
+	// GET /api/v0/shapefiles/:id/features/:geoid
+	let shapefiles_feature = warp::get()
+		.and(warp::path!(String / "features" / String))
+		.and(conditional::conditions())
+		.and(resources::current(store))
+		.and_then(
+			|id: String, geoid: String, conditions, resources: Arc<Resources>| {
+				resources::blocking(move || {
+					shapefiles::feature(resources.shapefiles(), &id, &geoid, &conditions)
+				})
+			},
+		);
+
+	// GET /api/v0/shapefiles/:id/locate?lat=&lon=
+	let shapefiles_locate = warp::get()
+		.and(warp::path!(String / "locate"))
+		.and(warp::query())
+		.and(conditional::conditions())
+		.and(resources::current(store))
+		.and_then(
+			move |id: String, parameters, conditions, resources: Arc<Resources>| {
+				resources::blocking(move || {
+					shapefiles::locate(
+						resources.shapefiles(),
+						resources.datasets(),
+						plan_store,
+						&id,
+						parameters,
+						&conditions,
+					)
+				})
+			},
+		);
+
+	// GET /api/v0/shapefiles/:id/tiles/:z/:x/:y.mvt
+	let shapefiles_tile = warp::get()
+		.and(warp::path!(String / "tiles" / u8 / u32 / String))
+		.and(warp::query())
+		.and(conditional::conditions())
+		.and(resources::current(store))
+		.and_then(
+			|id: String, z: u8, x: u32, y: String, parameters, conditions, resources: Arc<Resources>| {
+				resources::blocking(move || {
+					shapefiles::tile(
+						resources.shapefiles(),
+						resources.datasets(),
+						&id,
+						(z, x, &y),
+						parameters,
+						&conditions,
+					)
+				})
+			},
+		);
 
 	// ... /api/v0/shapefiles/...
 	let shapefiles = warp::any()
 		.and(warp::path!("shapefiles" / ..))
-		.and(shapefiles_index.or(shapefiles_show))
+		.and(
+			shapefiles_index
+				.or(shapefiles_show)
+				.or(shapefiles_feature)
+				.or(shapefiles_locate)
+				.or(shapefiles_tile),
+		)
 		.boxed();
 
 	Ok(shapefiles)
 }
 
+pub fn datasets(
+	store: &'static Store,
+) -> distringo::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
+	// GET /api/v0/datasets
+	let datasets_index = warp::get()
+		.and(warp::path::end())
+		.and(conditional::conditions())
+		.and(resources::current(store))
+		.map(|conditions, resources: Arc<Resources>| {
+			datasets::index(resources.datasets(), &conditions)
+		});
+
+	// GET /api/v0/datasets/:id
+	let datasets_show = warp::get()
+		.and(warp::path!(String))
+		.and(conditional::conditions())
+		.and(resources::current(store))
+		.and_then(|id: String, conditions, resources: Arc<Resources>| {
+			resources::blocking(move || datasets::show(resources.datasets(), &id, &conditions))
+		});
+
+	// GET /api/v0/datasets/:id/records/:logrecno
+	let datasets_record = warp::get()
+		.and(warp::path!(String / "records" / u64))
+		.and(conditional::conditions())
+		.and(resources::current(store))
+		.and_then(
+			|id: String, logrecno: u64, conditions, resources: Arc<Resources>| {
+				resources::blocking(move || {
+					datasets::record(resources.datasets(), &id, logrecno, &conditions)
+				})
+			},
+		);
+
+	// GET /api/v0/datasets/:id/geoids/:geoid
+	let datasets_geoid = warp::get()
+		.and(warp::path!(String / "geoids" / String))
+		.and(conditional::conditions())
+		.and(resources::current(store))
+		.and_then(
+			|id: String, geoid: String, conditions, resources: Arc<Resources>| {
+				resources::blocking(move || datasets::geoid(resources.datasets(), &id, &geoid, &conditions))
+			},
+		);
+
+	// GET /api/v0/datasets/:id/tables
+	let datasets_tables = warp::get()
+		.and(warp::path!(String / "tables"))
+		.and(conditional::conditions())
+		.and(resources::current(store))
+		.and_then(|id: String, conditions, resources: Arc<Resources>| {
+			resources::blocking(move || datasets::tables(resources.datasets(), &id, &conditions))
+		});
+
+	// GET /api/v0/datasets/:id/tables/:table
+	let datasets_table = warp::get()
+		.and(warp::path!(String / "tables" / String))
+		.and(conditional::conditions())
+		.and(resources::current(store))
+		.and_then(
+			|id: String, table: String, conditions, resources: Arc<Resources>| {
+				resources::blocking(move || datasets::table(resources.datasets(), &id, &table, &conditions))
+			},
+		);
+
+	// ... /api/v0/datasets/...
+	let datasets = warp::any()
+		.and(warp::path!("datasets" / ..))
+		.and(
+			datasets_index
+				.or(datasets_show)
+				.unify()
+				.or(datasets_record)
+				.unify()
+				.or(datasets_geoid)
+				.unify()
+				.or(datasets_tables)
+				.unify()
+				.or(datasets_table)
+				.unify(),
+		)
+		.boxed();
+
+	Ok(datasets)
+}
+
+pub fn sessions(
+	store: &'static Store,
+) -> distringo::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
+	// GET /api/v0/sessions
+	let sessions_index = warp::get()
+		.and(warp::path::end())
+		.and(resources::current(store))
+		.map(|resources: Arc<Resources>| sessions::index(resources.sessions()));
+
+	// GET /api/v0/sessions/:id
+	let sessions_show = warp::get()
+		.and(warp::path!(String))
+		.and(resources::current(store))
+		.map(|id: String, resources: Arc<Resources>| sessions::show(resources.sessions(), &id));
+
+	// ... /api/v0/sessions/...
+	let sessions = warp::any()
+		.and(warp::path!("sessions" / ..))
+		.and(sessions_index.or(sessions_show).unify())
+		.boxed();
+
+	Ok(sessions)
+}
+
+/// The largest request body accepted when creating or changing a plan, or
+/// computing on a set of geometries
+const PLAN_BODY_LIMIT: u64 = 16 * 1024 * 1024;
+
+fn json_body<T: serde::de::DeserializeOwned + Send>(
+) -> impl Filter<Extract = (T,), Error = warp::Rejection> + Clone {
+	warp::body::content_length_limit(PLAN_BODY_LIMIT).and(warp::body::json())
+}
+
+pub fn plans(
+	store: &'static PlanStore,
+	loaded: &'static Store,
+) -> distringo::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
+	// GET /api/v0/plans
+	let plans_index = warp::get()
+		.and(warp::path::end())
+		.map(move || plans::index(store));
+
+	// POST /api/v0/plans
+	let plans_create = warp::post()
+		.and(warp::path::end())
+		.and(json_body())
+		.and(resources::current(loaded))
+		.and_then(move |parameters, resources: Arc<Resources>| {
+			resources::blocking(move || plans::create(store, resources.context(), parameters))
+		});
+
+	// GET /api/v0/plans/:id
+	let plans_show = warp::get()
+		.and(warp::path!(PlanId))
+		.map(move |id| plans::show(store, id));
+
+	// PUT /api/v0/plans/:id
+	let plans_replace = warp::put()
+		.and(warp::path!(PlanId))
+		.and(json_body())
+		.and(resources::current(loaded))
+		.and_then(move |id, parameters, resources: Arc<Resources>| {
+			resources::blocking(move || plans::replace(store, resources.context(), id, parameters))
+		});
+
+	// PATCH /api/v0/plans/:id
+	let plans_reassign = warp::patch()
+		.and(warp::path!(PlanId))
+		.and(json_body())
+		.and(resources::current(loaded))
+		.and_then(move |id, reassignments, resources: Arc<Resources>| {
+			resources::blocking(move || plans::reassign(store, resources.context(), id, reassignments))
+		});
+
+	// GET /api/v0/plans/:id/districts.geojson
+	let plans_districts = warp::get()
+		.and(warp::path!(PlanId / "districts.geojson"))
+		.and(resources::current(loaded))
+		.and_then(move |id, resources: Arc<Resources>| {
+			resources::blocking(move || plans::districts::show(store, resources.context(), id))
+		})
+		.with(warp::compression::gzip());
+
+	// GET /api/v0/plans/:id/live (WebSocket)
+	let plans_live = warp::get()
+		.and(warp::path!(PlanId / "live"))
+		.and(warp::ws())
+		.map(move |id, ws| plans::live::upgrade(store, loaded, id, ws));
+
+	// DELETE /api/v0/plans/:id
+	let plans_delete = warp::delete()
+		.and(warp::path!(PlanId))
+		.map(move |id| plans::delete(store, id));
+
+	// ... /api/v0/plans/...
+	let plans = warp::any()
+		.and(warp::path!("plans" / ..))
+		.and(
+			plans_index
+				.or(plans_create)
+				.unify()
+				.or(plans_show)
+				.unify()
+				.or(plans_replace)
+				.unify()
+				.or(plans_reassign)
+				.unify()
+				.or(plans_live)
+				.unify()
+				.or(plans_delete)
+				.unify()
+				.or(plans_districts),
+		)
+		.boxed();
+
+	Ok(plans)
+}
+
+pub fn computations(
+	store: &'static Store,
+) -> distringo::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
+	// POST /api/v0/computations/convex_hull
+	let convex_hull = warp::post()
+		.and(warp::path!("convex_hull"))
+		.and(json_body())
+		.and(resources::current(store))
+		.and_then(|parameters, resources: Arc<Resources>| {
+			resources::blocking(move || {
+				computations::convex_hull(resources.shapefiles(), resources.datasets(), parameters)
+			})
+		});
+
+	// ... /api/v0/computations/...
+	let computations = warp::any()
+		.and(warp::path!("computations" / ..))
+		.and(convex_hull)
+		.boxed();
+
+	Ok(computations)
+}
+
 pub mod v0 {
-	pub use super::shapefiles;
+	pub use super::{computations, datasets, plans, sessions, shapefiles};
+}
+
+/// Check everything the API would load from a configuration, without loading
+/// any of it
+pub fn check(cfg: &config::Config, report: &mut validation::Report) {
+	/// The entries of a section, by key, for those that deserialize
+	fn entries<C: serde::de::DeserializeOwned>(
+		cfg: &config::Config,
+		section: &str,
+		report: &mut validation::Report,
+	) -> Vec<(String, C)> {
+		let mut configuration: Vec<(String, config::Value)> = match cfg.get_table(section) {
+			Ok(configuration) => configuration.into_iter().collect(),
+			Err(config::ConfigError::NotFound(_)) => Vec::new(),
+			Err(error) => {
+				report.push(section, error);
+				Vec::new()
+			}
+		};
+		configuration.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+		configuration
+			.into_iter()
+			.filter_map(|(id, value)| {
+				let key: String = format!("{}.{}", section, id);
+
+				match value.try_into::<C>() {
+					Ok(config) => Some((key, config)),
+					Err(error) => {
+						report.push(&key, error);
+						None
+					}
+				}
+			})
+			.collect()
+	}
+
+	let ids = |section: &str| -> Vec<String> {
+		cfg
+			.get_table(section)
+			.map(|configuration| configuration.into_keys().collect())
+			.unwrap_or_default()
+	};
+
+	for (key, config) in entries::<ShapefileConfiguration>(cfg, "shapefiles", report) {
+		config.check(&key, report);
+	}
+
+	for (key, config) in entries::<DatasetConfiguration>(cfg, "datasets", report) {
+		config.check(&key, report);
+	}
+
+	let (datasets, shapefiles) = (ids("datasets"), ids("shapefiles"));
+	for (key, config) in entries::<SessionConfiguration>(cfg, "sessions", report) {
+		config.check(&key, &datasets, &shapefiles, report);
+	}
+
+	if let Err(error) = resources::memory_limit(cfg) {
+		report.push("server.memory_limit", error);
+	}
+
+	match cfg.get_str("plans.directory") {
+		Ok(directory) if std::path::Path::new(&directory).is_file() => report.push(
+			"plans.directory",
+			format_args!("{} is not a directory", directory),
+		),
+		Ok(_) => {}
+		Err(error) => report.push("plans.directory", error),
+	}
+}
+
+/// Swap in the resources of a configuration, loading only what has changed
+///
+/// Does nothing if the API hasn't been built yet.
+pub fn reload(cfg: &config::Config) -> distringo::Result<()> {
+	match cache::loaded() {
+		Some(store) => store.reload(cfg),
+		None => Ok(()),
+	}
+}
+
+/// Whether any file the loaded resources came from has changed since
+pub fn stale() -> bool {
+	cache::loaded().is_some_and(Store::stale)
 }
 
 pub fn api(
 	cfg: &config::Config,
 ) -> distringo::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
-	let shapefiles = shapefiles(cfg)?;
+	let store: &'static Store = cache::resources(cfg)?;
+	let plan_store: &'static PlanStore = cache::plans(cfg)?;
+
+	let shapefiles = shapefiles(store, plan_store)?;
+	let datasets = datasets(store)?;
+	let sessions = sessions(store)?;
+	let plans = plans(plan_store, store)?;
+	let computations = computations(store)?;
 
 	let api = warp::path("api");
 	let api_v0 = api.and(warp::path("v0"));
 
-	let gets = shapefiles;
+	let endpoints = shapefiles
+		.or(datasets)
+		.or(sessions)
+		.or(plans)
+		.or(computations);
 
-	Ok(warp::any().and(api_v0.or(api).unify()).and(gets).boxed())
+	Ok(
+		warp::any()
+			.and(api_v0.or(api).unify())
+			.and(endpoints)
+			.boxed(),
+	)
 }
@@ -0,0 +1,215 @@
+//! Content codings for response bodies
+//!
+//! Bodies are compressed as they're written, so that large ones are never
+//! held whole before they're compressed.
+
+use std::io::Write;
+
+use hyper::body::Bytes;
+use warp::Filter;
+
+/// The gzip compression level: the best there is
+const GZIP_LEVEL: u32 = 9;
+
+/// The Brotli quality, which is a little short of the best; the best is
+/// impractically slow on whole states' worth of blocks.
+const BROTLI_QUALITY: u32 = 9;
+
+/// The base-2 logarithm of the Brotli window size
+const BROTLI_WINDOW: u32 = 22;
+
+/// A content coding a response can be sent with
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Encoding {
+	#[default]
+	Identity,
+	Gzip,
+	Brotli,
+}
+
+impl Encoding {
+	/// Codings that can be offered, in order of preference when the client
+	/// doesn't have one
+	const PREFERENCE: [Self; 3] = [Self::Brotli, Self::Gzip, Self::Identity];
+
+	fn token(self) -> &'static str {
+		match self {
+			Self::Identity => "identity",
+			Self::Gzip => "gzip",
+			Self::Brotli => "br",
+		}
+	}
+
+	/// Pick the coding to use for an `Accept-Encoding` header
+	///
+	/// The coding with the highest quality value is chosen, with ties broken
+	/// by [`Self::PREFERENCE`].  Without the header, no coding is used.
+	pub(crate) fn negotiate(accept_encoding: Option<&str>) -> Self {
+		let accept_encoding: &str = match accept_encoding {
+			Some(accept_encoding) => accept_encoding,
+			None => return Self::Identity,
+		};
+
+		let preferences: Vec<(&str, f32)> = accept_encoding
+			.split(',')
+			.filter_map(|coding| {
+				let mut parts = coding.split(';').map(str::trim);
+				let name: &str = parts.next().filter(|name| !name.is_empty())?;
+				let quality: f32 = parts
+					.find_map(|parameter| parameter.strip_prefix("q="))
+					.map_or(Some(1.0), |quality| quality.parse().ok())?;
+
+				Some((name, quality))
+			})
+			.collect();
+
+		let quality = |encoding: Self| -> f32 {
+			let named = preferences
+				.iter()
+				.find(|(name, _)| name.eq_ignore_ascii_case(encoding.token()));
+			let wildcard = preferences.iter().find(|(name, _)| *name == "*");
+
+			match (named, wildcard, encoding) {
+				(Some((_, quality)), _, _) => *quality,
+				(None, Some((_, quality)), _) => *quality,
+				// Identity is acceptable unless it's ruled out.
+				(None, None, Self::Identity) => 0.001,
+				(None, None, _) => 0.0,
+			}
+		};
+
+		Self::PREFERENCE
+			.iter()
+			.copied()
+			.map(|encoding| (encoding, quality(encoding)))
+			.filter(|(_, quality)| *quality > 0.0)
+			.fold(
+				None,
+				|best: Option<(Self, f32)>, (encoding, quality)| match best {
+					Some((_, best_quality)) if best_quality >= quality => best,
+					_ => Some((encoding, quality)),
+				},
+			)
+			.map_or(Self::Identity, |(encoding, _)| encoding)
+	}
+
+	/// Compress whatever `write` writes with this coding
+	pub(crate) fn encode_with<F>(self, write: F) -> Bytes
+	where
+		F: FnOnce(&mut dyn Write) -> std::io::Result<()>,
+	{
+		match self {
+			Self::Identity => {
+				let mut data: Vec<u8> = Vec::new();
+				write(&mut data).expect("failed to write in memory");
+				data.into()
+			}
+			Self::Gzip => {
+				let mut writer = std::io::BufWriter::new(flate2::write::GzEncoder::new(
+					Vec::new(),
+					flate2::Compression::new(GZIP_LEVEL),
+				));
+				write(&mut writer).expect("failed to compress in memory");
+				writer
+					.into_inner()
+					.map_err(std::io::IntoInnerError::into_error)
+					.and_then(flate2::write::GzEncoder::finish)
+					.expect("failed to compress in memory")
+					.into()
+			}
+			Self::Brotli => {
+				let mut compressed: Vec<u8> = Vec::new();
+				{
+					let mut writer = std::io::BufWriter::new(brotli::CompressorWriter::new(
+						&mut compressed,
+						4096,
+						BROTLI_QUALITY,
+						BROTLI_WINDOW,
+					));
+					write(&mut writer).expect("failed to compress in memory");
+					writer.flush().expect("failed to compress in memory");
+				}
+				compressed.into()
+			}
+		}
+	}
+
+	/// Add the headers describing a body sent with this coding
+	pub(crate) fn headers(self, response: http::response::Builder) -> http::response::Builder {
+		let response = response.header(hyper::header::VARY, "accept-encoding");
+
+		match self {
+			Self::Identity => response,
+			_ => response.header(hyper::header::CONTENT_ENCODING, self.token()),
+		}
+	}
+}
+
+/// Extract the coding to respond to a request with
+pub(crate) fn accepted(
+) -> impl Filter<Extract = (Encoding,), Error = std::convert::Infallible> + Clone {
+	warp::header::headers_cloned().map(|headers: http::HeaderMap| {
+		let accept_encoding: Option<&str> = headers
+			.get(hyper::header::ACCEPT_ENCODING)
+			.and_then(|value| value.to_str().ok());
+
+		Encoding::negotiate(accept_encoding)
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Encoding;
+	use std::io::Read;
+
+	#[test]
+	fn negotiation_follows_quality_values() {
+		let negotiate = |header: &str| Encoding::negotiate(Some(header));
+
+		assert_eq!(Encoding::negotiate(None), Encoding::Identity);
+		assert_eq!(negotiate("gzip, deflate, br"), Encoding::Brotli);
+		assert_eq!(negotiate("gzip, deflate"), Encoding::Gzip);
+		assert_eq!(negotiate("br;q=0.5, gzip"), Encoding::Gzip);
+		assert_eq!(negotiate("br;q=0, *"), Encoding::Gzip);
+		assert_eq!(negotiate("deflate"), Encoding::Identity);
+		assert_eq!(negotiate(""), Encoding::Identity);
+	}
+
+	#[test]
+	fn every_coding_round_trips() {
+		let data: Vec<u8> = b"{\"type\":\"FeatureCollection\",\"features\":[]}".repeat(64);
+		let encode = |encoding: Encoding| encoding.encode_with(|writer| writer.write_all(&data));
+
+		let identity = encode(Encoding::Identity);
+		assert_eq!(identity.as_ref(), data.as_slice());
+
+		let gzip = encode(Encoding::Gzip);
+		let mut decompressed = Vec::new();
+		flate2::read::GzDecoder::new(gzip.as_ref())
+			.read_to_end(&mut decompressed)
+			.unwrap();
+		assert_eq!(decompressed, data);
+
+		let brotli = encode(Encoding::Brotli);
+		let mut decompressed = Vec::new();
+		brotli::Decompressor::new(brotli.as_ref(), 4096)
+			.read_to_end(&mut decompressed)
+			.unwrap();
+		assert_eq!(decompressed, data);
+
+		assert!(gzip.len() < data.len() && brotli.len() < data.len());
+	}
+
+	#[test]
+	fn bodies_can_be_written_in_pieces() {
+		let data: Vec<u8> = b"{\"type\":\"Feature\"}".repeat(64);
+
+		for encoding in [Encoding::Identity, Encoding::Gzip, Encoding::Brotli] {
+			let pieces =
+				encoding.encode_with(|writer| data.chunks(7).try_for_each(|piece| writer.write_all(piece)));
+
+			let whole = encoding.encode_with(|writer| writer.write_all(&data));
+			assert_eq!(pieces, whole);
+		}
+	}
+}
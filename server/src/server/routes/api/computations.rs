@@ -0,0 +1,241 @@
+use std::{collections::HashMap, sync::Arc};
+
+use geo::{
+	algorithm::{bounding_rect::BoundingRect, centroid::Centroid, contains::Contains},
+	MultiPolygon, Polygon,
+};
+use hyper::body::Body;
+
+use super::{
+	datasets::Dataset,
+	resources::Lazy,
+	responses::{json_response, unprocessable},
+	shapefiles::Shapefile,
+};
+
+/// The body of a convex hull computation
+///
+/// The hull is taken around `polygons` (GeoJSON Polygon or MultiPolygon
+/// geometries) and the blocks in `blocks`, which are looked up in
+/// `shapefile`.  If both a `shapefile` and a `dataset` are given, the
+/// population of the blocks whose centroids lie in the hull is included too.
+#[derive(Debug, serde::Deserialize)]
+pub struct ConvexHullParameters {
+	#[serde(default)]
+	polygons: Vec<geojson::Geometry>,
+	#[serde(default)]
+	blocks: Vec<String>,
+	shapefile: Option<String>,
+	dataset: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct ConvexHullProperties {
+	/// The area of the hull, in square meters
+	area: f64,
+	population: Option<u64>,
+}
+
+/// The total population of the features whose centroids lie in `hull`
+fn enclosed_population(
+	hull: &Polygon<f64>,
+	shapefile: &Shapefile,
+	populations: &HashMap<String, u64>,
+) -> u64 {
+	let bounds = match hull.bounding_rect() {
+		Some(bounds) => bounds,
+		None => return 0,
+	};
+
+	shapefile
+		.features()
+		.iter()
+		.filter_map(|(geoid, polygons)| {
+			let centroid = polygons.centroid()?;
+
+			let in_bounds: bool = centroid.x() >= bounds.min().x
+				&& centroid.x() <= bounds.max().x
+				&& centroid.y() >= bounds.min().y
+				&& centroid.y() <= bounds.max().y;
+
+			if in_bounds && hull.contains(&centroid) {
+				populations.get(geoid)
+			} else {
+				None
+			}
+		})
+		.sum()
+}
+
+pub fn convex_hull(
+	shapefiles: &Lazy<Shapefile>,
+	datasets: &Lazy<Dataset>,
+	parameters: ConvexHullParameters,
+) -> hyper::Response<Body> {
+	let shapefile: Option<Arc<Shapefile>> = match &parameters.shapefile {
+		Some(id) => match shapefiles.fetch(id) {
+			Ok(shapefile) => Some(shapefile),
+			Err(response) if response.status() == hyper::StatusCode::NOT_FOUND => {
+				return unprocessable(&format!("unknown shapefile {}", id))
+			}
+			Err(response) => return *response,
+		},
+		None => None,
+	};
+
+	let dataset: Option<Arc<Dataset>> = match &parameters.dataset {
+		Some(id) => match datasets.fetch(id) {
+			Ok(dataset) => Some(dataset),
+			Err(response) if response.status() == hyper::StatusCode::NOT_FOUND => {
+				return unprocessable(&format!("unknown dataset {}", id))
+			}
+			Err(response) => return *response,
+		},
+		None => None,
+	};
+
+	let mut polygons: Vec<MultiPolygon<f64>> = Vec::with_capacity(parameters.polygons.len());
+	for geometry in &parameters.polygons {
+		match distringo::geometry::multi_polygon(geometry) {
+			Some(geometry) => polygons.push(geometry),
+			None => return unprocessable("only Polygon and MultiPolygon geometries are supported"),
+		}
+	}
+
+	let mut blocks: Vec<&MultiPolygon<f64>> = Vec::with_capacity(parameters.blocks.len());
+	for geoid in &parameters.blocks {
+		let shapefile: &Shapefile = match shapefile.as_deref() {
+			Some(shapefile) => shapefile,
+			None => return unprocessable("blocks need a shapefile to be looked up in"),
+		};
+
+		match shapefile.polygons(geoid) {
+			Some(geometry) => blocks.push(geometry),
+			None => return unprocessable(&format!("unknown block {}", geoid)),
+		}
+	}
+
+	let hull: Polygon<f64> = match distringo::geometry::convex_hull(polygons.iter().chain(blocks)) {
+		Some(hull) => hull,
+		None => return unprocessable("no polygons or blocks were given"),
+	};
+
+	let population: Option<u64> = match (shapefile.as_deref(), dataset.as_deref()) {
+		(Some(shapefile), Some(dataset)) => {
+			Some(enclosed_population(&hull, shapefile, dataset.populations()))
+		}
+		_ => None,
+	};
+
+	let hull = MultiPolygon(vec![hull]);
+	let properties = ConvexHullProperties {
+		area: distringo::geometry::area(&hull),
+		population,
+	};
+
+	let feature = geojson::Feature {
+		bbox: None,
+		geometry: Some(geojson::Geometry::new(geojson::Value::from(&hull.0[0]))),
+		id: None,
+		properties: match serde_json::to_value(&properties) {
+			Ok(serde_json::Value::Object(properties)) => Some(properties),
+			_ => None,
+		},
+		foreign_members: None,
+	};
+
+	json_response(hyper::StatusCode::OK, &feature)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::ConvexHullParameters;
+	use crate::server::routes::api::{
+		resources::Lazy,
+		shapefiles::{Shapefile, ShapefileType},
+	};
+	use std::{collections::HashMap, sync::Arc};
+
+	fn shapefiles() -> Lazy<Shapefile> {
+		let blocks: geojson::GeoJson = "{\"type\":\"FeatureCollection\",\"features\":[\
+			{\"type\":\"Feature\",\"properties\":{\"GEOID10\":\"a\"},\"geometry\":{\"type\":\"Polygon\",\
+			\"coordinates\":[[[0.0,0.0],[1.0,0.0],[1.0,1.0],[0.0,1.0],[0.0,0.0]]]}},\
+			{\"type\":\"Feature\",\"properties\":{\"GEOID10\":\"b\"},\"geometry\":{\"type\":\"Polygon\",\
+			\"coordinates\":[[[2.0,2.0],[3.0,2.0],[3.0,3.0],[2.0,3.0],[2.0,2.0]]]}}\
+			]}"
+			.parse()
+			.unwrap();
+
+		let mut shapefiles = HashMap::new();
+		shapefiles.insert(
+			"blocks".to_string(),
+			Arc::new(Shapefile::new(ShapefileType::TabularBlock, blocks).unwrap()),
+		);
+		shapefiles.into()
+	}
+
+	async fn hull(parameters: &str) -> (hyper::StatusCode, serde_json::Value) {
+		let parameters: ConvexHullParameters = serde_json::from_str(parameters).unwrap();
+		let response = super::convex_hull(&shapefiles(), &Lazy::default(), parameters);
+
+		let status = response.status();
+		let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+		(status, serde_json::from_slice(&body).unwrap())
+	}
+
+	#[tokio::test]
+	async fn blocks_and_polygons_agree() {
+		let (status, by_blocks) = hull("{\"shapefile\":\"blocks\",\"blocks\":[\"a\",\"b\"]}").await;
+		assert_eq!(status, hyper::StatusCode::OK);
+		assert_eq!(by_blocks["type"], "Feature");
+		assert_eq!(by_blocks["geometry"]["type"], "Polygon");
+		assert_eq!(
+			by_blocks["properties"]["population"],
+			serde_json::Value::Null
+		);
+
+		let (_, by_polygons) = hull(
+			"{\"polygons\":[\
+			{\"type\":\"Polygon\",\"coordinates\":[[[0.0,0.0],[1.0,0.0],[1.0,1.0],[0.0,1.0],[0.0,0.0]]]},\
+			{\"type\":\"Polygon\",\"coordinates\":[[[2.0,2.0],[3.0,2.0],[3.0,3.0],[2.0,3.0],[2.0,2.0]]]}\
+			]}",
+		)
+		.await;
+
+		assert_eq!(by_blocks["geometry"], by_polygons["geometry"]);
+		assert!(by_blocks["properties"]["area"].as_f64().unwrap() > 0.0);
+	}
+
+	#[test]
+	fn population_is_counted_by_centroid() {
+		let shapefile = shapefiles().get("blocks").unwrap();
+		let hull = distringo::geometry::convex_hull(shapefile.polygons("a")).unwrap();
+
+		let mut populations = HashMap::new();
+		populations.insert("a".to_string(), 5);
+		populations.insert("b".to_string(), 7);
+
+		assert_eq!(
+			super::enclosed_population(&hull, &shapefile, &populations),
+			5
+		);
+	}
+
+	#[tokio::test]
+	async fn bad_parameters_are_unprocessable() {
+		for parameters in &[
+			"{}",
+			"{\"blocks\":[\"a\"]}",
+			"{\"shapefile\":\"blocks\",\"blocks\":[\"z\"]}",
+			"{\"dataset\":\"unknown\",\"blocks\":[\"a\"]}",
+			"{\"polygons\":[{\"type\":\"Point\",\"coordinates\":[0.0,0.0]}]}",
+		] {
+			assert_eq!(
+				hull(parameters).await.0,
+				hyper::StatusCode::UNPROCESSABLE_ENTITY,
+				"{}",
+				parameters
+			);
+		}
+	}
+}
@@ -0,0 +1,319 @@
+//! Validators and conditional requests
+//!
+//! Responses carry a strong `ETag`, and a `Last-Modified` date where one is
+//! known.  Bodies are tagged with a hash of what they are made from, so that
+//! they needn't be made to be revalidated.  Requests
+//! revalidating a cached copy get `304 Not Modified`, and a byte range of a
+//! response can be asked for to resume a download.
+
+use core::{
+	hash::{Hash, Hasher},
+	ops::Bound,
+};
+use std::time::SystemTime;
+
+use headers::{
+	AcceptRanges, ContentRange, ETag, HeaderMapExt, IfModifiedSince, IfNoneMatch, IfRange,
+	LastModified, Range,
+};
+use hyper::body::{Body, Bytes};
+use warp::Filter;
+
+use super::responses::{empty_response, Refusal};
+
+/// A hash of some data
+pub(crate) fn hash(data: &[u8]) -> u64 {
+	let mut hasher = fnv::FnvHasher::default();
+	hasher.write(data);
+	hasher.finish()
+}
+
+/// The entity tag of a body made from `parts`, which must include the hash
+/// of every resource it's made from and whatever of the request goes into it
+pub(crate) fn derived_etag<H: Hash>(parts: &H) -> ETag {
+	let mut hasher = fnv::FnvHasher::default();
+	parts.hash(&mut hasher);
+
+	format!("\"{:016x}\"", hasher.finish())
+		.parse()
+		.expect("invalid entity tag")
+}
+
+/// The modification time of a file, if the platform keeps one
+pub(crate) fn modified<P: AsRef<std::path::Path>>(path: P) -> Option<SystemTime> {
+	std::fs::metadata(path)
+		.and_then(|metadata| metadata.modified())
+		.ok()
+}
+
+/// The conditional headers of a request
+#[derive(Clone, Debug, Default)]
+pub struct Conditions {
+	if_none_match: Option<IfNoneMatch>,
+	if_modified_since: Option<IfModifiedSince>,
+	range: Option<Range>,
+	if_range: Option<IfRange>,
+}
+
+impl Conditions {
+	pub(crate) fn from_headers(headers: &http::HeaderMap) -> Self {
+		Self {
+			if_none_match: headers.typed_get(),
+			if_modified_since: headers.typed_get(),
+			range: headers.typed_get(),
+			if_range: headers.typed_get(),
+		}
+	}
+
+	/// Whether the client's cached copy is still good
+	///
+	/// `If-Modified-Since` is only considered without `If-None-Match`.
+	fn not_modified(&self, etag: &ETag, last_modified: Option<SystemTime>) -> bool {
+		match (&self.if_none_match, &self.if_modified_since, last_modified) {
+			(Some(if_none_match), _, _) => !if_none_match.precondition_passes(etag),
+			(None, Some(since), Some(last_modified)) => !since.is_modified(last_modified),
+			_ => false,
+		}
+	}
+
+	/// The byte range asked for, as `Ok(None)` for the whole thing, or `Err` if
+	/// it lies outside of `length` bytes
+	///
+	/// Asking for more than one range gets the whole thing, as does an
+	/// `If-Range` that no longer matches.
+	fn range(
+		&self,
+		length: u64,
+		etag: &ETag,
+		last_modified: Option<SystemTime>,
+	) -> Result<Option<(u64, u64)>, ()> {
+		let range: &Range = match &self.range {
+			Some(range) => range,
+			None => return Ok(None),
+		};
+
+		let last_modified: Option<LastModified> = last_modified.map(LastModified::from);
+		if let Some(if_range) = &self.if_range {
+			if if_range.is_modified(Some(etag), last_modified.as_ref()) {
+				return Ok(None);
+			}
+		}
+
+		let bounds: Vec<(Bound<u64>, Bound<u64>)> = range.iter().collect();
+		let (start, end): (u64, u64) = match bounds.as_slice() {
+			[(Bound::Included(start), Bound::Included(end))] => {
+				(*start, (*end).min(length.saturating_sub(1)))
+			}
+			[(Bound::Included(start), Bound::Unbounded)] => (*start, length.saturating_sub(1)),
+			[(Bound::Unbounded, Bound::Included(suffix))] if *suffix > 0 => {
+				(length.saturating_sub(*suffix), length.saturating_sub(1))
+			}
+			[_] => return Err(()),
+			_ => return Ok(None),
+		};
+
+		if length == 0 || start > end || start >= length {
+			Err(())
+		} else {
+			Ok(Some((start, end)))
+		}
+	}
+}
+
+/// Extract the conditional headers of a request
+pub(crate) fn conditions(
+) -> impl Filter<Extract = (Conditions,), Error = std::convert::Infallible> + Clone {
+	warp::header::headers_cloned().map(|headers: http::HeaderMap| Conditions::from_headers(&headers))
+}
+
+/// Respond with the body `make` makes, or as little of it as the request's
+/// conditions allow
+///
+/// `response` has the status and headers of a full response.  The body isn't
+/// made at all for a client whose copy is still good.  If making it fails,
+/// the response it fails with is sent instead.
+pub(crate) fn respond_with<F>(
+	conditions: &Conditions,
+	mut response: http::response::Builder,
+	etag: &ETag,
+	last_modified: Option<SystemTime>,
+	make: F,
+) -> hyper::Response<Body>
+where
+	F: FnOnce() -> Result<Bytes, Refusal>,
+{
+	if let Some(headers) = response.headers_mut() {
+		headers.typed_insert(etag.clone());
+		headers.typed_insert(AcceptRanges::bytes());
+		if let Some(last_modified) = last_modified {
+			headers.typed_insert(LastModified::from(last_modified));
+		}
+	}
+
+	if conditions.not_modified(etag, last_modified) {
+		return response
+			.status(hyper::StatusCode::NOT_MODIFIED)
+			.body(Body::empty())
+			.unwrap();
+	}
+
+	let data: Bytes = match make() {
+		Ok(data) => data,
+		Err(response) => return *response,
+	};
+
+	let length: u64 = data.len() as u64;
+	match conditions.range(length, etag, last_modified) {
+		Ok(None) => response.body(data.into()).unwrap(),
+		Ok(Some((start, end))) => {
+			if let Some(headers) = response.headers_mut() {
+				headers
+					.typed_insert(ContentRange::bytes(start..=end, length).expect("invalid content range"));
+			}
+
+			response
+				.status(hyper::StatusCode::PARTIAL_CONTENT)
+				.body(data.slice(start as usize..=end as usize).into())
+				.unwrap()
+		}
+		Err(()) => {
+			let mut unsatisfiable = empty_response(hyper::StatusCode::RANGE_NOT_SATISFIABLE);
+			unsatisfiable
+				.headers_mut()
+				.typed_insert(ContentRange::unsatisfied_bytes(length));
+			unsatisfiable
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{derived_etag, respond_with, Conditions};
+	use hyper::body::Bytes;
+	use std::time::{Duration, SystemTime};
+
+	fn conditions(headers: &[(&'static str, &str)]) -> Conditions {
+		let mut map = http::HeaderMap::new();
+		for (name, value) in headers {
+			map.insert(*name, value.parse().unwrap());
+		}
+
+		Conditions::from_headers(&map)
+	}
+
+	async fn request(
+		headers: &[(&'static str, &str)],
+		last_modified: Option<SystemTime>,
+	) -> (hyper::StatusCode, http::HeaderMap, Bytes) {
+		let response = respond_with(
+			&conditions(headers),
+			http::response::Builder::new().status(hyper::StatusCode::OK),
+			&derived_etag(&"0123456789"),
+			last_modified,
+			|| Ok(Bytes::from_static(b"0123456789")),
+		);
+
+		let (parts, body) = response.into_parts();
+		let body = hyper::body::to_bytes(body).await.unwrap();
+		(parts.status, parts.headers, body)
+	}
+
+	#[tokio::test]
+	async fn matching_entity_tags_are_not_modified() {
+		let (status, headers, body) = request(&[], None).await;
+		assert_eq!(status, hyper::StatusCode::OK);
+		assert_eq!(body, "0123456789");
+
+		let tag = headers[hyper::header::ETAG].to_str().unwrap().to_string();
+		let (status, _, body) = request(&[("if-none-match", &tag)], None).await;
+		assert_eq!(status, hyper::StatusCode::NOT_MODIFIED);
+		assert!(body.is_empty());
+
+		let (status, _, _) = request(&[("if-none-match", "\"other\"")], None).await;
+		assert_eq!(status, hyper::StatusCode::OK);
+	}
+
+	#[tokio::test]
+	async fn unchanged_files_are_not_modified() {
+		let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+		let since = httpdate(modified);
+		let before = httpdate(modified - Duration::from_secs(60));
+
+		let (status, headers, _) = request(&[("if-modified-since", &since)], Some(modified)).await;
+		assert_eq!(status, hyper::StatusCode::NOT_MODIFIED);
+		assert_eq!(headers[hyper::header::LAST_MODIFIED], since.as_str());
+
+		let (status, _, _) = request(&[("if-modified-since", &before)], Some(modified)).await;
+		assert_eq!(status, hyper::StatusCode::OK);
+	}
+
+	fn httpdate(time: SystemTime) -> String {
+		let mut map = http::HeaderMap::new();
+		headers::HeaderMapExt::typed_insert(&mut map, headers::LastModified::from(time));
+		map[hyper::header::LAST_MODIFIED]
+			.to_str()
+			.unwrap()
+			.to_string()
+	}
+
+	#[tokio::test]
+	async fn ranges_are_partial_content() {
+		let (status, headers, body) = request(&[("range", "bytes=2-4")], None).await;
+		assert_eq!(status, hyper::StatusCode::PARTIAL_CONTENT);
+		assert_eq!(headers[hyper::header::CONTENT_RANGE], "bytes 2-4/10");
+		assert_eq!(body, "234");
+
+		let (_, _, body) = request(&[("range", "bytes=7-")], None).await;
+		assert_eq!(body, "789");
+
+		let (_, _, body) = request(&[("range", "bytes=-2")], None).await;
+		assert_eq!(body, "89");
+
+		let (status, headers, _) = request(&[("range", "bytes=20-30")], None).await;
+		assert_eq!(status, hyper::StatusCode::RANGE_NOT_SATISFIABLE);
+		assert_eq!(headers[hyper::header::CONTENT_RANGE], "bytes */10");
+	}
+
+	#[tokio::test]
+	async fn stale_ranges_get_everything() {
+		let (_, headers, _) = request(&[], None).await;
+		let tag = headers[hyper::header::ETAG].to_str().unwrap().to_string();
+
+		let (status, _, body) = request(&[("range", "bytes=2-4"), ("if-range", &tag)], None).await;
+		assert_eq!(status, hyper::StatusCode::PARTIAL_CONTENT);
+		assert_eq!(body, "234");
+
+		let (status, _, body) =
+			request(&[("range", "bytes=2-4"), ("if-range", "\"other\"")], None).await;
+		assert_eq!(status, hyper::StatusCode::OK);
+		assert_eq!(body, "0123456789");
+	}
+
+	#[tokio::test]
+	async fn fresh_copies_are_not_made_again() {
+		let etag = derived_etag(&("resource", 1));
+		assert_ne!(etag, derived_etag(&("resource", 2)));
+
+		let response = respond_with(
+			&conditions(&[]),
+			http::response::Builder::new().status(hyper::StatusCode::OK),
+			&etag,
+			None,
+			|| Ok(Bytes::from_static(b"body")),
+		);
+		assert_eq!(response.status(), hyper::StatusCode::OK);
+		let tag = response.headers()[hyper::header::ETAG]
+			.to_str()
+			.unwrap()
+			.to_string();
+
+		let response = respond_with(
+			&conditions(&[("if-none-match", &tag)]),
+			http::response::Builder::new().status(hyper::StatusCode::OK),
+			&etag,
+			None,
+			|| panic!("the body was made"),
+		);
+		assert_eq!(response.status(), hyper::StatusCode::NOT_MODIFIED);
+	}
+}
@@ -0,0 +1,795 @@
+use core::convert::TryFrom;
+
+use core::hash::{Hash, Hasher};
+
+use std::{
+	collections::{BTreeMap, HashMap},
+	path::{Path, PathBuf},
+	sync::{
+		atomic::{AtomicU64, AtomicUsize, Ordering},
+		Arc, Mutex,
+	},
+	time::SystemTime,
+};
+
+use distringo::{
+	Dataset as _, IndexedDataset, LogicalRecordNumber, PackingList, Schema, Table, TableDictionary,
+	TableSegmentLocation,
+};
+use hyper::body::{Body, Bytes};
+use once_cell::sync::OnceCell;
+
+use super::{
+	conditional::{self, Conditions},
+	resources::{FileBacked, Lazy, Resource},
+	responses::Refusal,
+	validation::Report,
+};
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+pub struct TableConfiguration {
+	schema: String,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+pub struct DatasetConfiguration {
+	/// The packing list, or for the 2000 redistricting files, which have
+	/// none, the geographical header file, e.g. `ingeo.upl`
+	packing_list: String,
+	#[serde(default)]
+	tables: Vec<HashMap<String, TableConfiguration>>,
+}
+
+impl DatasetConfiguration {
+	/// Note anything that would keep the dataset configured at `key` from
+	/// loading
+	///
+	/// The packing list is read, but none of the files it lists are.
+	pub fn check(&self, key: &str, report: &mut Report) {
+		let packing_list_key: String = format!("{}.packing_list", key);
+
+		let packing_list = match PackingList::from_file(&self.packing_list) {
+			Ok(packing_list) => packing_list,
+			Err(error) => {
+				report.push(
+					&packing_list_key,
+					format_args!("{}: {}", self.packing_list, error),
+				);
+				return;
+			}
+		};
+
+		let mut tabular_files: Vec<(&u32, &PathBuf)> = packing_list.tabular_files().iter().collect();
+		tabular_files.sort();
+
+		let files = core::iter::once(packing_list.geographical_header_file())
+			.chain(tabular_files.into_iter().map(|(_, file)| file));
+		for file in files {
+			if let Some(path) = packing_list.locate(file) {
+				report.require_file(&packing_list_key, path);
+			}
+		}
+
+		for (name, table) in self.tables.iter().flat_map(HashMap::iter) {
+			let table_key: String = format!("{}.tables.{}", key, name);
+
+			let id: Table = match packing_list.schema().table(name) {
+				Some(id) => id,
+				None => {
+					report.push(&table_key, "no such table in the dataset's schema");
+					continue;
+				}
+			};
+
+			match TableDictionary::from_file(id, &table.schema) {
+				Ok(dictionary) => {
+					let columns: usize = packing_list
+						.table_locations()
+						.get(&id)
+						.map_or(0, |locations| {
+							locations.iter().map(TableSegmentLocation::columns).sum()
+						});
+
+					if dictionary.columns().len() != columns {
+						report.push(
+							&format!("{}.schema", table_key),
+							format_args!(
+								"{} describes {} columns, but the dataset has {}",
+								table.schema,
+								dictionary.columns().len(),
+								columns
+							),
+						);
+					}
+				}
+				Err(error) => report.push(
+					&format!("{}.schema", table_key),
+					format_args!("{}: {}", table.schema, error),
+				),
+			}
+		}
+	}
+}
+
+impl FileBacked for DatasetConfiguration {
+	/// The packing list
+	fn path(&self) -> &Path {
+		Path::new(&self.packing_list)
+	}
+
+	/// The packing list, the files it lists and the table dictionaries
+	///
+	/// If the packing list can't be read, only it and the dictionaries are.
+	fn files(&self) -> Vec<PathBuf> {
+		let mut files: Vec<PathBuf> = vec![PathBuf::from(&self.packing_list)];
+
+		if let Ok(packing_list) = PackingList::from_file(&self.packing_list) {
+			let mut tabular_files: Vec<(&u32, &PathBuf)> = packing_list.tabular_files().iter().collect();
+			tabular_files.sort();
+
+			let listed = core::iter::once(packing_list.geographical_header_file())
+				.chain(tabular_files.into_iter().map(|(_, file)| file));
+			files.extend(listed.filter_map(|file| packing_list.locate(file)));
+		}
+
+		let mut dictionaries: Vec<PathBuf> = self
+			.tables
+			.iter()
+			.flat_map(HashMap::values)
+			.map(|table| PathBuf::from(&table.schema))
+			.collect();
+		dictionaries.sort();
+		files.extend(dictionaries);
+
+		files
+	}
+}
+
+/// A loaded and indexed dataset
+///
+/// `IndexedDataset` seeks on shared file handles when reading records, so
+/// access to it is serialized through a `Mutex`.
+pub struct Dataset {
+	schema: Schema,
+	rows: usize,
+	tables: Vec<Table>,
+	dictionaries: BTreeMap<String, TableDictionary>,
+	populations: OnceCell<HashMap<String, u64>>,
+	/// Roughly how many bytes `populations` takes up, once it's computed
+	populations_size: AtomicUsize,
+	/// A hash of the files the dataset was read from, which responses made
+	/// from it are tagged with
+	hash: u64,
+	/// Roughly how many bytes the indices of `inner` take up
+	index_size: usize,
+	inner: Mutex<IndexedDataset>,
+}
+
+impl core::fmt::Debug for Dataset {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		f.debug_struct("Dataset")
+			.field("schema", &self.schema)
+			.field("rows", &self.rows)
+			.field("tables", &self.tables)
+			.finish()
+	}
+}
+
+impl Dataset {
+	pub fn from_packing_list_file<P: AsRef<Path>>(path: P) -> distringo::Result<Self> {
+		let inner = IndexedDataset::from_packing_list_file(&path)?.index()?;
+
+		Ok(Self {
+			hash: hash_files(path.as_ref())?,
+			..Self::from(inner)
+		})
+	}
+
+	pub(crate) fn hash(&self) -> u64 {
+		self.hash
+	}
+
+	pub fn schema(&self) -> Schema {
+		self.schema
+	}
+
+	pub fn rows(&self) -> usize {
+		self.rows
+	}
+
+	pub fn tables(&self) -> &[Table] {
+		&self.tables
+	}
+
+	pub fn dictionaries(&self) -> &BTreeMap<String, TableDictionary> {
+		&self.dictionaries
+	}
+
+	/// Roughly how many bytes the dataset takes up
+	///
+	/// The records themselves stay on disk, so this is mostly the indices.
+	pub fn footprint(&self) -> usize {
+		self.index_size + self.populations_size.load(Ordering::Relaxed)
+	}
+
+	/// Load the data dictionary for `name` from the YAML file at `path`
+	///
+	/// The dictionary must describe exactly as many columns as the dataset has
+	/// for the table.
+	pub fn load_dictionary<P: AsRef<Path>>(&mut self, name: &str, path: P) -> distringo::Result<()> {
+		let table: Table = self
+			.schema
+			.table(name)
+			.ok_or(distringo::Error::InvalidTableName)?;
+
+		let dictionary = TableDictionary::from_file(table, &path)?;
+
+		let columns: usize = self
+			.inner
+			.get_mut()
+			.expect("dataset lock poisoned")
+			.table_locations(&table)
+			.ok_or(distringo::Error::InvalidTableName)?
+			.iter()
+			.map(distringo::TableSegmentLocation::columns)
+			.sum();
+
+		if dictionary.columns().len() != columns {
+			log::warn!(
+				"Dictionary for table {} has {} columns, but the dataset has {}",
+				table,
+				dictionary.columns().len(),
+				columns
+			);
+			return Err(distringo::Error::InvalidDictionary);
+		}
+
+		let mut hasher = fnv::FnvHasher::default();
+		(self.hash, name, std::fs::read(path)?).hash(&mut hasher);
+		self.hash = hasher.finish();
+
+		self.dictionaries.insert(table.to_string(), dictionary);
+
+		Ok(())
+	}
+
+	/// Read the logical record `logrecno` and split it into its tables
+	pub fn tables_for_logrecno(
+		&self,
+		logrecno: LogicalRecordNumber,
+	) -> distringo::Result<TableValues> {
+		let inner = self.inner.lock().expect("dataset lock poisoned");
+		let record = inner.get_logical_record(logrecno)?;
+
+		self
+			.tables
+			.iter()
+			.filter_map(|table| Some((table, inner.table_locations(table)?)))
+			.map(
+				|(table, locations)| -> distringo::Result<(String, Vec<serde_json::Number>)> {
+					let values: Vec<serde_json::Number> = record
+						.table(locations)
+						.ok_or(distringo::Error::InvalidLogicalRecordNumber)?
+						.iter()
+						.map(|value| value.parse::<serde_json::Number>())
+						.collect::<Result<Vec<serde_json::Number>, _>>()?;
+
+					Ok((table.to_string(), values))
+				},
+			)
+			.collect()
+	}
+
+	/// The population of every indexed GeoID
+	///
+	/// This reads every geographical header, so it is only computed the first
+	/// time it is needed.
+	pub fn populations(&self) -> &HashMap<String, u64> {
+		self.populations.get_or_init(|| {
+			let inner = self.inner.lock().expect("dataset lock poisoned");
+
+			let populations: HashMap<String, u64> = inner
+				.geoids()
+				.filter_map(|geoid| {
+					match inner
+						.get_header_for_geoid(geoid)
+						.and_then(|header| header.population())
+					{
+						Ok(population) => Some((geoid.to_string(), population)),
+						Err(error) => {
+							log::warn!("Couldn't read population of {}: {}", geoid, error);
+							None
+						}
+					}
+				})
+				.collect();
+
+			let size: usize = populations
+				.keys()
+				.map(|geoid| geoid.len() + core::mem::size_of::<(String, u64)>())
+				.sum();
+			self.populations_size.store(size, Ordering::Relaxed);
+
+			populations
+		})
+	}
+
+	/// Find the logical record number and name of a GeoID
+	pub fn header_for_geoid(&self, geoid: &str) -> distringo::Result<(LogicalRecordNumber, String)> {
+		let inner = self.inner.lock().expect("dataset lock poisoned");
+		let header = inner.get_header_for_geoid(geoid)?;
+
+		Ok((header.logrecno(), header.name().to_string()))
+	}
+}
+
+/// A hash of a packing list, and of the size and modification time of each
+/// file it lists
+fn hash_files(path: &Path) -> distringo::Result<u64> {
+	let packing_list = PackingList::from_file(path)?;
+
+	let mut tabular_files: Vec<(&u32, &std::path::PathBuf)> =
+		packing_list.tabular_files().iter().collect();
+	tabular_files.sort();
+
+	let mut hasher = fnv::FnvHasher::default();
+	std::fs::read(path)?.hash(&mut hasher);
+
+	let files = core::iter::once(packing_list.geographical_header_file())
+		.chain(tabular_files.into_iter().map(|(_, file)| file));
+	for file in files.filter_map(|file| packing_list.locate(file)) {
+		let metadata: std::fs::Metadata = std::fs::metadata(file)?;
+		(metadata.len(), metadata.modified().ok()).hash(&mut hasher);
+	}
+
+	Ok(hasher.finish())
+}
+
+/// A hash no other dataset has, for a dataset that wasn't read from files
+fn unique_hash() -> u64 {
+	static DATASETS: AtomicU64 = AtomicU64::new(0);
+
+	let mut hasher = fnv::FnvHasher::default();
+	(SystemTime::now(), DATASETS.fetch_add(1, Ordering::Relaxed)).hash(&mut hasher);
+	hasher.finish()
+}
+
+impl From<IndexedDataset> for Dataset {
+	fn from(inner: IndexedDataset) -> Self {
+		let mut tables: Vec<Table> = inner.tables().copied().collect();
+		tables.sort_by_key(ToString::to_string);
+
+		Self {
+			schema: inner.schema(),
+			rows: inner.rows(),
+			tables,
+			dictionaries: BTreeMap::new(),
+			populations: OnceCell::new(),
+			populations_size: AtomicUsize::new(0),
+			hash: unique_hash(),
+			index_size: inner.index_size(),
+			inner: Mutex::new(inner),
+		}
+	}
+}
+
+impl TryFrom<&DatasetConfiguration> for Dataset {
+	type Error = distringo::Error;
+
+	fn try_from(dc: &DatasetConfiguration) -> distringo::Result<Self> {
+		let mut dataset = Self::from_packing_list_file(&dc.packing_list)?;
+
+		for (name, table) in dc.tables.iter().flat_map(HashMap::iter) {
+			dataset.load_dictionary(name, &table.schema)?;
+		}
+
+		Ok(dataset)
+	}
+}
+
+impl Resource for Dataset {
+	type Configuration = DatasetConfiguration;
+
+	fn load(id: &str, configuration: &DatasetConfiguration) -> distringo::Result<Self> {
+		log::info!("Indexing dataset {}", id);
+
+		Self::try_from(configuration)
+	}
+
+	fn footprint(&self) -> usize {
+		Dataset::footprint(self)
+	}
+}
+
+/// The cells of each table of a record
+///
+/// Most cells are counts, but some tables hold decimals, such as the median
+/// ages and average household sizes of SF1 and DHC.
+pub type TableValues = BTreeMap<String, Vec<serde_json::Number>>;
+
+#[derive(serde::Serialize)]
+struct DatasetSummary<'a> {
+	id: &'a str,
+	schema: Schema,
+	tables: Vec<String>,
+	rows: usize,
+}
+
+#[derive(serde::Serialize)]
+struct LogicalRecordSummary<'a> {
+	logrecno: LogicalRecordNumber,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	geoid: Option<&'a str>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	name: Option<String>,
+	tables: TableValues,
+}
+
+fn json<T: serde::Serialize>(value: &T) -> Result<Bytes, Refusal> {
+	Ok(
+		serde_json::to_vec(value)
+			.expect("failed to serialize response")
+			.into(),
+	)
+}
+
+fn not_found() -> hyper::Response<Body> {
+	http::response::Builder::new()
+		.status(hyper::StatusCode::NOT_FOUND)
+		.body("{}".into())
+		.unwrap()
+}
+
+fn error_response(error: &distringo::Error) -> hyper::Response<Body> {
+	match error {
+		distringo::Error::InvalidGeoId | distringo::Error::InvalidLogicalRecordNumber => not_found(),
+		error => {
+			log::warn!("Error reading dataset: {}", error);
+
+			http::response::Builder::new()
+				.status(hyper::StatusCode::INTERNAL_SERVER_ERROR)
+				.body("{}".into())
+				.unwrap()
+		}
+	}
+}
+
+/// Respond with the JSON `make` makes from a dataset, tagged with the
+/// dataset's hash and `request`
+fn respond<H, F>(
+	dataset: &Dataset,
+	request: &H,
+	conditions: &Conditions,
+	make: F,
+) -> hyper::Response<Body>
+where
+	H: Hash,
+	F: FnOnce() -> Result<Bytes, Refusal>,
+{
+	conditional::respond_with(
+		conditions,
+		http::response::Builder::new()
+			.status(hyper::StatusCode::OK)
+			.header(hyper::header::CONTENT_TYPE, "application/json"),
+		&conditional::derived_etag(&(dataset.hash, request)),
+		None,
+		make,
+	)
+}
+
+pub fn index(datasets: &Lazy<Dataset>, conditions: &Conditions) -> hyper::Response<Body> {
+	let mut ids: Vec<&String> = datasets.keys().collect();
+	ids.sort();
+
+	conditional::respond_with(
+		conditions,
+		http::response::Builder::new()
+			.status(hyper::StatusCode::OK)
+			.header(hyper::header::CONTENT_TYPE, "application/json"),
+		&conditional::derived_etag(&ids),
+		None,
+		|| json(&ids),
+	)
+}
+
+pub fn show(datasets: &Lazy<Dataset>, id: &str, conditions: &Conditions) -> hyper::Response<Body> {
+	let dataset: Arc<Dataset> = match datasets.fetch(id) {
+		Ok(dataset) => dataset,
+		Err(response) => return *response,
+	};
+
+	respond(&dataset, &id, conditions, || {
+		json(&DatasetSummary {
+			id,
+			schema: dataset.schema(),
+			tables: dataset.tables().iter().map(ToString::to_string).collect(),
+			rows: dataset.rows(),
+		})
+	})
+}
+
+pub fn record(
+	datasets: &Lazy<Dataset>,
+	id: &str,
+	logrecno: LogicalRecordNumber,
+	conditions: &Conditions,
+) -> hyper::Response<Body> {
+	let dataset: Arc<Dataset> = match datasets.fetch(id) {
+		Ok(dataset) => dataset,
+		Err(response) => return *response,
+	};
+
+	respond(&dataset, &logrecno, conditions, || {
+		match dataset.tables_for_logrecno(logrecno) {
+			Ok(tables) => json(&LogicalRecordSummary {
+				logrecno,
+				geoid: None,
+				name: None,
+				tables,
+			}),
+			Err(error) => Err(error_response(&error).into()),
+		}
+	})
+}
+
+pub fn geoid(
+	datasets: &Lazy<Dataset>,
+	id: &str,
+	geoid: &str,
+	conditions: &Conditions,
+) -> hyper::Response<Body> {
+	let dataset: Arc<Dataset> = match datasets.fetch(id) {
+		Ok(dataset) => dataset,
+		Err(response) => return *response,
+	};
+
+	respond(&dataset, &geoid, conditions, || {
+		let summary = dataset
+			.header_for_geoid(geoid)
+			.and_then(|(logrecno, name)| {
+				Ok(LogicalRecordSummary {
+					logrecno,
+					geoid: Some(geoid),
+					name: Some(name),
+					tables: dataset.tables_for_logrecno(logrecno)?,
+				})
+			});
+
+		match summary {
+			Ok(summary) => json(&summary),
+			Err(error) => Err(error_response(&error).into()),
+		}
+	})
+}
+
+pub fn tables(
+	datasets: &Lazy<Dataset>,
+	id: &str,
+	conditions: &Conditions,
+) -> hyper::Response<Body> {
+	let dataset: Arc<Dataset> = match datasets.fetch(id) {
+		Ok(dataset) => dataset,
+		Err(response) => return *response,
+	};
+
+	respond(&dataset, &"tables", conditions, || {
+		json(
+			&dataset
+				.dictionaries()
+				.values()
+				.collect::<Vec<&TableDictionary>>(),
+		)
+	})
+}
+
+pub fn table(
+	datasets: &Lazy<Dataset>,
+	id: &str,
+	table: &str,
+	conditions: &Conditions,
+) -> hyper::Response<Body> {
+	let dataset: Arc<Dataset> = match datasets.fetch(id) {
+		Ok(dataset) => dataset,
+		Err(response) => return *response,
+	};
+	let table: String = table.to_lowercase();
+
+	match dataset.dictionaries().get(&table) {
+		Some(dictionary) => respond(&dataset, &("tables", &table), conditions, || {
+			json(dictionary)
+		}),
+		None => not_found(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Dataset, Lazy};
+	use once_cell::sync::OnceCell;
+	use std::{collections::HashMap, sync::Arc};
+
+	static CACHE: OnceCell<Lazy<Dataset>> = OnceCell::new();
+
+	fn datasets() -> &'static Lazy<Dataset> {
+		CACHE.get_or_init(|| {
+			let mut dataset =
+				Dataset::from_packing_list_file("../tests/data/in2010.pl.prd.packinglist.txt.trim")
+					.expect("failed to load test dataset");
+			dataset
+				.load_dictionary("h1", "../schemas/2010/pl94_171/h1.yml")
+				.expect("failed to load test dictionary");
+
+			let mut map = HashMap::new();
+			map.insert("in2010".to_string(), Arc::new(dataset));
+			map.into()
+		})
+	}
+
+	async fn body_of(response: hyper::Response<hyper::Body>) -> String {
+		let bytes = hyper::body::to_bytes(response.into_body())
+			.await
+			.expect("failed to read body");
+		String::from_utf8(bytes.to_vec()).expect("body was not utf-8")
+	}
+
+	#[tokio::test]
+	async fn show_returns_summary() {
+		let response = super::show(datasets(), "in2010", &Default::default());
+		assert_eq!(response.status(), hyper::StatusCode::OK);
+		assert_eq!(
+			body_of(response).await,
+			"{\"id\":\"in2010\",\"schema\":{\"census2010\":\"pl94_171\"},\"tables\":[\"h1\",\"p1\",\"p2\",\"p3\",\"p4\"],\"rows\":380856}"
+		);
+	}
+
+	#[test]
+	fn every_file_of_a_dataset_is_watched() {
+		use super::{DatasetConfiguration, FileBacked};
+
+		let configuration: DatasetConfiguration = serde_json::from_str(
+			"{\"packing_list\":\"../tests/data/in2010.pl.prd.packinglist.txt.trim\",\
+			\"tables\":[{\"h1\":{\"schema\":\"../schemas/2010/pl94_171/h1.yml\"}}]}",
+		)
+		.unwrap();
+
+		let files: Vec<String> = configuration
+			.files()
+			.iter()
+			.map(|file| file.file_name().unwrap().to_string_lossy().into_owned())
+			.collect();
+		assert_eq!(
+			files,
+			[
+				"in2010.pl.prd.packinglist.txt.trim",
+				"ingeo2010.pl.trim",
+				"in000012010.pl.trim",
+				"in000022010.pl.trim",
+				"h1.yml",
+			]
+		);
+	}
+
+	#[tokio::test]
+	async fn decimal_cells_are_kept() {
+		// The Indiana block of the redistricting files, laid out as SF1's, with
+		// the average household sizes of P17 in a second segment
+		let directory = crate::server::routes::api::plans::tests::directory("sf1");
+		std::fs::write(
+			directory.join("in2010.sf1.prd.packinglist.txt"),
+			"       STUSAB: IN\n\
+			p1|1:1|\n\
+			p17|2:3|\n\
+			ingeo2010.sf1|2011-05-20 10:49:10|500|380856|\n\
+			in000012010.sf1|2011-05-20 10:48:23|300|380856|\n\
+			in000022010.sf1|2011-05-20 10:48:29|50|380856|\n",
+		)
+		.unwrap();
+		// SF1 puts blocks under another summary level.
+		let mut geo: String = std::fs::read_to_string("../tests/data/ingeo2010.pl.trim").unwrap();
+		geo.replace_range(8..11, "101");
+		std::fs::write(directory.join("ingeo2010.sf1"), geo).unwrap();
+		std::fs::copy(
+			"../tests/data/in000012010.pl.trim",
+			directory.join("in000012010.sf1"),
+		)
+		.unwrap();
+		std::fs::write(
+			directory.join("in000022010.sf1"),
+			"SF1ST,IN,000,02,0335180,2.21,0.5,1.71\n",
+		)
+		.unwrap();
+
+		let dataset =
+			Dataset::from_packing_list_file(directory.join("in2010.sf1.prd.packinglist.txt")).unwrap();
+		let mut map = HashMap::new();
+		map.insert("sf1".to_string(), Arc::new(dataset));
+		let datasets: Lazy<Dataset> = map.into();
+
+		let response = super::geoid(&datasets, "sf1", "181570052001013", &Default::default());
+		assert_eq!(response.status(), hyper::StatusCode::OK);
+		assert!(body_of(response)
+			.await
+			.contains("\"tables\":{\"p1\":[53],\"p17\":[2.21,0.5,1.71]}"));
+
+		std::fs::remove_dir_all(&directory).unwrap();
+	}
+
+	#[test]
+	fn show_unknown_returns_404() {
+		let response = super::show(datasets(), "<some unknown id>", &Default::default());
+		assert_eq!(response.status(), hyper::StatusCode::NOT_FOUND);
+	}
+
+	#[tokio::test]
+	async fn geoid_returns_tables() {
+		let response = super::geoid(datasets(), "in2010", "181570052001013", &Default::default());
+		assert_eq!(response.status(), hyper::StatusCode::OK);
+
+		let body = body_of(response).await;
+		assert!(body.starts_with(
+			"{\"logrecno\":335180,\"geoid\":\"181570052001013\",\"name\":\"Block 1013\",\"tables\":{\"h1\":[24,24,0],"
+		));
+		assert!(body.contains("\"p1\":[53,52,50,0,0,2,0,0,1,1,0,"));
+	}
+
+	#[test]
+	fn geoid_unknown_returns_404() {
+		let response = super::geoid(datasets(), "in2010", "000000000000000", &Default::default());
+		assert_eq!(response.status(), hyper::StatusCode::NOT_FOUND);
+	}
+
+	#[test]
+	fn record_out_of_range_returns_404() {
+		let response = super::record(datasets(), "in2010", 0, &Default::default());
+		assert_eq!(response.status(), hyper::StatusCode::NOT_FOUND);
+
+		let response = super::record(datasets(), "in2010", 380_857, &Default::default());
+		assert_eq!(response.status(), hyper::StatusCode::NOT_FOUND);
+	}
+
+	#[test]
+	fn populations_are_read_from_headers() {
+		assert_eq!(
+			datasets()
+				.get("in2010")
+				.unwrap()
+				.populations()
+				.get("181570052001013"),
+			Some(&53)
+		);
+	}
+
+	#[test]
+	fn mismatched_dictionary_is_rejected() {
+		let mut dataset =
+			Dataset::from_packing_list_file("../tests/data/in2010.pl.prd.packinglist.txt.trim")
+				.expect("failed to load test dataset");
+
+		assert!(dataset
+			.load_dictionary("p1", "../schemas/2010/pl94_171/h1.yml")
+			.is_err());
+		assert!(dataset
+			.load_dictionary("p9", "../schemas/2010/pl94_171/h1.yml")
+			.is_err());
+	}
+
+	#[tokio::test]
+	async fn table_returns_columns() {
+		let response = super::table(datasets(), "in2010", "H1", &Default::default());
+		assert_eq!(response.status(), hyper::StatusCode::OK);
+		assert_eq!(
+			body_of(response).await,
+			"{\"table\":\"h1\",\"columns\":[\
+			{\"id\":\"H0010001\",\"label\":\"Total\",\"depth\":0,\"parent\":null},\
+			{\"id\":\"H0010002\",\"label\":\"Occupied\",\"depth\":1,\"parent\":\"H0010001\"},\
+			{\"id\":\"H0010003\",\"label\":\"Vacant\",\"depth\":1,\"parent\":\"H0010001\"}\
+			]}"
+		);
+	}
+
+	#[test]
+	fn table_without_dictionary_returns_404() {
+		let response = super::table(datasets(), "in2010", "p1", &Default::default());
+		assert_eq!(response.status(), hyper::StatusCode::NOT_FOUND);
+	}
+}
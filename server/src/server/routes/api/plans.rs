@@ -0,0 +1,859 @@
+use std::{
+	collections::{BTreeMap, HashMap},
+	fs,
+	io::Write,
+	path::{Path, PathBuf},
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		Arc, RwLock,
+	},
+};
+
+use hyper::body::Body;
+use tokio::sync::broadcast;
+
+use super::{
+	datasets::Dataset,
+	resources::Lazy,
+	responses::{empty_response, json_response, unprocessable},
+	sessions::Session,
+	shapefiles::Shapefile,
+};
+
+pub mod districts;
+pub mod live;
+pub mod metrics;
+
+pub type PlanId = u64;
+pub type District = u32;
+
+/// The length of a block GeoID; shorter GeoIDs are larger summary levels
+const BLOCK_GEOID_LENGTH: usize = 15;
+
+/// A redistricting plan: some metadata plus an assignment of blocks to
+/// districts
+///
+/// Districts are numbered from `1` to `districts`; blocks without an entry in
+/// `assignments` are unassigned.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Plan {
+	id: PlanId,
+	name: String,
+	session: String,
+	districts: District,
+	version: u64,
+	assignments: BTreeMap<String, District>,
+}
+
+impl Plan {
+	pub fn id(&self) -> PlanId {
+		self.id
+	}
+
+	pub fn session(&self) -> &str {
+		&self.session
+	}
+
+	pub fn districts(&self) -> District {
+		self.districts
+	}
+
+	/// The number of changes that have been made to the plan
+	pub fn version(&self) -> u64 {
+		self.version
+	}
+
+	pub fn assignments(&self) -> &BTreeMap<String, District> {
+		&self.assignments
+	}
+
+	/// Apply a batch of reassignments, bumping the version
+	pub fn reassign(&mut self, reassignments: &Reassignments) {
+		for (geoid, district) in &reassignments.assignments {
+			match district {
+				Some(district) => self.assignments.insert(geoid.clone(), *district),
+				None => self.assignments.remove(geoid),
+			};
+		}
+
+		self.version += 1;
+	}
+}
+
+/// The body of a request creating or replacing a plan
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct PlanParameters {
+	name: String,
+	session: String,
+	districts: District,
+	#[serde(default)]
+	assignments: BTreeMap<String, District>,
+}
+
+/// A batch of block reassignments; a `null` district unassigns the block
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct Reassignments {
+	assignments: BTreeMap<String, Option<District>>,
+}
+
+impl Reassignments {
+	pub fn assignments(&self) -> &BTreeMap<String, Option<District>> {
+		&self.assignments
+	}
+}
+
+#[derive(Debug, serde::Serialize)]
+struct PlanSummary<'a> {
+	id: PlanId,
+	name: &'a str,
+	session: &'a str,
+	districts: District,
+	version: u64,
+}
+
+#[derive(Debug, PartialEq, serde::Serialize)]
+pub struct DistrictTotal {
+	district: District,
+	population: u64,
+	blocks: usize,
+}
+
+#[derive(Debug, PartialEq, serde::Serialize)]
+pub struct PlanTotals {
+	id: PlanId,
+	version: u64,
+	districts: Vec<DistrictTotal>,
+}
+
+/// Sum up the population and block count of each district
+///
+/// Blocks without a known population count towards `blocks` only.
+pub fn totals(plan: &Plan, populations: Option<&HashMap<String, u64>>) -> PlanTotals {
+	let mut districts: Vec<DistrictTotal> = (1..=plan.districts)
+		.map(|district| DistrictTotal {
+			district,
+			population: 0,
+			blocks: 0,
+		})
+		.collect();
+
+	for (geoid, district) in &plan.assignments {
+		if let Some(total) = districts.get_mut((*district as usize).wrapping_sub(1)) {
+			total.blocks += 1;
+			total.population += populations
+				.and_then(|populations| populations.get(geoid))
+				.copied()
+				.unwrap_or(0);
+		}
+	}
+
+	PlanTotals {
+		id: plan.id,
+		version: plan.version,
+		districts,
+	}
+}
+
+/// The loaded resources that plans are validated and totaled against
+#[derive(Clone, Copy)]
+pub struct Context<'a> {
+	pub sessions: &'a HashMap<String, Session>,
+	pub datasets: &'a Lazy<Dataset>,
+	pub shapefiles: &'a Lazy<Shapefile>,
+}
+
+impl<'a> Context<'a> {
+	/// The first dataset in a session, whose block populations plans are
+	/// totaled with
+	pub fn dataset(&self, session: &str) -> Option<Arc<Dataset>> {
+		let session: &Session = self.sessions.get(session)?;
+
+		session.datasets().find_map(|id| self.datasets.get(id))
+	}
+
+	/// The first shapefile in a session with features to draw districts from
+	pub fn shapefile(&self, session: &str) -> Option<Arc<Shapefile>> {
+		let session: &Session = self.sessions.get(session)?;
+
+		session
+			.shapefiles()
+			.filter_map(|id| self.shapefiles.get(id))
+			.find(|shapefile| !shapefile.features().is_empty())
+	}
+
+	/// The blocks a session's plans may assign: those of its dataset, or
+	/// failing that, of its shapefile
+	fn blocks(&self, session: &str) -> Option<Blocks> {
+		self
+			.dataset(session)
+			.map(Blocks::Dataset)
+			.or_else(|| self.shapefile(session).map(Blocks::Shapefile))
+	}
+
+	fn validate_assignment(
+		blocks: Option<&Blocks>,
+		districts: District,
+		geoid: &str,
+		district: Option<District>,
+	) -> Result<(), String> {
+		if let Some(district) = district {
+			if district == 0 || district > districts {
+				return Err(format!(
+					"district {} of {} is out of range",
+					district, geoid
+				));
+			}
+		}
+
+		match blocks {
+			Some(blocks) if blocks.contains(geoid) => Ok(()),
+			Some(_) => Err(format!("unknown block {}", geoid)),
+			None => Err(format!(
+				"the session has no blocks to assign {} from",
+				geoid
+			)),
+		}
+	}
+
+	fn validate_assignments<'i, I>(
+		&self,
+		session: &str,
+		districts: District,
+		assignments: I,
+	) -> Result<(), String>
+	where
+		I: IntoIterator<Item = (&'i String, Option<District>)>,
+	{
+		let mut assignments = assignments.into_iter().peekable();
+		if assignments.peek().is_none() {
+			return Ok(());
+		}
+
+		let blocks: Option<Blocks> = self.blocks(session);
+
+		assignments.try_for_each(|(geoid, district)| {
+			Self::validate_assignment(blocks.as_ref(), districts, geoid, district)
+		})
+	}
+
+	pub fn validate_parameters(&self, parameters: &PlanParameters) -> Result<(), String> {
+		if !self.sessions.contains_key(&parameters.session) {
+			return Err(format!("unknown session {}", parameters.session));
+		}
+
+		if parameters.districts == 0 {
+			return Err("a plan needs at least one district".to_string());
+		}
+
+		self.validate_assignments(
+			&parameters.session,
+			parameters.districts,
+			parameters
+				.assignments
+				.iter()
+				.map(|(geoid, district)| (geoid, Some(*district))),
+		)
+	}
+
+	pub fn validate_reassignments(
+		&self,
+		session: &str,
+		districts: District,
+		reassignments: &Reassignments,
+	) -> Result<(), String> {
+		self.validate_assignments(
+			session,
+			districts,
+			reassignments
+				.assignments
+				.iter()
+				.map(|(geoid, district)| (geoid, *district)),
+		)
+	}
+}
+
+/// What the blocks of a plan are checked against
+enum Blocks {
+	Dataset(Arc<Dataset>),
+	Shapefile(Arc<Shapefile>),
+}
+
+impl Blocks {
+	fn contains(&self, geoid: &str) -> bool {
+		match self {
+			// Datasets index every summary level, not only blocks.
+			Blocks::Dataset(dataset) => {
+				geoid.len() == BLOCK_GEOID_LENGTH && dataset.populations().contains_key(geoid)
+			}
+			Blocks::Shapefile(shapefile) => shapefile.contains(geoid),
+		}
+	}
+}
+
+/// Why a change to a plan couldn't be made
+#[derive(Debug)]
+pub enum PlanError {
+	NotFound,
+	Invalid(String),
+	/// The plan changed while the change to it was being checked
+	Conflict,
+	Storage(distringo::Error),
+}
+
+impl From<distringo::Error> for PlanError {
+	fn from(error: distringo::Error) -> Self {
+		Self::Storage(error)
+	}
+}
+
+/// Plans, persisted as one JSON file each in a directory
+///
+/// Every plan is kept in memory as well; the directory is only read when the
+/// store is opened.  Files are written to a temporary path and then renamed
+/// into place so that a crash never leaves a half-written plan behind.
+/// Changed plans are written once the store is unlocked again, so that other
+/// plans can be used meanwhile.
+///
+/// The next plan ID is persisted alongside the plans, so that IDs of deleted
+/// plans are never handed out again.
+///
+/// Every change to a plan is also published to its subscribers (see
+/// [`PlanStore::subscribe`]) while the store is still locked, so subscribers
+/// see versions in order.
+#[derive(Debug)]
+pub struct PlanStore {
+	directory: PathBuf,
+	plans: RwLock<BTreeMap<PlanId, Plan>>,
+	/// Only changed while `plans` is locked for writing
+	next: AtomicU64,
+	channels: std::sync::Mutex<HashMap<PlanId, broadcast::Sender<Arc<Plan>>>>,
+	outlines: districts::OutlineCache,
+	feeds: live::Feeds,
+	/// Held while writing or removing changed plans' files, so that an older
+	/// version is never written over a newer one
+	writing: std::sync::Mutex<()>,
+}
+
+/// How many updates a subscriber may fall behind before it misses some
+const CHANNEL_CAPACITY: usize = 64;
+
+/// The file in a store's directory holding the next plan ID
+const NEXT_ID: &str = "next";
+
+/// Write a file through a temporary file, so it's never left half-written
+fn write_atomically(path: &Path, data: &[u8]) -> distringo::Result<()> {
+	let mut temporary: std::ffi::OsString = path.as_os_str().to_owned();
+	temporary.push(".tmp");
+	let temporary = PathBuf::from(temporary);
+
+	let mut file = fs::File::create(&temporary)?;
+	file.write_all(data)?;
+	file.sync_all()?;
+
+	fs::rename(&temporary, path)?;
+
+	Ok(())
+}
+
+impl PlanStore {
+	pub fn open<P: AsRef<Path>>(directory: P) -> distringo::Result<Self> {
+		let directory: PathBuf = directory.as_ref().to_path_buf();
+		fs::create_dir_all(&directory)?;
+
+		let mut plans = BTreeMap::new();
+
+		for entry in fs::read_dir(&directory)? {
+			let path: PathBuf = entry?.path();
+
+			if path.extension().and_then(std::ffi::OsStr::to_str) != Some("json") {
+				continue;
+			}
+
+			let plan: Plan = serde_json::from_reader(fs::File::open(&path)?)?;
+
+			plans.insert(plan.id, plan);
+		}
+
+		let next: PlanId = match fs::read_to_string(directory.join(NEXT_ID)) {
+			Ok(next) => next.trim().parse()?,
+			Err(error) if error.kind() == std::io::ErrorKind::NotFound => 1,
+			Err(error) => return Err(error.into()),
+		};
+		// Plans from before the ID was persisted may be numbered past it.
+		let next: PlanId = plans.keys().next_back().map_or(next, |id| next.max(id + 1));
+
+		log::info!("Loaded {} plan(s) from {:?}", plans.len(), directory);
+
+		Ok(Self {
+			directory,
+			plans: RwLock::new(plans),
+			next: AtomicU64::new(next),
+			channels: Default::default(),
+			outlines: Default::default(),
+			feeds: Default::default(),
+			writing: Default::default(),
+		})
+	}
+
+	fn path(&self, id: PlanId) -> PathBuf {
+		self.directory.join(format!("{}.json", id))
+	}
+
+	fn persist(&self, plan: &Plan) -> distringo::Result<()> {
+		write_atomically(&self.path(plan.id), &serde_json::to_vec(plan)?)
+	}
+
+	/// Write a changed plan, unless it has changed again or been deleted since
+	///
+	/// A later version is written by whoever changed it.
+	fn persist_latest(&self, plan: &Plan) -> distringo::Result<()> {
+		let _writing = self.writing.lock().expect("plan writing lock poisoned");
+
+		let latest: Option<u64> = self
+			.plans
+			.read()
+			.expect("plan store lock poisoned")
+			.get(&plan.id)
+			.map(Plan::version);
+
+		if latest == Some(plan.version) {
+			self.persist(plan)
+		} else {
+			Ok(())
+		}
+	}
+
+	fn publish(&self, plan: &Plan) {
+		let mut channels = self.channels.lock().expect("plan channel lock poisoned");
+
+		if let Some(sender) = channels.get(&plan.id) {
+			if sender.send(Arc::new(plan.clone())).is_err() {
+				// Nobody is listening anymore.
+				channels.remove(&plan.id);
+			}
+		}
+	}
+
+	/// Get a plan along with a receiver of every later version of it
+	pub fn subscribe(&self, id: PlanId) -> Option<(Plan, broadcast::Receiver<Arc<Plan>>)> {
+		let plans = self.plans.read().expect("plan store lock poisoned");
+		let plan: Plan = plans.get(&id)?.clone();
+
+		let mut channels = self.channels.lock().expect("plan channel lock poisoned");
+		let receiver = channels
+			.entry(id)
+			.or_insert_with(|| broadcast::channel(CHANNEL_CAPACITY).0)
+			.subscribe();
+
+		Some((plan, receiver))
+	}
+
+	pub fn get(&self, id: PlanId) -> Option<Plan> {
+		let plans = self.plans.read().expect("plan store lock poisoned");
+		plans.get(&id).cloned()
+	}
+
+	/// Look at a plan without copying it
+	pub fn inspect<F: FnOnce(&Plan) -> T, T>(&self, id: PlanId, f: F) -> Option<T> {
+		let plans = self.plans.read().expect("plan store lock poisoned");
+		plans.get(&id).map(f)
+	}
+
+	pub fn create(&self, parameters: PlanParameters) -> distringo::Result<Plan> {
+		let mut plans = self.plans.write().expect("plan store lock poisoned");
+
+		let id: PlanId = self.next.load(Ordering::SeqCst);
+		write_atomically(
+			&self.directory.join(NEXT_ID),
+			(id + 1).to_string().as_bytes(),
+		)?;
+		self.next.store(id + 1, Ordering::SeqCst);
+
+		let plan = Plan {
+			id,
+			name: parameters.name,
+			session: parameters.session,
+			districts: parameters.districts,
+			version: 0,
+			assignments: parameters.assignments,
+		};
+
+		self.persist(&plan)?;
+		plans.insert(id, plan.clone());
+
+		Ok(plan)
+	}
+
+	/// Modify a plan and persist it, returning the new version
+	///
+	/// The store is locked while `f` runs, so it should only apply a change
+	/// that has already been checked.  `f` may refuse the modification by
+	/// returning an error, in which case nothing is changed.  If writing the
+	/// plan fails, the new version is kept, and written with the next one.
+	pub fn update<F>(&self, id: PlanId, f: F) -> Result<Plan, PlanError>
+	where
+		F: FnOnce(&mut Plan) -> Result<(), PlanError>,
+	{
+		let plan: Plan = {
+			let mut plans = self.plans.write().expect("plan store lock poisoned");
+			let mut plan: Plan = plans.get(&id).ok_or(PlanError::NotFound)?.clone();
+
+			f(&mut plan)?;
+
+			self.publish(&plan);
+			plans.insert(id, plan.clone());
+			plan
+		};
+
+		self.persist_latest(&plan)?;
+
+		Ok(plan)
+	}
+
+	pub fn delete(&self, id: PlanId) -> distringo::Result<bool> {
+		let _writing = self.writing.lock().expect("plan writing lock poisoned");
+		let mut plans = self.plans.write().expect("plan store lock poisoned");
+
+		if plans.remove(&id).is_some() {
+			// Dropping the sender lets subscribers know the plan is gone.
+			let mut channels = self.channels.lock().expect("plan channel lock poisoned");
+			channels.remove(&id);
+			self.outlines.remove(id);
+
+			fs::remove_file(self.path(id))?;
+			Ok(true)
+		} else {
+			Ok(false)
+		}
+	}
+}
+
+fn error_response(error: &PlanError) -> hyper::Response<Body> {
+	match error {
+		PlanError::NotFound => empty_response(hyper::StatusCode::NOT_FOUND),
+		PlanError::Invalid(error) => unprocessable(error),
+		PlanError::Conflict => empty_response(hyper::StatusCode::CONFLICT),
+		PlanError::Storage(error) => {
+			log::warn!("Error persisting plan: {}", error);
+			empty_response(hyper::StatusCode::INTERNAL_SERVER_ERROR)
+		}
+	}
+}
+
+fn update_response<T: serde::Serialize>(result: Result<T, PlanError>) -> hyper::Response<Body> {
+	match result {
+		Ok(value) => json_response(hyper::StatusCode::OK, &value),
+		Err(error) => error_response(&error),
+	}
+}
+
+pub fn index(store: &PlanStore) -> hyper::Response<Body> {
+	let plans = store.plans.read().expect("plan store lock poisoned");
+
+	let summaries: Vec<PlanSummary> = plans
+		.values()
+		.map(|plan| PlanSummary {
+			id: plan.id,
+			name: &plan.name,
+			session: &plan.session,
+			districts: plan.districts,
+			version: plan.version,
+		})
+		.collect();
+
+	json_response(hyper::StatusCode::OK, &summaries)
+}
+
+pub fn show(store: &PlanStore, id: PlanId) -> hyper::Response<Body> {
+	match store.get(id) {
+		Some(plan) => json_response(hyper::StatusCode::OK, &plan),
+		None => empty_response(hyper::StatusCode::NOT_FOUND),
+	}
+}
+
+pub fn create(
+	store: &PlanStore,
+	context: Context,
+	parameters: PlanParameters,
+) -> hyper::Response<Body> {
+	if let Err(error) = context.validate_parameters(&parameters) {
+		return unprocessable(&error);
+	}
+
+	match store.create(parameters) {
+		Ok(plan) => json_response(hyper::StatusCode::CREATED, &plan),
+		Err(error) => error_response(&PlanError::Storage(error)),
+	}
+}
+
+pub fn replace(
+	store: &PlanStore,
+	context: Context,
+	id: PlanId,
+	parameters: PlanParameters,
+) -> hyper::Response<Body> {
+	if let Err(error) = context.validate_parameters(&parameters) {
+		return unprocessable(&error);
+	}
+
+	update_response(store.update(id, |plan| {
+		plan.name = parameters.name;
+		plan.session = parameters.session;
+		plan.districts = parameters.districts;
+		plan.assignments = parameters.assignments;
+		plan.version += 1;
+
+		Ok(())
+	}))
+}
+
+/// Check a batch of reassignments against the plan's blocks, then apply it
+///
+/// Checking may load a dataset, so it's done before the store is locked.  The
+/// batch is refused if the plan's session or districts change meanwhile.
+pub fn checked_reassign(
+	store: &PlanStore,
+	context: Context,
+	id: PlanId,
+	reassignments: &Reassignments,
+) -> Result<Plan, PlanError> {
+	let (session, districts): (String, District) = store
+		.inspect(id, |plan| (plan.session.clone(), plan.districts))
+		.ok_or(PlanError::NotFound)?;
+
+	context
+		.validate_reassignments(&session, districts, reassignments)
+		.map_err(PlanError::Invalid)?;
+
+	store.update(id, |plan| {
+		if plan.session != session || plan.districts != districts {
+			return Err(PlanError::Conflict);
+		}
+
+		plan.reassign(reassignments);
+
+		Ok(())
+	})
+}
+
+pub fn reassign(
+	store: &PlanStore,
+	context: Context,
+	id: PlanId,
+	reassignments: Reassignments,
+) -> hyper::Response<Body> {
+	update_response(
+		checked_reassign(store, context, id, &reassignments).map(|plan| {
+			let dataset: Option<Arc<Dataset>> = context.dataset(&plan.session);
+
+			totals(&plan, dataset.as_deref().map(Dataset::populations))
+		}),
+	)
+}
+
+pub fn delete(store: &PlanStore, id: PlanId) -> hyper::Response<Body> {
+	match store.delete(id) {
+		Ok(true) => empty_response(hyper::StatusCode::NO_CONTENT),
+		Ok(false) => empty_response(hyper::StatusCode::NOT_FOUND),
+		Err(error) => error_response(&PlanError::Storage(error)),
+	}
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+	use super::{Context, Lazy, PlanParameters, PlanStore, Reassignments};
+	use crate::server::routes::api::{
+		datasets::Dataset,
+		sessions::{Session, SessionConfiguration},
+		shapefiles::{Shapefile, ShapefileType},
+	};
+	use std::{
+		collections::{BTreeMap, HashMap},
+		sync::Arc,
+	};
+
+	/// A fresh, empty directory for a store to live in
+	pub(crate) fn directory(name: &str) -> std::path::PathBuf {
+		let directory = std::env::temp_dir().join(format!(
+			"distringo-{}-{}-{}",
+			name,
+			std::process::id(),
+			std::time::SystemTime::now()
+				.duration_since(std::time::UNIX_EPOCH)
+				.unwrap()
+				.as_nanos()
+		));
+		std::fs::create_dir_all(&directory).unwrap();
+		directory
+	}
+
+	/// Sessions, datasets and shapefiles for a [`Context`] to borrow
+	pub(crate) type Loaded = (HashMap<String, Session>, Lazy<Dataset>, Lazy<Shapefile>);
+
+	/// A row of three one-degree square blocks, `a`, `b` and `c`
+	pub(crate) fn blocks() -> geojson::GeoJson {
+		"{\"type\":\"FeatureCollection\",\"features\":[\
+		{\"type\":\"Feature\",\"properties\":{\"GEOID10\":\"a\"},\"geometry\":{\"type\":\"Polygon\",\
+		\"coordinates\":[[[0.0,0.0],[1.0,0.0],[1.0,1.0],[0.0,1.0],[0.0,0.0]]]}},\
+		{\"type\":\"Feature\",\"properties\":{\"GEOID10\":\"b\"},\"geometry\":{\"type\":\"Polygon\",\
+		\"coordinates\":[[[1.0,0.0],[2.0,0.0],[2.0,1.0],[1.0,1.0],[1.0,0.0]]]}},\
+		{\"type\":\"Feature\",\"properties\":{\"GEOID10\":\"c\"},\"geometry\":{\"type\":\"Polygon\",\
+		\"coordinates\":[[[2.0,0.0],[3.0,0.0],[3.0,1.0],[2.0,1.0],[2.0,0.0]]]}}\
+		]}"
+			.parse()
+			.unwrap()
+	}
+
+	/// A session drawing its blocks from [`blocks`], without a dataset
+	pub(crate) fn resources() -> Loaded {
+		let datasets: Lazy<Dataset> = Lazy::default();
+		let mut shapefiles = HashMap::new();
+		shapefiles.insert(
+			"blocks".to_string(),
+			Arc::new(Shapefile::new(ShapefileType::TabularBlock, blocks()).unwrap()),
+		);
+		let shapefiles: Lazy<Shapefile> = shapefiles.into();
+
+		let configuration: SessionConfiguration =
+			serde_json::from_str("{\"shapefiles\":[\"blocks\"]}").unwrap();
+		let mut sessions = HashMap::new();
+		sessions.insert(
+			"session".to_string(),
+			Session::new(configuration, &datasets, &shapefiles).unwrap(),
+		);
+
+		(sessions, datasets, shapefiles)
+	}
+
+	pub(crate) fn parameters(districts: u32, assignments: &[(&str, u32)]) -> PlanParameters {
+		PlanParameters {
+			name: "Plan".to_string(),
+			session: "session".to_string(),
+			districts,
+			assignments: assignments
+				.iter()
+				.map(|(geoid, district)| (geoid.to_string(), *district))
+				.collect(),
+		}
+	}
+
+	#[test]
+	fn plans_survive_reopening() {
+		let directory = directory("plans");
+
+		let store = PlanStore::open(&directory).unwrap();
+		let plan = store.create(parameters(2, &[("a", 1)])).unwrap();
+		assert_eq!(plan.id(), 1);
+		assert_eq!(store.create(parameters(2, &[])).unwrap().id(), 2);
+		assert!(store.delete(2).unwrap());
+		drop(store);
+
+		let store = PlanStore::open(&directory).unwrap();
+		assert_eq!(store.get(1), Some(plan));
+		assert_eq!(store.get(2), None);
+
+		// The deleted plan's ID isn't reused.
+		assert_eq!(store.create(parameters(2, &[])).unwrap().id(), 3);
+	}
+
+	#[tokio::test]
+	async fn reassign_returns_totals() {
+		let (sessions, datasets, shapefiles) = resources();
+		let context = Context {
+			sessions: &sessions,
+			datasets: &datasets,
+			shapefiles: &shapefiles,
+		};
+		let store = PlanStore::open(directory("reassign")).unwrap();
+		let plan = store.create(parameters(2, &[("a", 1), ("b", 1)])).unwrap();
+
+		let mut assignments = BTreeMap::new();
+		assignments.insert("a".to_string(), Some(2));
+		assignments.insert("b".to_string(), None);
+		assignments.insert("c".to_string(), Some(2));
+
+		let response = super::reassign(&store, context, plan.id(), Reassignments { assignments });
+		assert_eq!(response.status(), hyper::StatusCode::OK);
+
+		let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+		assert_eq!(
+			body,
+			"{\"id\":1,\"version\":1,\"districts\":[\
+			{\"district\":1,\"population\":0,\"blocks\":0},\
+			{\"district\":2,\"population\":0,\"blocks\":2}\
+			]}"
+		);
+
+		let plan = store.get(plan.id()).unwrap();
+		assert_eq!(plan.assignments().len(), 2);
+		assert_eq!(plan.assignments().get("b"), None);
+	}
+
+	#[test]
+	fn out_of_range_districts_are_unprocessable() {
+		let (sessions, datasets, shapefiles) = resources();
+		let context = Context {
+			sessions: &sessions,
+			datasets: &datasets,
+			shapefiles: &shapefiles,
+		};
+		let store = PlanStore::open(directory("invalid")).unwrap();
+
+		let response = super::create(&store, context, parameters(2, &[("a", 3)]));
+		assert_eq!(response.status(), hyper::StatusCode::UNPROCESSABLE_ENTITY);
+
+		let plan = store.create(parameters(2, &[])).unwrap();
+		let mut assignments = BTreeMap::new();
+		assignments.insert("a".to_string(), Some(0));
+
+		let response = super::reassign(&store, context, plan.id(), Reassignments { assignments });
+		assert_eq!(response.status(), hyper::StatusCode::UNPROCESSABLE_ENTITY);
+		assert_eq!(store.get(plan.id()).unwrap().version(), 0);
+	}
+
+	#[test]
+	fn only_blocks_can_be_assigned() {
+		let (sessions, datasets, shapefiles) = resources();
+		let context = Context {
+			sessions: &sessions,
+			datasets: &datasets,
+			shapefiles: &shapefiles,
+		};
+
+		let blocks = context.blocks("session");
+		let validate = |geoid: &str| Context::validate_assignment(blocks.as_ref(), 2, geoid, Some(1));
+		assert_eq!(validate("a"), Ok(()));
+		assert!(validate("d").is_err());
+
+		// Without a dataset or a shapefile, nothing can be assigned.
+		assert!(Context::validate_assignment(None, 2, "a", Some(1)).is_err());
+
+		let mut empty: HashMap<String, Session> = HashMap::new();
+		empty.insert(
+			"session".to_string(),
+			Session::new(serde_json::from_str("{}").unwrap(), &datasets, &shapefiles).unwrap(),
+		);
+		let context = Context {
+			sessions: &empty,
+			..context
+		};
+		assert!(context
+			.validate_parameters(&parameters(2, &[("a", 1)]))
+			.is_err());
+		assert_eq!(context.validate_parameters(&parameters(2, &[])), Ok(()));
+	}
+
+	#[test]
+	fn unknown_plans_return_404() {
+		let store = PlanStore::open(directory("unknown")).unwrap();
+
+		assert_eq!(
+			super::show(&store, 7).status(),
+			hyper::StatusCode::NOT_FOUND
+		);
+		assert_eq!(
+			super::delete(&store, 7).status(),
+			hyper::StatusCode::NOT_FOUND
+		);
+	}
+}
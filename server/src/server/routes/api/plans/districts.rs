@@ -0,0 +1,219 @@
+use std::{
+	collections::HashMap,
+	sync::{Arc, Mutex},
+};
+
+use geo::MultiPolygon;
+use hyper::body::{Body, Bytes};
+
+use super::{
+	super::{
+		datasets::Dataset,
+		responses::{empty_response, unprocessable},
+		shapefiles::Shapefile,
+	},
+	totals, Context, Plan, PlanId, PlanStore,
+};
+
+/// Dissolved district outlines, kept until the plan they were drawn from
+/// changes
+#[derive(Debug, Default)]
+pub struct OutlineCache {
+	entries: Mutex<HashMap<PlanId, (u64, Bytes)>>,
+}
+
+impl OutlineCache {
+	/// Get the outlines of a version of a plan, drawing them if needed
+	///
+	/// Drawing happens without holding the lock, so a slow plan doesn't hold
+	/// up the others.  If two versions race, the newer one is kept.
+	fn get_or_draw<F: FnOnce() -> Bytes>(&self, plan: &Plan, draw: F) -> Bytes {
+		{
+			let entries = self.entries.lock().expect("outline cache lock poisoned");
+
+			if let Some((version, data)) = entries.get(&plan.id) {
+				if *version == plan.version {
+					return data.clone();
+				}
+			}
+		}
+
+		let data: Bytes = draw();
+
+		let mut entries = self.entries.lock().expect("outline cache lock poisoned");
+		match entries.get(&plan.id) {
+			Some((version, _)) if *version > plan.version => {}
+			_ => {
+				entries.insert(plan.id, (plan.version, data.clone()));
+			}
+		}
+
+		data
+	}
+
+	pub(super) fn remove(&self, id: PlanId) {
+		let mut entries = self.entries.lock().expect("outline cache lock poisoned");
+		entries.remove(&id);
+	}
+}
+
+/// Draw a `FeatureCollection` with one feature per district
+fn draw(plan: &Plan, shapefile: &Shapefile, populations: Option<&HashMap<String, u64>>) -> Bytes {
+	let mut members: Vec<Vec<&MultiPolygon<f64>>> = vec![Vec::new(); plan.districts as usize];
+
+	for (geoid, district) in &plan.assignments {
+		let member = (*district as usize)
+			.checked_sub(1)
+			.and_then(|idx| members.get_mut(idx));
+
+		if let (Some(member), Some(polygons)) = (member, shapefile.polygons(geoid)) {
+			member.push(polygons);
+		}
+	}
+
+	let features: Vec<geojson::Feature> = totals(plan, populations)
+		.districts
+		.into_iter()
+		.zip(members)
+		.map(|(total, members)| {
+			let outline: MultiPolygon<f64> = distringo::geometry::dissolve(members);
+
+			let properties = match serde_json::to_value(&total) {
+				Ok(serde_json::Value::Object(properties)) => Some(properties),
+				_ => None,
+			};
+
+			geojson::Feature {
+				bbox: None,
+				geometry: Some(geojson::Geometry::new(geojson::Value::from(&outline))),
+				id: None,
+				properties,
+				foreign_members: None,
+			}
+		})
+		.collect();
+
+	let collection = geojson::FeatureCollection {
+		bbox: None,
+		features,
+		foreign_members: None,
+	};
+
+	Bytes::from(collection.to_string())
+}
+
+/// Serve the outline of every district of a plan as GeoJSON
+pub fn show(store: &PlanStore, context: Context, id: PlanId) -> hyper::Response<Body> {
+	let plan: Plan = match store.get(id) {
+		Some(plan) => plan,
+		None => return empty_response(hyper::StatusCode::NOT_FOUND),
+	};
+
+	let shapefile: Arc<Shapefile> = match context.shapefile(&plan.session) {
+		Some(shapefile) => shapefile,
+		None => return unprocessable("the plan's session has no block shapefile"),
+	};
+
+	let data: Bytes = store.outlines.get_or_draw(&plan, || {
+		let dataset: Option<Arc<Dataset>> = context.dataset(&plan.session);
+
+		draw(
+			&plan,
+			&shapefile,
+			dataset.as_deref().map(Dataset::populations),
+		)
+	});
+
+	http::response::Builder::new()
+		.status(hyper::StatusCode::OK)
+		.header(hyper::header::CONTENT_TYPE, "application/vnd.geo+json")
+		.body(data.into())
+		.unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::{
+		tests::{directory, parameters, resources},
+		Context, PlanStore, Reassignments,
+	};
+	use crate::server::routes::api::sessions::{Session, SessionConfiguration};
+
+	async fn outlines(
+		store: &PlanStore,
+		context: Context<'_>,
+		id: u64,
+	) -> (hyper::StatusCode, serde_json::Value) {
+		let response = super::show(store, context, id);
+		let status = response.status();
+		let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+
+		(status, serde_json::from_slice(&body).unwrap())
+	}
+
+	#[tokio::test]
+	async fn districts_are_dissolved() {
+		let (sessions, datasets, shapefiles) = resources();
+		let context = Context {
+			sessions: &sessions,
+			datasets: &datasets,
+			shapefiles: &shapefiles,
+		};
+
+		let store = PlanStore::open(directory("districts")).unwrap();
+		let plan = store
+			.create(parameters(2, &[("a", 1), ("b", 1), ("c", 2)]))
+			.unwrap();
+
+		let (status, collection) = outlines(&store, context, plan.id()).await;
+		assert_eq!(status, hyper::StatusCode::OK);
+		assert_eq!(collection["features"][0]["properties"]["district"], 1);
+		assert_eq!(collection["features"][0]["properties"]["blocks"], 2);
+		assert_eq!(
+			collection["features"][0]["geometry"]["type"],
+			"MultiPolygon"
+		);
+		assert_eq!(
+			collection["features"][0]["geometry"]["coordinates"]
+				.as_array()
+				.unwrap()
+				.len(),
+			1
+		);
+
+		// Changing the plan draws the outlines again.
+		let reassignments: Reassignments = serde_json::from_str("{\"assignments\":{\"b\":2}}").unwrap();
+		super::super::reassign(&store, context, plan.id(), reassignments);
+
+		let (_, collection) = outlines(&store, context, plan.id()).await;
+		assert_eq!(collection["features"][0]["properties"]["blocks"], 1);
+		assert_eq!(collection["features"][1]["properties"]["blocks"], 2);
+	}
+
+	#[tokio::test]
+	async fn sessions_without_shapefiles_are_unprocessable() {
+		let (mut sessions, datasets, shapefiles) = resources();
+		let configuration: SessionConfiguration = serde_json::from_str("{}").unwrap();
+		sessions.insert(
+			"session".to_string(),
+			Session::new(configuration, &datasets, &shapefiles).unwrap(),
+		);
+		let context = Context {
+			sessions: &sessions,
+			datasets: &datasets,
+			shapefiles: &shapefiles,
+		};
+
+		let store = PlanStore::open(directory("no-districts")).unwrap();
+		let plan = store.create(parameters(2, &[])).unwrap();
+
+		assert_eq!(
+			super::show(&store, context, plan.id()).status(),
+			hyper::StatusCode::UNPROCESSABLE_ENTITY
+		);
+		assert_eq!(
+			super::show(&store, context, 7).status(),
+			hyper::StatusCode::NOT_FOUND
+		);
+	}
+}
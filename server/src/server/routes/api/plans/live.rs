@@ -0,0 +1,378 @@
+use std::{
+	collections::HashMap,
+	sync::{Arc, Mutex},
+};
+
+use futures::{SinkExt, StreamExt};
+use hyper::body::Body;
+use tokio::sync::{
+	broadcast::{self, error::RecvError},
+	watch,
+};
+use warp::{
+	ws::{Message, WebSocket, Ws},
+	Reply,
+};
+
+use super::{
+	super::{resources::Store, responses::empty_response},
+	metrics::{metrics, PlanMetrics},
+	Context, Plan, PlanError, PlanId, PlanStore, Reassignments,
+};
+
+/// A message pushed to a connected client
+#[derive(Debug, serde::Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Update<'a> {
+	/// The metrics of a new version of the plan, sent to every client
+	Metrics(&'a PlanMetrics),
+	/// Why a client's own reassignments were rejected
+	Error { error: &'a str },
+}
+
+async fn send(
+	sink: &mut futures::stream::SplitSink<WebSocket, Message>,
+	update: &Update<'_>,
+) -> bool {
+	let text: String = serde_json::to_string(update).expect("failed to serialize update");
+
+	sink.send(Message::text(text)).await.is_ok()
+}
+
+/// The latest metrics of a plan, as its clients receive them
+type Metrics = watch::Receiver<Arc<PlanMetrics>>;
+
+/// The metrics of the plans with clients connected, computed once per version
+/// for all of a plan's clients
+#[derive(Debug, Default)]
+pub struct Feeds {
+	entries: Mutex<HashMap<PlanId, (usize, Metrics)>>,
+}
+
+impl Feeds {
+	/// Follow the metrics of a plan, starting a feed of them if there's none
+	///
+	/// `plan` and `receiver` are only used to start the feed; otherwise the
+	/// client gets the feed's latest metrics.
+	async fn join(
+		&self,
+		store: &'static PlanStore,
+		resources: &'static Store,
+		plan: Plan,
+		receiver: broadcast::Receiver<Arc<Plan>>,
+	) -> Option<Metrics> {
+		if let Some(metrics) = self.follow(plan.id) {
+			return Some(metrics);
+		}
+
+		let (id, version): (PlanId, u64) = (plan.id, plan.version);
+		let initial: PlanMetrics = compute(resources, Arc::new(plan)).await?;
+		let (sender, metrics) = watch::channel(Arc::new(initial));
+
+		{
+			let mut entries = self.entries.lock().expect("feed lock poisoned");
+
+			// Another client may have started a feed meanwhile.
+			if let Some((clients, metrics)) = entries.get_mut(&id) {
+				*clients += 1;
+				return Some(metrics.clone());
+			}
+
+			entries.insert(id, (1, metrics.clone()));
+		}
+
+		tokio::spawn(feed(store, resources, id, version, sender, receiver));
+
+		Some(metrics)
+	}
+
+	fn follow(&self, id: PlanId) -> Option<Metrics> {
+		let mut entries = self.entries.lock().expect("feed lock poisoned");
+
+		entries.get_mut(&id).map(|(clients, metrics)| {
+			*clients += 1;
+			metrics.clone()
+		})
+	}
+
+	/// Stop following a plan's metrics, ending the feed after its last client
+	fn leave(&self, id: PlanId) {
+		let mut entries = self.entries.lock().expect("feed lock poisoned");
+
+		if let Some((clients, _)) = entries.get_mut(&id) {
+			*clients -= 1;
+			if *clients == 0 {
+				entries.remove(&id);
+			}
+		}
+	}
+}
+
+/// Compute the metrics of a version of a plan, without blocking the runtime
+async fn compute(resources: &'static Store, plan: Arc<Plan>) -> Option<PlanMetrics> {
+	// The resources are looked up afresh for each version, so that a reload
+	// reaches plans that are already being followed.
+	tokio::task::spawn_blocking(move || metrics(&plan, resources.current().context()))
+		.await
+		.map_err(|error| log::warn!("Error computing plan metrics: {}", error))
+		.ok()
+}
+
+/// Compute the metrics of each new version of a plan for its clients, until
+/// they have all left or the plan is deleted
+async fn feed(
+	store: &'static PlanStore,
+	resources: &'static Store,
+	id: PlanId,
+	mut version: u64,
+	sender: watch::Sender<Arc<PlanMetrics>>,
+	mut receiver: broadcast::Receiver<Arc<Plan>>,
+) {
+	loop {
+		let plan: Arc<Plan> = tokio::select! {
+			_ = sender.closed() => break,
+			update = receiver.recv() => match update {
+				Ok(plan) => plan,
+				// Skip ahead to the current version; the stale ones still in the
+				// channel are ignored below.
+				Err(RecvError::Lagged(_)) => match store.get(id) {
+					Some(plan) => Arc::new(plan),
+					None => break,
+				},
+				Err(RecvError::Closed) => break,
+			},
+		};
+
+		if plan.version <= version {
+			continue;
+		}
+		version = plan.version;
+
+		let metrics: Option<PlanMetrics> = compute(resources, plan).await;
+		if metrics.map_or(true, |metrics| sender.send(Arc::new(metrics)).is_err()) {
+			break;
+		}
+	}
+}
+
+/// Apply a client's reassignments; the new version reaches every client
+/// (including this one) through the plan's channel.
+///
+/// Persisting the plan blocks, so this is to be run with `spawn_blocking`.
+fn apply(store: &PlanStore, context: Context, id: PlanId, text: &str) -> Result<(), PlanError> {
+	let reassignments: Reassignments =
+		serde_json::from_str(text).map_err(|error| PlanError::Invalid(error.to_string()))?;
+
+	super::checked_reassign(store, context, id, &reassignments).map(|_| ())
+}
+
+async fn connection(
+	socket: WebSocket,
+	store: &'static PlanStore,
+	resources: &'static Store,
+	plan: Plan,
+	receiver: broadcast::Receiver<Arc<Plan>>,
+) {
+	let id: PlanId = plan.id;
+
+	if let Some(metrics) = store.feeds.join(store, resources, plan, receiver).await {
+		serve(socket, store, resources, id, metrics).await;
+		store.feeds.leave(id);
+	}
+}
+
+async fn serve(
+	socket: WebSocket,
+	store: &'static PlanStore,
+	resources: &'static Store,
+	id: PlanId,
+	mut metrics: Metrics,
+) {
+	let (mut sink, mut stream) = socket.split();
+
+	let initial: Arc<PlanMetrics> = metrics.borrow_and_update().clone();
+	if !send(&mut sink, &Update::Metrics(&initial)).await {
+		return;
+	}
+
+	loop {
+		tokio::select! {
+			message = stream.next() => {
+				let message: Message = match message {
+					Some(Ok(message)) if !message.is_close() => message,
+					_ => break,
+				};
+
+				// Pings are answered for us, and there's nothing to do with binary
+				// messages.
+				let text: String = match message.to_str() {
+					Ok(text) => text.to_string(),
+					Err(()) => continue,
+				};
+
+				let applied = tokio::task::spawn_blocking(move || {
+					apply(store, resources.current().context(), id, &text)
+				});
+
+				let error: String = match applied.await {
+					Ok(Ok(())) => continue,
+					Ok(Err(PlanError::NotFound)) => break,
+					Ok(Err(PlanError::Invalid(error))) => error,
+					Ok(Err(PlanError::Conflict)) => "the plan changed; try again".to_string(),
+					Ok(Err(PlanError::Storage(error))) => {
+						log::warn!("Error persisting plan: {}", error);
+						"the plan couldn't be saved".to_string()
+					}
+					Err(error) => {
+						log::warn!("Error applying reassignments: {}", error);
+						break;
+					}
+				};
+
+				if !send(&mut sink, &Update::Error { error: &error }).await {
+					break;
+				}
+			}
+			changed = metrics.changed() => {
+				if changed.is_err() {
+					break;
+				}
+
+				let update: Arc<PlanMetrics> = metrics.borrow_and_update().clone();
+				if !send(&mut sink, &Update::Metrics(&update)).await {
+					break;
+				}
+			}
+		}
+	}
+
+	let _ = sink.close().await;
+}
+
+/// Upgrade to a WebSocket streaming the metrics of every version of a plan
+///
+/// Clients receive the metrics of the current version as soon as they
+/// connect, and may send `Reassignments` to change the plan.
+pub fn upgrade(
+	store: &'static PlanStore,
+	resources: &'static Store,
+	id: PlanId,
+	ws: Ws,
+) -> hyper::Response<Body> {
+	match store.subscribe(id) {
+		Some((plan, receiver)) => ws
+			.on_upgrade(move |socket| connection(socket, store, resources, plan, receiver))
+			.into_response(),
+		None => empty_response(hyper::StatusCode::NOT_FOUND),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::{
+		tests::{directory, parameters, resources},
+		PlanStore,
+	};
+	use crate::server::routes::api::resources::{Resources, Store};
+	use std::sync::Arc;
+	use warp::{test::WsClient, ws::Message};
+
+	fn filter() -> warp::filters::BoxedFilter<(impl warp::Reply,)> {
+		let (sessions, datasets, shapefiles) = resources();
+		let loaded: &'static Store = Box::leak(Box::new(Store::from(Resources::new(
+			shapefiles, datasets, sessions,
+		))));
+
+		let store: &'static PlanStore =
+			Box::leak(Box::new(PlanStore::open(directory("live")).unwrap()));
+		store.create(parameters(2, &[("a", 1)])).unwrap();
+
+		crate::server::routes::api::plans(store, loaded).unwrap()
+	}
+
+	async fn receive(client: &mut WsClient) -> serde_json::Value {
+		let message: Message = client.recv().await.unwrap();
+		serde_json::from_str(message.to_str().unwrap()).unwrap()
+	}
+
+	#[tokio::test]
+	async fn collaborators_see_each_others_changes() {
+		let filter = filter();
+
+		let mut alice = warp::test::ws()
+			.path("/plans/1/live")
+			.handshake(filter.clone())
+			.await
+			.unwrap();
+		let mut bob = warp::test::ws()
+			.path("/plans/1/live")
+			.handshake(filter)
+			.await
+			.unwrap();
+
+		let snapshot = receive(&mut alice).await;
+		assert_eq!(snapshot["type"], "metrics");
+		assert_eq!(snapshot["version"], 0);
+		assert_eq!(snapshot["districts"][0]["blocks"], 1);
+		assert_eq!(receive(&mut bob).await["version"], 0);
+
+		alice.send_text("{\"assignments\":{\"a\":2,\"b\":2}}").await;
+
+		for client in [&mut alice, &mut bob].iter_mut() {
+			let update = receive(client).await;
+			assert_eq!(update["version"], 1);
+			assert_eq!(update["districts"][0]["blocks"], 0);
+			assert_eq!(update["districts"][1]["blocks"], 2);
+		}
+
+		bob.send_text("{\"assignments\":{\"a\":3}}").await;
+		let error = receive(&mut bob).await;
+		assert_eq!(error["type"], "error");
+		assert_eq!(error["error"], "district 3 of a is out of range");
+
+		bob.send_text("{\"assignments\":{\"a\":null}}").await;
+		assert_eq!(receive(&mut alice).await["version"], 2);
+		assert_eq!(receive(&mut bob).await["version"], 2);
+	}
+
+	#[tokio::test]
+	async fn clients_of_a_plan_share_its_metrics() {
+		let (sessions, datasets, shapefiles) = resources();
+		let loaded: &'static Store = Box::leak(Box::new(Store::from(Resources::new(
+			shapefiles, datasets, sessions,
+		))));
+		let store: &'static PlanStore =
+			Box::leak(Box::new(PlanStore::open(directory("feeds")).unwrap()));
+		let id = store.create(parameters(2, &[("a", 1)])).unwrap().id();
+
+		let (plan, receiver) = store.subscribe(id).unwrap();
+		let alice = store
+			.feeds
+			.join(store, loaded, plan, receiver)
+			.await
+			.unwrap();
+		let (plan, receiver) = store.subscribe(id).unwrap();
+		let bob = store
+			.feeds
+			.join(store, loaded, plan, receiver)
+			.await
+			.unwrap();
+		assert!(Arc::ptr_eq(&alice.borrow(), &bob.borrow()));
+
+		// Following the feed makes for a third client.
+		store.feeds.leave(id);
+		assert!(store.feeds.follow(id).is_some());
+		store.feeds.leave(id);
+		store.feeds.leave(id);
+		assert!(store.feeds.follow(id).is_none());
+	}
+
+	#[tokio::test]
+	async fn unknown_plans_are_not_upgraded() {
+		assert!(warp::test::ws()
+			.path("/plans/7/live")
+			.handshake(filter())
+			.await
+			.is_err());
+	}
+}
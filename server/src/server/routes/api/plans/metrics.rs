@@ -0,0 +1,142 @@
+use std::{collections::HashMap, sync::Arc};
+
+use distringo::geometry::{PartSummary, Topology};
+
+use super::{
+	super::{datasets::Dataset, shapefiles::Shapefile},
+	totals, Context, District, Plan, PlanId, BLOCK_GEOID_LENGTH,
+};
+
+/// How a single district measures up
+///
+/// Shape metrics are only available when the plan's session has a block
+/// shapefile, and `deviation` only when it has a dataset.
+#[derive(Debug, PartialEq, serde::Serialize)]
+pub struct DistrictMetrics {
+	district: District,
+	population: u64,
+	blocks: usize,
+	/// Relative deviation from the ideal population, e.g. `-0.02` for 2% under
+	deviation: Option<f64>,
+	pieces: Option<usize>,
+	/// `None` for districts without any blocks
+	contiguous: Option<bool>,
+	polsby_popper: Option<f64>,
+}
+
+#[derive(Debug, PartialEq, serde::Serialize)]
+pub struct PlanMetrics {
+	id: PlanId,
+	version: u64,
+	ideal_population: Option<f64>,
+	districts: Vec<DistrictMetrics>,
+}
+
+impl PlanMetrics {
+	pub fn version(&self) -> u64 {
+		self.version
+	}
+}
+
+/// The population each district would have if they were all equal
+fn ideal_population(plan: &Plan, populations: &HashMap<String, u64>) -> Option<f64> {
+	let total: u64 = populations
+		.iter()
+		.filter(|(geoid, _)| geoid.len() == BLOCK_GEOID_LENGTH)
+		.map(|(_, population)| population)
+		.sum();
+
+	if total > 0 && plan.districts > 0 {
+		Some(total as f64 / f64::from(plan.districts))
+	} else {
+		None
+	}
+}
+
+fn shapes(plan: &Plan, topology: &Topology) -> Vec<PartSummary> {
+	let mut parts: Vec<Option<usize>> = vec![None; topology.len()];
+
+	for (geoid, district) in &plan.assignments {
+		if let Some(idx) = topology.index_of(geoid) {
+			parts[idx] = (*district as usize).checked_sub(1);
+		}
+	}
+
+	topology.summarize(&parts, plan.districts as usize)
+}
+
+/// Compute population balance, contiguity and compactness for each district
+pub fn metrics(plan: &Plan, context: Context) -> PlanMetrics {
+	let dataset: Option<Arc<Dataset>> = context.dataset(&plan.session);
+	let populations: Option<&HashMap<String, u64>> = dataset.as_deref().map(Dataset::populations);
+	let ideal: Option<f64> = populations.and_then(|populations| ideal_population(plan, populations));
+	let shapefile: Option<Arc<Shapefile>> = context.shapefile(&plan.session);
+	let shapes: Option<Vec<PartSummary>> = shapefile
+		.as_deref()
+		.map(Shapefile::topology)
+		.map(|topology| shapes(plan, topology));
+
+	let districts: Vec<DistrictMetrics> = totals(plan, populations)
+		.districts
+		.into_iter()
+		.enumerate()
+		.map(|(idx, total)| {
+			let shape: Option<&PartSummary> = shapes.as_ref().map(|shapes| &shapes[idx]);
+
+			DistrictMetrics {
+				district: total.district,
+				population: total.population,
+				blocks: total.blocks,
+				deviation: ideal.map(|ideal| total.population as f64 / ideal - 1.0),
+				pieces: shape.map(|shape| shape.pieces),
+				contiguous: shape
+					.filter(|shape| shape.pieces > 0)
+					.map(|shape| shape.pieces == 1),
+				polsby_popper: shape.map(PartSummary::polsby_popper),
+			}
+		})
+		.collect();
+
+	PlanMetrics {
+		id: plan.id,
+		version: plan.version,
+		ideal_population: ideal,
+		districts,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::{tests::resources, Context, Plan};
+
+	#[test]
+	fn split_districts_are_not_contiguous() {
+		let (sessions, datasets, shapefiles) = resources();
+		let context = Context {
+			sessions: &sessions,
+			datasets: &datasets,
+			shapefiles: &shapefiles,
+		};
+
+		let plan: Plan = serde_json::from_str(
+			"{\"id\":1,\"name\":\"Plan\",\"session\":\"session\",\"districts\":3,\"version\":3,\
+			\"assignments\":{\"a\":1,\"b\":2,\"c\":1}}",
+		)
+		.unwrap();
+
+		let metrics = super::metrics(&plan, context);
+		let value = serde_json::to_value(&metrics).unwrap();
+
+		assert_eq!(value["version"], 3);
+		assert_eq!(value["ideal_population"], serde_json::Value::Null);
+		assert_eq!(value["districts"][0]["blocks"], 2);
+		assert_eq!(value["districts"][0]["pieces"], 2);
+		assert_eq!(value["districts"][0]["contiguous"], false);
+		assert_eq!(value["districts"][1]["contiguous"], true);
+		assert_eq!(value["districts"][2]["blocks"], 0);
+		assert_eq!(value["districts"][2]["contiguous"], serde_json::Value::Null);
+
+		let square: f64 = value["districts"][1]["polsby_popper"].as_f64().unwrap();
+		assert!((square - core::f64::consts::PI / 4.0).abs() < 0.01);
+	}
+}
@@ -0,0 +1,834 @@
+//! The datasets, shapefiles and sessions loaded from the configuration
+//!
+//! These can be reloaded while the server runs.  A reload builds a whole new
+//! set of resources and swaps it in at once, so requests already being served
+//! keep the set they started with.
+//!
+//! Datasets and shapefiles are only loaded the first time they're asked for,
+//! on a thread where blocking is fine.  If `server.memory_limit` is set, the
+//! ones used least recently are unloaded again to keep within it, and loaded
+//! again when next asked for.  Resources grow as they're used, so the limit is
+//! checked every time one is asked for.
+
+use std::{
+	collections::HashMap,
+	convert::TryFrom,
+	path::{Path, PathBuf},
+	sync::{
+		atomic::{AtomicU64, AtomicUsize, Ordering},
+		Arc, Mutex, RwLock, Weak,
+	},
+	time::SystemTime,
+};
+
+use warp::{Filter, Reply};
+
+use super::{
+	conditional,
+	datasets::Dataset,
+	plans::Context,
+	responses::{empty_response, Refusal},
+	sessions::{Session, SessionConfiguration},
+	shapefiles::Shapefile,
+};
+
+/// The configuration of a resource that is loaded from a file
+pub trait FileBacked: serde::de::DeserializeOwned + serde::Serialize {
+	/// The file the resource is loaded from
+	fn path(&self) -> &Path;
+
+	/// Every file the resource is read from, any of which changing calls for
+	/// loading it again
+	fn files(&self) -> Vec<PathBuf> {
+		vec![self.path().to_path_buf()]
+	}
+}
+
+/// A resource loaded from an entry of the configuration
+pub trait Resource: Sized + Send + Sync + 'static {
+	type Configuration: FileBacked + Send + Sync + 'static;
+
+	/// Load the resource `id` from its configuration
+	fn load(id: &str, configuration: &Self::Configuration) -> distringo::Result<Self>;
+
+	/// Roughly how many bytes the resource takes up
+	fn footprint(&self) -> usize;
+}
+
+/// How many bytes loaded resources may take up, from `server.memory_limit` in
+/// mebibytes
+///
+/// A missing limit, or a limit of zero, is no limit.
+pub fn memory_limit(cfg: &config::Config) -> distringo::Result<usize> {
+	let mebibytes: i64 = match cfg.get_int("server.memory_limit") {
+		Ok(mebibytes) => mebibytes,
+		Err(config::ConfigError::NotFound(_)) => 0,
+		Err(error) => return Err(error.into()),
+	};
+
+	usize::try_from(mebibytes)
+		.ok()
+		.and_then(|mebibytes| mebibytes.checked_mul(1 << 20))
+		.ok_or_else(|| {
+			config::ConfigError::Message("the memory limit must be a number of mebibytes".to_string())
+				.into()
+		})
+}
+
+/// What a resource was loaded from, to tell whether it needs loading again
+#[derive(Debug, PartialEq)]
+struct Source {
+	configuration: serde_json::Value,
+	files: Vec<PathBuf>,
+	/// When each of `files` was last modified
+	modified: Vec<Option<SystemTime>>,
+}
+
+impl Source {
+	fn new<C: FileBacked>(configuration: &C) -> Self {
+		let files: Vec<PathBuf> = configuration.files();
+
+		Self {
+			configuration: serde_json::to_value(configuration)
+				.expect("failed to serialize configuration"),
+			modified: modification_times(&files),
+			files,
+		}
+	}
+
+	/// Whether any of the files has changed since the source was made
+	fn changed(&self) -> bool {
+		modification_times(&self.files) != self.modified
+	}
+}
+
+/// When each of some files was last modified
+fn modification_times(files: &[PathBuf]) -> Vec<Option<SystemTime>> {
+	files.iter().map(conditional::modified).collect()
+}
+
+/// A resource which can be unloaded to make room for others
+trait Unload: Send + Sync {
+	/// The key of the resource in the configuration
+	fn key(&self) -> &str;
+
+	/// How many bytes the resource takes up now, or zero if it isn't loaded
+	fn footprint(&self) -> usize;
+
+	/// When the resource was last asked for, by the clock of its [`Budget`]
+	fn last_used(&self) -> u64;
+
+	/// Unload the resource, unless it's being loaded or couldn't be loaded
+	/// again
+	///
+	/// Whoever still holds the resource keeps it until they're done.
+	fn unload(&self) -> bool;
+}
+
+/// How much memory loaded resources may take up
+///
+/// One budget is shared by every set of resources loaded by a [`Store`], so
+/// resources kept alive by requests still being served count against it too.
+#[derive(Default)]
+struct Budget {
+	/// In bytes; zero is no limit
+	limit: AtomicUsize,
+	/// Counts every use of a resource, to tell which were used least recently
+	clock: AtomicU64,
+	slots: Mutex<Vec<Weak<dyn Unload>>>,
+}
+
+impl Budget {
+	fn tick(&self) -> u64 {
+		self.clock.fetch_add(1, Ordering::Relaxed) + 1
+	}
+
+	fn register(&self, slot: Weak<dyn Unload>) {
+		let mut slots = self.slots.lock().expect("budget lock poisoned");
+
+		slots.retain(|slot| slot.strong_count() > 0);
+		slots.push(slot);
+	}
+
+	/// Unload the resources used least recently until the rest fit
+	///
+	/// The resource used most recently is kept, even if it doesn't fit alone.
+	fn enforce(&self) {
+		let limit: usize = self.limit.load(Ordering::Relaxed);
+		if limit == 0 {
+			return;
+		}
+
+		let mut slots: Vec<Arc<dyn Unload>> = self
+			.slots
+			.lock()
+			.expect("budget lock poisoned")
+			.iter()
+			.filter_map(Weak::upgrade)
+			.collect();
+		slots.sort_by_key(|slot| slot.last_used());
+
+		let footprints: Vec<usize> = slots.iter().map(|slot| slot.footprint()).collect();
+		let mut used: usize = footprints.iter().sum();
+		slots.pop();
+
+		for (slot, footprint) in slots.into_iter().zip(footprints) {
+			if used <= limit {
+				break;
+			}
+
+			if footprint > 0 && slot.unload() {
+				log::info!("Unloaded {} to stay within the memory limit", slot.key());
+				used -= footprint;
+			}
+		}
+	}
+}
+
+/// Why a resource couldn't be loaded
+#[derive(Clone, Debug)]
+pub struct Failure {
+	/// When each of the files was last modified as of loading it
+	modified: Vec<Option<SystemTime>>,
+	message: String,
+}
+
+impl core::fmt::Display for Failure {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		f.write_str(&self.message)
+	}
+}
+
+/// Whether a resource is loaded
+enum State<T> {
+	Unloaded,
+	Loaded(Arc<T>),
+	/// Loading failed, and isn't tried again until a file changes
+	Failed(Failure),
+}
+
+/// A resource, loaded or not
+struct Slot<T: Resource> {
+	id: String,
+	key: String,
+	/// What to load the resource from, or `None` for a resource that was given
+	/// already loaded, and so is never unloaded
+	configuration: Option<T::Configuration>,
+	source: Option<Source>,
+	state: Mutex<State<T>>,
+	/// How many bytes the resource took up when last measured
+	footprint: AtomicUsize,
+	last_used: AtomicU64,
+}
+
+impl<T: Resource> Slot<T> {
+	/// The resource, which is loaded if it isn't already
+	///
+	/// Every resource is measured again afterwards, since this one may have
+	/// grown since it was last asked for.
+	fn get(&self, budget: &Budget) -> Result<Arc<T>, Failure> {
+		self.last_used.store(budget.tick(), Ordering::Relaxed);
+
+		let resource: Arc<T> = self.load()?;
+		budget.enforce();
+
+		Ok(resource)
+	}
+
+	fn load(&self) -> Result<Arc<T>, Failure> {
+		let mut state = self.state.lock().expect("resource lock poisoned");
+		if let State::Loaded(resource) = &*state {
+			return Ok(Arc::clone(resource));
+		}
+
+		let modified: Vec<Option<SystemTime>> = self
+			.source
+			.as_ref()
+			.map_or_else(Vec::new, |source| modification_times(&source.files));
+		match &*state {
+			State::Failed(failure) if failure.modified == modified => return Err(failure.clone()),
+			_ => log::info!("Loading {}", self.key),
+		}
+
+		let configuration: &T::Configuration = self
+			.configuration
+			.as_ref()
+			.expect("resources given loaded are never unloaded");
+
+		let loaded: Result<Arc<T>, Failure> =
+			T::load(&self.id, configuration)
+				.map(Arc::new)
+				.map_err(|error| {
+					log::error!("Error loading {}: {}", self.key, error);
+					Failure {
+						modified,
+						message: error.to_string(),
+					}
+				});
+
+		*state = match &loaded {
+			Ok(resource) => State::Loaded(Arc::clone(resource)),
+			Err(failure) => State::Failed(failure.clone()),
+		};
+
+		loaded
+	}
+
+	fn is_loaded(&self) -> bool {
+		matches!(self.state.try_lock().as_deref(), Ok(State::Loaded(_)))
+	}
+}
+
+impl<T: Resource> Unload for Slot<T> {
+	fn key(&self) -> &str {
+		&self.key
+	}
+
+	/// The resource is measured unless it's being loaded, in which case it
+	/// hasn't changed since it was last measured
+	fn footprint(&self) -> usize {
+		let footprint: usize = match self.state.try_lock().as_deref() {
+			Ok(State::Loaded(resource)) => resource.footprint(),
+			Ok(_) => 0,
+			Err(_) => return self.footprint.load(Ordering::Relaxed),
+		};
+
+		self.footprint.store(footprint, Ordering::Relaxed);
+		footprint
+	}
+
+	fn last_used(&self) -> u64 {
+		self.last_used.load(Ordering::Relaxed)
+	}
+
+	fn unload(&self) -> bool {
+		if self.configuration.is_none() {
+			return false;
+		}
+
+		match self.state.try_lock().as_deref_mut() {
+			Ok(state @ State::Loaded(_)) => {
+				*state = State::Unloaded;
+				self.footprint.store(0, Ordering::Relaxed);
+				true
+			}
+			_ => false,
+		}
+	}
+}
+
+/// The resources of one section of the configuration, each loaded the first
+/// time it's asked for
+pub struct Lazy<T: Resource> {
+	slots: HashMap<String, Arc<Slot<T>>>,
+	budget: Arc<Budget>,
+}
+
+impl<T: Resource> core::fmt::Debug for Lazy<T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		f.debug_set().entries(self.slots.keys()).finish()
+	}
+}
+
+impl<T: Resource> Default for Lazy<T> {
+	fn default() -> Self {
+		Self {
+			slots: HashMap::new(),
+			budget: Arc::default(),
+		}
+	}
+}
+
+/// Resources that were loaded up front, which are never unloaded
+impl<T: Resource> From<HashMap<String, Arc<T>>> for Lazy<T> {
+	fn from(resources: HashMap<String, Arc<T>>) -> Self {
+		let slots = resources
+			.into_iter()
+			.map(|(id, resource)| {
+				let slot = Slot {
+					key: id.clone(),
+					id: id.clone(),
+					configuration: None,
+					source: None,
+					footprint: AtomicUsize::new(resource.footprint()),
+					state: Mutex::new(State::Loaded(resource)),
+					last_used: AtomicU64::new(0),
+				};
+
+				(id, Arc::new(slot))
+			})
+			.collect();
+
+		Self {
+			slots,
+			budget: Arc::default(),
+		}
+	}
+}
+
+/// The entries of a section of the configuration
+///
+/// A missing section has no entries.
+fn entries(
+	cfg: &config::Config,
+	section: &str,
+) -> distringo::Result<HashMap<String, config::Value>> {
+	match cfg.get_table(section) {
+		Ok(entries) => Ok(entries),
+		Err(config::ConfigError::NotFound(_)) => Ok(HashMap::new()),
+		Err(error) => Err(error.into()),
+	}
+}
+
+impl<T: Resource> Lazy<T> {
+	/// Read every entry of a section of the configuration, without loading any
+	///
+	/// Entries whose configuration and file are the same as in `previous` keep
+	/// what was loaded for them.  Every file must exist, but isn't read until
+	/// the resource is first asked for.
+	fn load(
+		cfg: &config::Config,
+		section: &str,
+		previous: &Self,
+		budget: &Arc<Budget>,
+	) -> distringo::Result<Self> {
+		let mut slots: HashMap<String, Arc<Slot<T>>> = HashMap::new();
+
+		for (id, value) in entries(cfg, section)? {
+			let key: String = format!("{}.{}", section, id);
+			let configuration: T::Configuration = value.try_into()?;
+
+			if let Err(error) = std::fs::metadata(configuration.path()) {
+				log::error!("Error loading {}: {}", key, error);
+				return Err(error.into());
+			}
+
+			let source = Source::new(&configuration);
+
+			let slot: Arc<Slot<T>> = match previous.slots.get(&id) {
+				Some(slot) if slot.source.as_ref() == Some(&source) => Arc::clone(slot),
+				_ => {
+					let slot = Arc::new(Slot {
+						id: id.clone(),
+						key,
+						configuration: Some(configuration),
+						source: Some(source),
+						state: Mutex::new(State::Unloaded),
+						footprint: AtomicUsize::new(0),
+						last_used: AtomicU64::new(0),
+					});
+
+					let unload: Arc<dyn Unload> = Arc::clone(&slot) as Arc<dyn Unload>;
+					budget.register(Arc::downgrade(&unload));
+
+					slot
+				}
+			};
+
+			slots.insert(id, slot);
+		}
+
+		Ok(Self {
+			slots,
+			budget: Arc::clone(budget),
+		})
+	}
+
+	/// The resource `id`, loading it if it isn't already
+	///
+	/// A resource that fails to load is treated as if there were no such
+	/// resource.  Loading isn't tried again until one of its files changes.
+	pub fn get(&self, id: &str) -> Option<Arc<T>> {
+		self.slots.get(id)?.get(&self.budget).ok()
+	}
+
+	/// The resource `id` for a request, or the response to send without it
+	///
+	/// There is no such resource if it isn't configured, but a resource that
+	/// fails to load is an error of the server's.
+	pub fn fetch(&self, id: &str) -> Result<Arc<T>, Refusal> {
+		let slot: &Slot<T> = self
+			.slots
+			.get(id)
+			.ok_or_else(|| empty_response(hyper::StatusCode::NOT_FOUND))?;
+
+		slot
+			.get(&self.budget)
+			.map_err(|_| empty_response(hyper::StatusCode::INTERNAL_SERVER_ERROR).into())
+	}
+
+	/// The configuration of resource `id`, if it was loaded from one
+	pub fn configuration(&self, id: &str) -> Option<&T::Configuration> {
+		self.slots.get(id)?.configuration.as_ref()
+	}
+
+	pub fn contains_key(&self, id: &str) -> bool {
+		self.slots.contains_key(id)
+	}
+
+	pub fn keys(&self) -> impl Iterator<Item = &String> {
+		self.slots.keys()
+	}
+
+	pub fn len(&self) -> usize {
+		self.slots.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.slots.is_empty()
+	}
+
+	/// Whether resource `id` is loaded, without loading it
+	pub fn is_loaded(&self, id: &str) -> bool {
+		self.slots.get(id).is_some_and(|slot| slot.is_loaded())
+	}
+
+	/// Whether any of the files has changed since it was read
+	fn stale(&self) -> bool {
+		self
+			.slots
+			.values()
+			.filter_map(|slot| slot.source.as_ref())
+			.any(Source::changed)
+	}
+}
+
+/// A complete set of resources
+#[derive(Default)]
+pub struct Resources {
+	shapefiles: Lazy<Shapefile>,
+	datasets: Lazy<Dataset>,
+	sessions: HashMap<String, Session>,
+	budget: Arc<Budget>,
+}
+
+impl Resources {
+	/// Resources that weren't loaded from a configuration
+	///
+	/// None of these are reused by a reload.
+	pub fn new(
+		shapefiles: Lazy<Shapefile>,
+		datasets: Lazy<Dataset>,
+		sessions: HashMap<String, Session>,
+	) -> Self {
+		Self {
+			shapefiles,
+			datasets,
+			sessions,
+			budget: Arc::default(),
+		}
+	}
+
+	/// Read the resources of a configuration, keeping what was loaded by
+	/// `previous` for those that haven't changed
+	///
+	/// Sessions are cheap to resolve, so they're always resolved again.
+	pub fn load(cfg: &config::Config, previous: &Self) -> distringo::Result<Self> {
+		let limit: usize = memory_limit(cfg)?;
+		let budget: &Arc<Budget> = &previous.budget;
+
+		let shapefiles: Lazy<Shapefile> = Lazy::load(cfg, "shapefiles", &previous.shapefiles, budget)?;
+		let datasets: Lazy<Dataset> = Lazy::load(cfg, "datasets", &previous.datasets, budget)?;
+
+		let sessions: HashMap<String, Session> = entries(cfg, "sessions")?
+			.into_iter()
+			.map(|(id, value)| -> distringo::Result<(String, Session)> {
+				let config: SessionConfiguration = value.try_into()?;
+				let session = Session::new(config, &datasets, &shapefiles)?;
+
+				Ok((id, session))
+			})
+			.collect::<distringo::Result<_>>()?;
+
+		budget.limit.store(limit, Ordering::Relaxed);
+		budget.enforce();
+
+		Ok(Self {
+			shapefiles,
+			datasets,
+			sessions,
+			budget: Arc::clone(budget),
+		})
+	}
+
+	pub fn shapefiles(&self) -> &Lazy<Shapefile> {
+		&self.shapefiles
+	}
+
+	pub fn datasets(&self) -> &Lazy<Dataset> {
+		&self.datasets
+	}
+
+	pub fn sessions(&self) -> &HashMap<String, Session> {
+		&self.sessions
+	}
+
+	/// The resources plans are validated and totaled against
+	pub fn context(&self) -> Context<'_> {
+		Context {
+			sessions: self.sessions(),
+			datasets: self.datasets(),
+			shapefiles: self.shapefiles(),
+		}
+	}
+}
+
+/// The current resources, which a reload replaces
+pub struct Store {
+	current: RwLock<Arc<Resources>>,
+	reloading: Mutex<()>,
+}
+
+impl From<Resources> for Store {
+	fn from(resources: Resources) -> Self {
+		Self {
+			current: RwLock::new(Arc::new(resources)),
+			reloading: Mutex::new(()),
+		}
+	}
+}
+
+impl Store {
+	/// The resources as they are now
+	///
+	/// These stay loaded for as long as they're held, even if they've since
+	/// been replaced.
+	pub fn current(&self) -> Arc<Resources> {
+		Arc::clone(&self.current.read().expect("resources lock poisoned"))
+	}
+
+	/// Read the resources of a configuration and swap them in
+	///
+	/// What was loaded for anything that hasn't changed is kept.  If anything
+	/// can't be read, the current resources are kept.  Reloads happen one at a
+	/// time.
+	pub fn reload(&self, cfg: &config::Config) -> distringo::Result<()> {
+		let _reloading = self.reloading.lock().expect("reload lock poisoned");
+
+		let resources: Resources = Resources::load(cfg, &self.current())?;
+		*self.current.write().expect("resources lock poisoned") = Arc::new(resources);
+
+		Ok(())
+	}
+
+	/// Whether any file the current resources were read from has changed
+	pub fn stale(&self) -> bool {
+		let current = self.current();
+
+		current.shapefiles.stale() || current.datasets.stale()
+	}
+}
+
+/// Serve a request on a thread where blocking is fine
+///
+/// Serving requests can load resources, which reads whole files, and make
+/// large bodies, neither of which should hold up other requests.
+pub(crate) async fn blocking<F, R>(serve: F) -> Result<warp::reply::Response, warp::Rejection>
+where
+	F: FnOnce() -> R + Send + 'static,
+	R: Reply + 'static,
+{
+	match tokio::task::spawn_blocking(serve).await {
+		Ok(reply) => Ok(reply.into_response()),
+		Err(error) => {
+			log::error!("Error serving request: {}", error);
+			Ok(empty_response(hyper::StatusCode::INTERNAL_SERVER_ERROR))
+		}
+	}
+}
+
+/// Extract the resources current when a request is made
+pub(crate) fn current(
+	store: &'static Store,
+) -> impl Filter<Extract = (Arc<Resources>,), Error = std::convert::Infallible> + Clone {
+	warp::any().map(move || store.current())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Resources, Store};
+	use std::{
+		path::Path,
+		sync::{atomic::Ordering, Arc},
+	};
+
+	const EMPTY: &str = "{\"type\":\"FeatureCollection\",\"features\":[]}";
+
+	const SQUARE: &str = r#"{"type":"FeatureCollection","features":[{"type":"Feature","properties":{"GEOID10":"180979999999999"},"geometry":{"type":"Polygon","coordinates":[[[0,0],[1,0],[1,1],[0,1],[0,0]]]}}]}"#;
+
+	/// Write `contents` to `path`, making sure the modification time moves on,
+	/// even on coarse filesystems
+	fn rewrite(path: &Path, contents: &str) {
+		let modified = std::fs::metadata(path)
+			.and_then(|metadata| metadata.modified())
+			.unwrap();
+		while std::fs::metadata(path)
+			.and_then(|metadata| metadata.modified())
+			.unwrap()
+			== modified
+		{
+			std::thread::sleep(std::time::Duration::from_millis(10));
+			std::fs::write(path, contents).unwrap();
+		}
+	}
+
+	fn config(directory: &Path) -> config::Config {
+		let yaml: String = format!(
+			"
+shapefiles:
+  first: {{ type: tabular_block, file: '{0}/first.geojson' }}
+  second: {{ type: tabular_block, file: '{0}/second.geojson' }}
+sessions:
+  session: {{ shapefiles: [first, second] }}
+",
+			directory.display()
+		);
+
+		let mut config = config::Config::default();
+		config
+			.merge(config::File::from_str(&yaml, config::FileFormat::Yaml))
+			.unwrap();
+		config
+	}
+
+	#[test]
+	fn only_changed_resources_are_reloaded() {
+		let directory = crate::server::routes::api::plans::tests::directory("resources");
+		std::fs::write(directory.join("first.geojson"), EMPTY).unwrap();
+		std::fs::write(directory.join("second.geojson"), EMPTY).unwrap();
+
+		let store = Store::from(Resources::default());
+		store.reload(&config(&directory)).unwrap();
+		let before = store.current();
+		assert_eq!(before.shapefiles().len(), 2);
+		assert!(before.sessions().contains_key("session"));
+		assert!(!before.shapefiles().is_loaded("first"));
+		assert!(!store.stale());
+
+		let (first, second) = (
+			before.shapefiles().get("first").unwrap(),
+			before.shapefiles().get("second").unwrap(),
+		);
+		assert!(before.shapefiles().is_loaded("first"));
+
+		rewrite(&directory.join("second.geojson"), EMPTY);
+		assert!(store.stale());
+
+		store.reload(&config(&directory)).unwrap();
+		let after = store.current();
+
+		assert!(after.shapefiles().is_loaded("first"));
+		assert!(!after.shapefiles().is_loaded("second"));
+		assert!(Arc::ptr_eq(
+			&first,
+			&after.shapefiles().get("first").unwrap()
+		));
+		assert!(!Arc::ptr_eq(
+			&second,
+			&after.shapefiles().get("second").unwrap()
+		));
+		assert!(!store.stale());
+	}
+
+	#[test]
+	fn least_recently_used_resources_are_unloaded() {
+		let directory = crate::server::routes::api::plans::tests::directory("resources-limited");
+		std::fs::write(directory.join("first.geojson"), EMPTY).unwrap();
+		std::fs::write(directory.join("second.geojson"), EMPTY).unwrap();
+
+		let store = Store::from(Resources::default());
+		store.reload(&config(&directory)).unwrap();
+		let resources = store.current();
+		resources.budget.limit.store(1, Ordering::Relaxed);
+
+		let first = resources.shapefiles().get("first").unwrap();
+		resources.shapefiles().get("second").unwrap();
+		assert!(!resources.shapefiles().is_loaded("first"));
+		assert!(resources.shapefiles().is_loaded("second"));
+
+		// Unloading doesn't take a shapefile from whoever is using it.
+		assert!(first.features().is_empty());
+
+		let again = resources.shapefiles().get("first").unwrap();
+		assert!(!Arc::ptr_eq(&first, &again));
+		assert!(!resources.shapefiles().is_loaded("second"));
+	}
+
+	#[test]
+	fn resources_are_measured_as_they_grow() {
+		let directory = crate::server::routes::api::plans::tests::directory("resources-growing");
+		std::fs::write(directory.join("first.geojson"), SQUARE).unwrap();
+		std::fs::write(directory.join("second.geojson"), EMPTY).unwrap();
+
+		let store = Store::from(Resources::default());
+		store.reload(&config(&directory)).unwrap();
+		let resources = store.current();
+
+		let first = resources.shapefiles().get("first").unwrap();
+		let second = resources.shapefiles().get("second").unwrap();
+		resources
+			.budget
+			.limit
+			.store(first.footprint() + second.footprint(), Ordering::Relaxed);
+
+		resources.shapefiles().get("second").unwrap();
+		assert!(resources.shapefiles().is_loaded("first"));
+
+		first.topology();
+		resources.shapefiles().get("second").unwrap();
+		assert!(!resources.shapefiles().is_loaded("first"));
+		assert!(resources.shapefiles().is_loaded("second"));
+	}
+
+	#[test]
+	fn failed_loads_are_errors_until_the_file_changes() {
+		let directory = crate::server::routes::api::plans::tests::directory("resources-broken");
+		std::fs::write(directory.join("first.geojson"), EMPTY).unwrap();
+		std::fs::write(directory.join("second.geojson"), EMPTY).unwrap();
+
+		let store = Store::from(Resources::default());
+		store.reload(&config(&directory)).unwrap();
+		let resources = store.current();
+		std::fs::write(directory.join("first.geojson"), "[").unwrap();
+
+		let status = |id: &str| {
+			resources
+				.shapefiles()
+				.fetch(id)
+				.err()
+				.map(|response| response.status())
+		};
+
+		assert_eq!(
+			status("first"),
+			Some(hyper::StatusCode::INTERNAL_SERVER_ERROR)
+		);
+		assert_eq!(
+			status("first"),
+			Some(hyper::StatusCode::INTERNAL_SERVER_ERROR)
+		);
+		assert!(!resources.shapefiles().is_loaded("first"));
+		assert!(resources.shapefiles().get("first").is_none());
+		assert_eq!(status("third"), Some(hyper::StatusCode::NOT_FOUND));
+
+		rewrite(&directory.join("first.geojson"), EMPTY);
+		assert_eq!(status("first"), None);
+		assert!(resources.shapefiles().is_loaded("first"));
+	}
+
+	#[test]
+	fn failed_reloads_keep_the_current_resources() {
+		let directory = crate::server::routes::api::plans::tests::directory("resources-failed");
+		std::fs::write(directory.join("first.geojson"), EMPTY).unwrap();
+		std::fs::write(directory.join("second.geojson"), EMPTY).unwrap();
+
+		let store = Store::from(Resources::default());
+		store.reload(&config(&directory)).unwrap();
+		let before = store.current();
+
+		std::fs::remove_file(directory.join("second.geojson")).unwrap();
+		assert!(store.reload(&config(&directory)).is_err());
+
+		assert!(Arc::ptr_eq(&before, &store.current()));
+	}
+}
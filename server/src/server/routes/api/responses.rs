@@ -0,0 +1,39 @@
+use hyper::body::Body;
+
+/// A response sent instead of the one asked for, e.g. when something isn't
+/// found
+///
+/// Responses are large, so they're boxed when returned as errors.
+pub(crate) type Refusal = Box<hyper::Response<Body>>;
+
+pub(crate) fn json_response<T: serde::Serialize>(
+	status: hyper::StatusCode,
+	value: &T,
+) -> hyper::Response<Body> {
+	use warp::Reply;
+
+	warp::reply::with_status(warp::reply::json(value), status).into_response()
+}
+
+pub(crate) fn empty_response(status: hyper::StatusCode) -> hyper::Response<Body> {
+	http::response::Builder::new()
+		.status(status)
+		.body(if status == hyper::StatusCode::NO_CONTENT {
+			Body::empty()
+		} else {
+			"{}".into()
+		})
+		.unwrap()
+}
+
+pub(crate) fn unprocessable(error: &str) -> hyper::Response<Body> {
+	#[derive(serde::Serialize)]
+	struct ErrorBody<'a> {
+		error: &'a str,
+	}
+
+	json_response(
+		hyper::StatusCode::UNPROCESSABLE_ENTITY,
+		&ErrorBody { error },
+	)
+}
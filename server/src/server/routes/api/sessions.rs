@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+use hyper::body::Body;
+
+use super::{
+	datasets::Dataset, resources::Lazy, shapefiles::Shapefile, shapefiles::ShapefileType,
+	validation::Report,
+};
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+pub struct SessionConfiguration {
+	#[serde(default)]
+	datasets: Vec<String>,
+	#[serde(default)]
+	shapefiles: Vec<String>,
+}
+
+impl SessionConfiguration {
+	/// Note every reference of the session configured at `key` that isn't to a
+	/// configured dataset or shapefile
+	pub fn check(&self, key: &str, datasets: &[String], shapefiles: &[String], report: &mut Report) {
+		for id in self.datasets.iter().filter(|id| !datasets.contains(id)) {
+			report.push(
+				&format!("{}.datasets", key),
+				format_args!("no dataset named {}", id),
+			);
+		}
+
+		for id in self.shapefiles.iter().filter(|id| !shapefiles.contains(id)) {
+			report.push(
+				&format!("{}.shapefiles", key),
+				format_args!("no shapefile named {}", id),
+			);
+		}
+	}
+}
+
+/// A resource the client should load for a session
+#[derive(Debug, serde::Serialize)]
+pub struct Resource {
+	id: String,
+	#[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+	ty: Option<ShapefileType>,
+	url: String,
+}
+
+/// A validated bundle of datasets and shapefiles
+#[derive(Debug, serde::Serialize)]
+pub struct Session {
+	datasets: Vec<Resource>,
+	shapefiles: Vec<Resource>,
+}
+
+impl Session {
+	/// Resolve a session's references against the loaded resources
+	///
+	/// Fails with the first reference that doesn't name a configured dataset or
+	/// shapefile.  Nothing is loaded to resolve them.
+	pub fn new(
+		sc: SessionConfiguration,
+		datasets: &Lazy<Dataset>,
+		shapefiles: &Lazy<Shapefile>,
+	) -> distringo::Result<Self> {
+		let datasets: Vec<Resource> = sc
+			.datasets
+			.into_iter()
+			.map(|id| {
+				if datasets.contains_key(&id) {
+					Ok(Resource {
+						url: format!("/api/v0/datasets/{}", id),
+						ty: None,
+						id,
+					})
+				} else {
+					Err(distringo::Error::InvalidReference(id))
+				}
+			})
+			.collect::<distringo::Result<_>>()?;
+
+		let shapefiles: Vec<Resource> = sc
+			.shapefiles
+			.into_iter()
+			.map(|id| {
+				if let Some(ty) = shapefiles.ty(&id) {
+					Ok(Resource {
+						url: format!("/api/v0/shapefiles/{}", id),
+						ty: Some(ty),
+						id,
+					})
+				} else {
+					Err(distringo::Error::InvalidReference(id))
+				}
+			})
+			.collect::<distringo::Result<_>>()?;
+
+		Ok(Self {
+			datasets,
+			shapefiles,
+		})
+	}
+
+	/// The identifiers of the session's datasets, in configuration order
+	pub fn datasets(&self) -> impl Iterator<Item = &str> {
+		self.datasets.iter().map(|resource| resource.id.as_str())
+	}
+
+	/// The identifiers of the session's shapefiles, in configuration order
+	pub fn shapefiles(&self) -> impl Iterator<Item = &str> {
+		self.shapefiles.iter().map(|resource| resource.id.as_str())
+	}
+}
+
+#[derive(serde::Serialize)]
+struct SessionSummary<'a> {
+	id: &'a str,
+	#[serde(flatten)]
+	session: &'a Session,
+}
+
+pub fn index(sessions: &HashMap<String, Session>) -> hyper::Response<Body> {
+	use warp::Reply;
+
+	let mut ids: Vec<&String> = sessions.keys().collect();
+	ids.sort();
+
+	warp::reply::json(&ids).into_response()
+}
+
+pub fn show(sessions: &HashMap<String, Session>, id: &str) -> hyper::Response<Body> {
+	use warp::Reply;
+
+	if let Some(session) = sessions.get(id) {
+		warp::reply::json(&SessionSummary { id, session }).into_response()
+	} else {
+		http::response::Builder::new()
+			.status(hyper::StatusCode::NOT_FOUND)
+			.body("{}".into())
+			.unwrap()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Session, SessionConfiguration};
+	use crate::server::routes::api::{
+		datasets::Dataset,
+		resources::Lazy,
+		shapefiles::{Shapefile, ShapefileType},
+	};
+	use geojson::{FeatureCollection, GeoJson};
+	use std::{collections::HashMap, sync::Arc};
+
+	fn shapefiles() -> Lazy<Shapefile> {
+		let contents = GeoJson::FeatureCollection(FeatureCollection {
+			bbox: None,
+			features: Vec::new(),
+			foreign_members: None,
+		});
+
+		let mut map = HashMap::new();
+		map.insert(
+			"tabblock".to_string(),
+			Arc::new(Shapefile::new(ShapefileType::TabularBlock, contents).unwrap()),
+		);
+		map.into()
+	}
+
+	fn configuration(shapefiles: &[&str]) -> SessionConfiguration {
+		SessionConfiguration {
+			datasets: vec![],
+			shapefiles: shapefiles.iter().map(ToString::to_string).collect(),
+		}
+	}
+
+	fn sessions() -> HashMap<String, Session> {
+		let datasets: Lazy<Dataset> = Lazy::default();
+		let session = Session::new(configuration(&["tabblock"]), &datasets, &shapefiles())
+			.expect("failed to resolve session");
+
+		let mut map = HashMap::new();
+		map.insert("session".to_string(), session);
+		map
+	}
+
+	#[test]
+	fn unknown_reference_is_rejected() {
+		let datasets: Lazy<Dataset> = Lazy::default();
+		let result = Session::new(configuration(&["tabblock", "bg"]), &datasets, &shapefiles());
+
+		assert!(matches!(
+			result,
+			Err(distringo::Error::InvalidReference(id)) if id == "bg"
+		));
+	}
+
+	#[tokio::test]
+	async fn show_returns_resources() {
+		let response = super::show(&sessions(), "session");
+		assert_eq!(response.status(), hyper::StatusCode::OK);
+
+		let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+		assert_eq!(
+			body,
+			"{\"id\":\"session\",\"datasets\":[],\"shapefiles\":[{\"id\":\"tabblock\",\"type\":\"tabular_block\",\"url\":\"/api/v0/shapefiles/tabblock\"}]}"
+		);
+	}
+
+	#[test]
+	fn show_unknown_returns_404() {
+		let response = super::show(&sessions(), "<some unknown id>");
+		assert_eq!(response.status(), hyper::StatusCode::NOT_FOUND);
+	}
+}
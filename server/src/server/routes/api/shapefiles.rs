@@ -2,47 +2,281 @@ use core::convert::TryFrom;
 
 use std::path::Path;
 
+use std::{
+	collections::{BTreeMap, HashMap},
+	io::{Read, Write},
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc, Mutex,
+	},
+	time::SystemTime,
+};
+
+use distringo::geometry::{
+	mvt::PropertyValue, topojson, Arcs, Region, SpatialIndex, TileId, TileIndex, Topology,
+};
+use geo::{Coordinate, MultiPolygon, Rect};
 use geojson::GeoJson;
-use hyper::body::Body;
+use headers::ETag;
+use hyper::body::{Body, Bytes};
+use once_cell::sync::OnceCell;
 
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
+use super::{
+	compression::Encoding,
+	conditional::{self, Conditions},
+	datasets::{Dataset, TableValues},
+	plans::{District, Plan, PlanId, PlanStore},
+	resources::{FileBacked, Lazy, Resource},
+	responses::{empty_response, unprocessable},
+	validation::Report,
+};
+
+#[derive(Clone, Copy, Debug, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ShapefileType {
 	#[serde(alias = "tabblock")]
 	TabularBlock,
 }
 
-pub struct ByteChunkStream<'buffer> {
-	iterator: std::slice::Chunks<'buffer, u8>,
+#[derive(Debug)]
+pub struct Shapefile {
+	ty: ShapefileType,
+	/// The members of the `FeatureCollection`, without its features
+	collection: geojson::FeatureCollection,
+	/// Every feature, in the order they were loaded in
+	skeletons: Vec<Skeleton>,
+	/// The GeoID and polygons of each feature that has both
+	///
+	/// This is the only copy of the shapefile's geometry; everything else is
+	/// made from it.
+	regions: Vec<Region>,
+	/// The position in `skeletons` of each of `regions`
+	positions: Vec<usize>,
+	/// The position in `skeletons` of the feature with each GeoID
+	lookup: HashMap<String, usize>,
+	/// A hash of the GeoJSON, which responses made from the shapefile are
+	/// tagged with
+	hash: u64,
+	last_modified: Option<SystemTime>,
+	/// Roughly how many bytes the features take up
+	size: usize,
+	/// Roughly how many bytes the indices built so far take up
+	indices: AtomicUsize,
+	spatial_index: OnceCell<SpatialIndex>,
+	topology: OnceCell<Topology>,
+	tile_index: OnceCell<TileIndex>,
+	arcs: OnceCell<Arcs>,
+	tiles: Mutex<Cache<TileKey>>,
+	bodies: Mutex<Cache<BodyKey>>,
 }
 
-impl<'buffer> ByteChunkStream<'buffer> {
-	fn new(buf: &'buffer str, chunk_size: usize) -> Self {
-		Self {
-			iterator: buf.as_bytes().chunks(chunk_size),
+/// A feature, without the geometry that is kept in [`Shapefile::regions`]
+///
+/// The geometry is left empty, but of the same type, so that the feature can
+/// be put back together as it was.
+#[derive(Debug)]
+struct Skeleton {
+	feature: geojson::Feature,
+	/// The index of the feature's polygons in [`Shapefile::regions`], for
+	/// features whose geometry was taken out
+	region: Option<usize>,
+}
+
+impl Skeleton {
+	/// Take the geometry out of a feature, if it has a GeoID and polygons
+	fn new(mut feature: geojson::Feature) -> (Self, Option<Region>) {
+		let region: Option<Region> = match (
+			distringo::geometry::feature_geoid(&feature),
+			feature.geometry.as_ref(),
+		) {
+			(Some(geoid), Some(geometry)) => {
+				distringo::geometry::multi_polygon(geometry).map(|polygons| (geoid.to_string(), polygons))
+			}
+			_ => None,
+		};
+
+		if let (Some(geometry), Some(_)) = (feature.geometry.as_mut(), &region) {
+			geometry.value = match geometry.value {
+				geojson::Value::Polygon(_) => geojson::Value::Polygon(Vec::new()),
+				_ => geojson::Value::MultiPolygon(Vec::new()),
+			};
 		}
+
+		let skeleton = Self {
+			feature,
+			region: None,
+		};
+
+		(skeleton, region)
 	}
-}
 
-use std::{
-	pin::Pin,
-	task::{Context, Poll},
-};
+	/// Roughly how many bytes the feature takes up
+	fn size(&self) -> usize {
+		let properties: usize = self
+			.feature
+			.properties
+			.as_ref()
+			.and_then(|properties| serde_json::to_vec(properties).ok())
+			.map_or(0, |properties| properties.len());
 
-impl<'buffer> futures::Stream for ByteChunkStream<'buffer> {
-	type Item = Result<&'buffer [u8], std::convert::Infallible>;
+		core::mem::size_of::<Self>() + properties * GEOJSON_EXPANSION
+	}
+
+	/// The feature with some polygons put back in
+	///
+	/// Polygon features stay polygons if they can.
+	fn with_polygons(&self, polygons: &MultiPolygon<f64>) -> geojson::Feature {
+		let mut feature: geojson::Feature = self.feature.clone();
+
+		if let Some(geometry) = feature.geometry.as_mut() {
+			geometry.value = match (&geometry.value, polygons.0.as_slice()) {
+				(geojson::Value::Polygon(_), [polygon]) => geojson::Value::from(polygon),
+				_ => geojson::Value::from(polygons),
+			};
+		}
 
-	fn poll_next(self: Pin<&mut Self>, _ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-		let next: Option<Result<&'buffer [u8], _>> = self.get_mut().iterator.next().map(Ok);
-		Poll::Ready(next)
+		feature
 	}
 }
 
+/// The zoom levels simplified copies of each shapefile are served for
+///
+/// Beyond the last of these, the full-resolution geometry is used.
+const SIMPLIFICATION_ZOOMS: &[u8] = &[4, 6, 8, 10, 12];
+
+/// The size of a 256-pixel tile's pixel at a zoom level, in degrees of
+/// longitude
+fn pixel_size(zoom: u8) -> f64 {
+	360.0 / f64::from(256_u32 << zoom)
+}
+
+/// The number of steps along each side of the grid TopoJSON positions are
+/// snapped to
+///
+/// Across a state, this is a grid of a few meters.
+const QUANTIZATION: u32 = 100_000;
+
+/// Roughly how many bytes parsed JSON takes up for each byte of its text
+const GEOJSON_EXPANSION: usize = 4;
+
+/// How many rendered tiles each shapefile keeps
+const TILE_CACHE_LIMIT: usize = 4096;
+
+/// How many encoded bodies each shapefile keeps
+const BODY_CACHE_LIMIT: usize = 8;
+
+/// How much of a shapefile is read when checking it
+///
+/// GDAL and the Census Bureau write the collection's `type` ahead of its
+/// features, well within this.
+const HEADER_LIMIT: u64 = 4096;
+
+/// A tile, and the hash of the dataset its populations were joined from
+type TileKey = (TileId, Option<u64>);
+
+/// A body's format, simplification level and coding
+type BodyKey = (Format, Option<usize>, Encoding);
+
+/// Encoded bodies, of which the ones used least recently are dropped to make
+/// room for new ones
 #[derive(Debug)]
-pub struct Shapefile {
-	ty: ShapefileType,
-	contents: GeoJson,
-	data: String,
+struct Cache<K> {
+	/// How many bodies are kept
+	limit: usize,
+	/// Counts every use of a body
+	clock: u64,
+	bodies: HashMap<K, (Bytes, u64)>,
+	/// The key of each body by when it was last used
+	uses: BTreeMap<u64, K>,
+	/// The length of all the bodies
+	size: usize,
+}
+
+impl<K: Copy + Eq + core::hash::Hash> Cache<K> {
+	fn new(limit: usize) -> Self {
+		Self {
+			limit,
+			clock: 0,
+			bodies: HashMap::new(),
+			uses: BTreeMap::new(),
+			size: 0,
+		}
+	}
+
+	fn tick(&mut self) -> u64 {
+		self.clock += 1;
+		self.clock
+	}
+
+	fn get(&mut self, key: &K) -> Option<Bytes> {
+		let now: u64 = self.tick();
+		let (data, used) = self.bodies.get_mut(key)?;
+
+		self.uses.remove(used);
+		self.uses.insert(now, *key);
+		*used = now;
+
+		Some(data.clone())
+	}
+
+	fn insert(&mut self, key: K, data: Bytes) {
+		while self.bodies.len() >= self.limit && !self.bodies.contains_key(&key) {
+			let oldest: K = match self.uses.pop_first() {
+				Some((_, oldest)) => oldest,
+				None => break,
+			};
+			if let Some((data, _)) = self.bodies.remove(&oldest) {
+				self.size -= data.len();
+			}
+		}
+
+		let now: u64 = self.tick();
+		self.size += data.len();
+		self.uses.insert(now, key);
+		if let Some((data, used)) = self.bodies.insert(key, (data, now)) {
+			self.size -= data.len();
+			self.uses.remove(&used);
+		}
+	}
+}
+
+/// Write the items of an iterator as a JSON array
+fn write_array<T, I>(writer: &mut dyn Write, items: I) -> std::io::Result<()>
+where
+	T: serde::Serialize,
+	I: Iterator<Item = T>,
+{
+	writer.write_all(b"[")?;
+	for (n, item) in items.enumerate() {
+		if n > 0 {
+			writer.write_all(b",")?;
+		}
+		serde_json::to_writer(&mut *writer, &item)?;
+	}
+	writer.write_all(b"]")
+}
+
+/// The body kept in `cache` under `key`, or the one `make` makes, which is
+/// kept from then on
+///
+/// The cache isn't locked while the body is made, so two requests for the
+/// same body at once may both make it.
+fn cached<K, F>(cache: &Mutex<Cache<K>>, key: K, make: F) -> Bytes
+where
+	K: Copy + Eq + core::hash::Hash,
+	F: FnOnce() -> Bytes,
+{
+	if let Some(data) = cache.lock().expect("cache lock poisoned").get(&key) {
+		return data;
+	}
+
+	let data: Bytes = make();
+	cache
+		.lock()
+		.expect("cache lock poisoned")
+		.insert(key, data.clone());
+
+	data
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -53,79 +287,815 @@ pub struct ShapefileConfiguration {
 }
 
 impl Shapefile {
+	/// Take a shapefile apart, which must be a `FeatureCollection`
+	pub fn new(ty: ShapefileType, contents: GeoJson) -> distringo::Result<Self> {
+		let hash: u64 = conditional::hash(contents.to_string().as_bytes());
+
+		Self::with_hash(ty, contents, hash)
+	}
+
+	fn with_hash(ty: ShapefileType, contents: GeoJson, hash: u64) -> distringo::Result<Self> {
+		let mut collection = geojson::FeatureCollection::try_from(contents)?;
+
+		let mut skeletons: Vec<Skeleton> = Vec::with_capacity(collection.features.len());
+		let mut regions: Vec<Region> = Vec::new();
+		let mut positions: Vec<usize> = Vec::new();
+		for feature in core::mem::take(&mut collection.features) {
+			let (mut skeleton, region) = Skeleton::new(feature);
+
+			if let Some(region) = region {
+				skeleton.region = Some(regions.len());
+				positions.push(skeletons.len());
+				regions.push(region);
+			}
+
+			skeletons.push(skeleton);
+		}
+
+		let lookup: HashMap<String, usize> = skeletons
+			.iter()
+			.enumerate()
+			.filter_map(|(position, skeleton)| {
+				Some((
+					distringo::geometry::feature_geoid(&skeleton.feature)?.to_string(),
+					position,
+				))
+			})
+			.collect();
+
+		let size: usize = core::mem::size_of::<Self>()
+			+ skeletons.iter().map(Skeleton::size).sum::<usize>()
+			+ regions
+				.iter()
+				.map(distringo::geometry::region_size)
+				.sum::<usize>()
+			+ positions.len() * core::mem::size_of::<usize>()
+			+ lookup
+				.keys()
+				.map(|geoid| geoid.len() + core::mem::size_of::<(String, usize)>())
+				.sum::<usize>();
+
+		Ok(Self {
+			ty,
+			collection,
+			skeletons,
+			regions,
+			positions,
+			lookup,
+			hash,
+			last_modified: None,
+			size,
+			indices: AtomicUsize::new(0),
+			spatial_index: OnceCell::new(),
+			topology: OnceCell::new(),
+			tile_index: OnceCell::new(),
+			arcs: OnceCell::new(),
+			tiles: Mutex::new(Cache::new(TILE_CACHE_LIMIT)),
+			bodies: Mutex::new(Cache::new(BODY_CACHE_LIMIT)),
+		})
+	}
+
+	/// Read a shapefile, which must be a `FeatureCollection`
 	pub fn from_file<P: AsRef<Path>>(ty: ShapefileType, path: P) -> distringo::Result<Self> {
-		let contents = std::fs::read_to_string(path)?.parse::<GeoJson>()?;
+		let text: String = std::fs::read_to_string(&path)?;
+		let hash: u64 = conditional::hash(text.as_bytes());
+		let contents: GeoJson = text.parse()?;
+		drop(text);
+
+		Ok(Self {
+			last_modified: conditional::modified(path),
+			..Self::with_hash(ty, contents, hash)?
+		})
+	}
+
+	pub fn ty(&self) -> ShapefileType {
+		self.ty
+	}
+
+	/// Roughly how many bytes the shapefile takes up
+	///
+	/// Everything computed since the shapefile was loaded counts, as do the
+	/// tiles and bodies it keeps.
+	pub fn footprint(&self) -> usize {
+		let tiles: usize = self.tiles.lock().expect("tile cache lock poisoned").size;
+		let bodies: usize = self.bodies.lock().expect("body cache lock poisoned").size;
+
+		self.size + self.indices.load(Ordering::Relaxed) + tiles + bodies
+	}
+
+	/// Build an index the first time it's needed, counting it towards the
+	/// footprint
+	fn index<'a, T, F>(&self, cell: &'a OnceCell<T>, size: fn(&T) -> usize, build: F) -> &'a T
+	where
+		F: FnOnce() -> T,
+	{
+		cell.get_or_init(|| {
+			let index: T = build();
+			self.indices.fetch_add(size(&index), Ordering::Relaxed);
+			index
+		})
+	}
+
+	/// The GeoID and polygons of each of the shapefile's features
+	pub fn features(&self) -> &[Region] {
+		&self.regions
+	}
+
+	fn spatial_index(&self) -> &SpatialIndex {
+		self.index(&self.spatial_index, SpatialIndex::size, || {
+			SpatialIndex::new(&self.regions)
+		})
+	}
+
+	/// Whether the shapefile has a feature with a GeoID
+	pub fn contains(&self, geoid: &str) -> bool {
+		self.lookup.contains_key(geoid)
+	}
+
+	/// The feature with a GeoID, as it was loaded
+	pub fn feature(&self, geoid: &str) -> Option<geojson::Feature> {
+		self.feature_at(*self.lookup.get(geoid)?, None)
+	}
+
+	/// The feature at a position, with its geometry simplified to a tolerance
+	/// with some arcs if they're given
+	///
+	/// Features that simplify away entirely are `None`.
+	fn feature_at(
+		&self,
+		position: usize,
+		simplified: Option<(&Arcs, f64)>,
+	) -> Option<geojson::Feature> {
+		let skeleton: &Skeleton = &self.skeletons[position];
+
+		match (skeleton.region, simplified) {
+			(None, _) => Some(skeleton.feature.clone()),
+			(Some(idx), None) => Some(skeleton.with_polygons(&self.regions[idx].1)),
+			(Some(idx), Some((arcs, tolerance))) => {
+				let polygons: MultiPolygon<f64> = arcs.simplified_multi_polygon(idx, Some(tolerance));
+
+				if polygons.0.is_empty() {
+					None
+				} else {
+					Some(skeleton.with_polygons(&polygons))
+				}
+			}
+		}
+	}
+
+	/// The adjacency structure of the shapefile's features
+	pub fn topology(&self) -> &Topology {
+		self.index(&self.topology, Topology::size, || {
+			log::info!("Building topology of {} features", self.regions.len());
+
+			Topology::new(&self.regions)
+		})
+	}
 
-		// TODO(rye): Avoid re-allocating as a String by having a more "streamable" result.
-		let data = contents.to_string();
+	/// The shapefile's features, decomposed into the arcs they share
+	fn arcs(&self) -> &Arcs {
+		self.index(&self.arcs, Arcs::size, || Arcs::new(&self.regions))
+	}
+
+	/// Write the features at some positions as a `FeatureCollection`, with
+	/// their geometry simplified as for [`Self::feature_at`]
+	///
+	/// Features are written one at a time, so the whole collection is never
+	/// held at once.
+	fn write_geojson<I>(
+		&self,
+		writer: &mut dyn Write,
+		positions: I,
+		simplified: Option<(&Arcs, f64)>,
+	) -> std::io::Result<()>
+	where
+		I: IntoIterator<Item = usize>,
+	{
+		let members = serde_json::Map::<String, serde_json::Value>::from(&self.collection);
+		let mut positions = Some(positions);
+
+		writer.write_all(b"{")?;
+		for (n, (key, value)) in members.iter().enumerate() {
+			if n > 0 {
+				writer.write_all(b",")?;
+			}
+			serde_json::to_writer(&mut *writer, key)?;
+			writer.write_all(b":")?;
+
+			if key == "features" {
+				let features = positions
+					.take()
+					.into_iter()
+					.flatten()
+					.filter_map(|position| self.feature_at(position, simplified));
+
+				write_array(writer, features)?;
+			} else {
+				serde_json::to_writer(&mut *writer, value)?;
+			}
+		}
+		writer.write_all(b"}")
+	}
+
+	/// Encode the shapefile's arcs as TopoJSON, simplified to `tolerance` if
+	/// it's given, in an object named `object`
+	///
+	/// Each geometry keeps the properties of the feature it came from.
+	fn write_topojson(
+		&self,
+		writer: &mut dyn Write,
+		tolerance: Option<f64>,
+		object: &str,
+	) -> std::io::Result<()> {
+		let arcs: &Arcs = self.arcs();
+		let topology = topojson::Topology::new(arcs, tolerance, object, QUANTIZATION, |idx| {
+			self.skeletons[self.positions[idx]]
+				.feature
+				.properties
+				.clone()
+		});
+
+		Ok(serde_json::to_writer(writer, &topology)?)
+	}
+
+	/// Compute what every request needs, and which is otherwise computed on
+	/// first use
+	///
+	/// Arcs, tile indices and topologies are only needed by some, so they're
+	/// left until they are.
+	pub fn prepare(&self) {
+		self.spatial_index();
+	}
+
+	/// The simplification level to serve for some parameters, if any, as an
+	/// index into [`SIMPLIFICATION_ZOOMS`]
+	///
+	/// A `zoom` gets the coarsest level that is still accurate to a pixel at
+	/// that zoom level, and a `tolerance` (in degrees) the coarsest level
+	/// simplified by no more than that.
+	fn level(parameters: &ShowParameters) -> Option<usize> {
+		match (parameters.zoom, parameters.tolerance) {
+			(Some(zoom), _) => SIMPLIFICATION_ZOOMS.iter().position(|level| *level >= zoom),
+			(None, Some(tolerance)) => SIMPLIFICATION_ZOOMS
+				.iter()
+				.position(|level| pixel_size(*level) <= tolerance),
+			(None, None) => None,
+		}
+	}
+
+	/// The encoded shapefile to serve for some parameters
+	///
+	/// Shared boundaries are simplified once for both sides, so that neighbors
+	/// stay flush with each other.  Features that simplify away entirely are
+	/// left out.  TopoJSON objects are named `object`, which is the same for
+	/// every request.  Bodies are encoded the first time they're asked for.
+	fn data(&self, parameters: &ShowParameters, object: &str, encoding: Encoding) -> Bytes {
+		let level: Option<usize> = Self::level(parameters);
+		let tolerance: Option<f64> = level.map(|level| pixel_size(SIMPLIFICATION_ZOOMS[level]));
+
+		cached(&self.bodies, (parameters.format, level, encoding), || {
+			log::debug!("Encoding shapefile {} at level {:?}", object, level);
+
+			encoding.encode_with(|writer| match parameters.format {
+				Format::GeoJson => self.write_geojson(
+					writer,
+					0..self.skeletons.len(),
+					tolerance.map(|tolerance| (self.arcs(), tolerance)),
+				),
+				Format::TopoJson => self.write_topojson(writer, tolerance, object),
+			})
+		})
+	}
+
+	/// The positions of the features that are in `bounds` and have one of
+	/// `geoids`, in the order they were loaded in
+	///
+	/// Either filter can be left out.
+	fn query(&self, bounds: Option<Rect<f64>>, geoids: Option<&[&str]>) -> Vec<usize> {
+		let mut positions: Vec<usize> = match (bounds, geoids) {
+			(Some(bounds), geoids) => self
+				.spatial_index()
+				.intersecting(&self.regions, bounds)
+				.into_iter()
+				.filter(|idx| {
+					geoids.map_or(true, |geoids| {
+						geoids.contains(&self.regions[*idx].0.as_str())
+					})
+				})
+				.map(|idx| self.positions[idx])
+				.collect(),
+			(None, Some(geoids)) => geoids
+				.iter()
+				.filter_map(|geoid| self.lookup.get(*geoid).copied())
+				.collect(),
+			(None, None) => (0..self.skeletons.len()).collect(),
+		};
+		positions.sort_unstable();
+		positions.dedup();
 
-		Ok(Self { ty, contents, data })
+		positions
+	}
+
+	fn tile_index(&self) -> &TileIndex {
+		self.index(&self.tile_index, TileIndex::size, || {
+			TileIndex::new(&self.regions)
+		})
+	}
+
+	/// Render a vector tile of the shapefile, in a layer named `layer`
+	///
+	/// If a dataset is given, each feature's population is included.  Tiles are
+	/// rendered the first time they're asked for, and `layer` must be the same
+	/// every time.
+	fn tile(&self, layer: &str, tile: TileId, dataset: Option<&Dataset>) -> Bytes {
+		cached(&self.tiles, (tile, dataset.map(Dataset::hash)), || {
+			let populations: Option<&HashMap<String, u64>> = dataset.map(Dataset::populations);
+
+			distringo::geometry::render(&self.regions, self.tile_index(), tile, layer, |geoid| {
+				match populations.and_then(|populations| populations.get(geoid)) {
+					Some(population) => vec![("population", PropertyValue::Uint(*population))],
+					None => Vec::new(),
+				}
+			})
+			.into()
+		})
+	}
+
+	/// The GeoID of the feature containing a point
+	pub fn locate(&self, c: Coordinate<f64>) -> Option<&str> {
+		let idx: usize = self.spatial_index().containing(&self.regions, c)?;
+
+		Some(&self.regions[idx].0)
+	}
+
+	/// The polygons of the feature with a GeoID
+	pub fn polygons(&self, geoid: &str) -> Option<&MultiPolygon<f64>> {
+		let idx: usize = self.skeletons[*self.lookup.get(geoid)?].region?;
+
+		Some(&self.regions[idx].1)
 	}
 }
 
-impl TryFrom<ShapefileConfiguration> for Shapefile {
+impl ShapefileConfiguration {
+	/// Note anything that would keep the shapefile configured at `key` from
+	/// loading
+	///
+	/// The type has already been checked by deserializing.  Only the start of
+	/// the file is read, to see that it's a `FeatureCollection`; the rest is
+	/// parsed when the shapefile is loaded.
+	pub fn check(&self, key: &str, report: &mut Report) {
+		let file_key: String = format!("{}.file", key);
+
+		let problems: usize = report.problems().len();
+		report.require_file(&file_key, &self.file);
+		if report.problems().len() > problems {
+			return;
+		}
+
+		let mut header: Vec<u8> = Vec::new();
+		let read = std::fs::File::open(&self.file)
+			.and_then(|file| file.take(HEADER_LIMIT).read_to_end(&mut header));
+		if let Err(error) = read {
+			report.push(&file_key, format_args!("{}: {}", self.file, error));
+			return;
+		}
+
+		// Whitespace inside strings is dropped as well, which doesn't matter for
+		// finding the type.
+		let header: String = String::from_utf8_lossy(&header)
+			.chars()
+			.filter(|c| !c.is_whitespace())
+			.collect();
+		if !header.starts_with('{') || !header.contains("\"type\":\"FeatureCollection\"") {
+			report.push(
+				&file_key,
+				format_args!(
+					"{} does not start as a GeoJSON FeatureCollection",
+					self.file
+				),
+			);
+		}
+	}
+}
+
+impl FileBacked for ShapefileConfiguration {
+	fn path(&self) -> &Path {
+		Path::new(&self.file)
+	}
+}
+
+impl TryFrom<&ShapefileConfiguration> for Shapefile {
 	type Error = distringo::Error;
 
-	fn try_from(sc: ShapefileConfiguration) -> distringo::Result<Self> {
-		Self::from_file(sc.ty, sc.file)
+	fn try_from(sc: &ShapefileConfiguration) -> distringo::Result<Self> {
+		Self::from_file(sc.ty, &sc.file)
 	}
 }
 
-pub fn index(shapefiles: &std::collections::HashMap<String, Shapefile>) -> impl warp::Reply {
+impl Resource for Shapefile {
+	type Configuration = ShapefileConfiguration;
+
+	fn load(id: &str, configuration: &ShapefileConfiguration) -> distringo::Result<Self> {
+		let shapefile = Self::try_from(configuration)?;
+
+		log::info!("Preparing shapefile {}", id);
+		shapefile.prepare();
+
+		Ok(shapefile)
+	}
+
+	fn footprint(&self) -> usize {
+		Shapefile::footprint(self)
+	}
+}
+
+impl Lazy<Shapefile> {
+	/// The type of shapefile `id`, which is known without loading it
+	pub fn ty(&self, id: &str) -> Option<ShapefileType> {
+		match self.configuration(id) {
+			Some(configuration) => Some(configuration.ty),
+			None => self.get(id).map(|shapefile| shapefile.ty()),
+		}
+	}
+}
+
+pub fn index(shapefiles: &Lazy<Shapefile>) -> impl warp::Reply {
 	warp::reply::json(&shapefiles.keys().collect::<Vec<&String>>())
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+	#[default]
+	GeoJson,
+	TopoJson,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct ShowParameters {
+	#[serde(default)]
+	format: Format,
+	zoom: Option<u8>,
+	tolerance: Option<f64>,
+	/// Only the features intersecting `minx,miny,maxx,maxy`
+	bbox: Option<String>,
+	/// Only the features with these comma-separated GeoIDs
+	geoid: Option<String>,
+}
+
+/// Parse a `minx,miny,maxx,maxy` bounding box
+fn parse_bbox(bbox: &str) -> Option<Rect<f64>> {
+	let values: Vec<f64> = bbox
+		.split(',')
+		.map(|value| {
+			value
+				.trim()
+				.parse()
+				.ok()
+				.filter(|value: &f64| value.is_finite())
+		})
+		.collect::<Option<Vec<f64>>>()?;
+
+	match values.as_slice() {
+		[min_x, min_y, max_x, max_y] => Some(Rect::new(
+			Coordinate {
+				x: *min_x,
+				y: *min_y,
+			},
+			Coordinate {
+				x: *max_x,
+				y: *max_y,
+			},
+		)),
+		_ => None,
+	}
+}
+
+/// Serve the features of a shapefile matching the filters in `parameters`
+fn query(
+	shapefile: &Shapefile,
+	parameters: &ShowParameters,
+	conditions: &Conditions,
+	encoding: Encoding,
+) -> hyper::Response<Body> {
+	let bounds: Option<Rect<f64>> = match parameters.bbox.as_deref().map(parse_bbox) {
+		Some(Some(bounds)) => Some(bounds),
+		Some(None) => return unprocessable("bbox must be four comma-separated numbers"),
+		None => None,
+	};
+
+	let geoids: Option<Vec<&str>> = parameters
+		.geoid
+		.as_deref()
+		.map(|geoids| geoids.split(',').map(str::trim).collect());
+
+	if let Format::TopoJson = parameters.format {
+		return unprocessable("feature queries can only be returned as GeoJSON");
+	}
+
+	let etag: ETag = conditional::derived_etag(&(
+		shapefile.hash,
+		&parameters.bbox,
+		&parameters.geoid,
+		Shapefile::level(parameters),
+		encoding,
+	));
+
+	conditional::respond_with(
+		conditions,
+		encoding.headers(
+			http::response::Builder::new()
+				.status(hyper::StatusCode::OK)
+				.header(hyper::header::CONTENT_TYPE, "application/vnd.geo+json"),
+		),
+		&etag,
+		shapefile.last_modified,
+		|| {
+			let positions: Vec<usize> = shapefile.query(bounds, geoids.as_deref());
+			let simplified: Option<(&Arcs, f64)> = Shapefile::level(parameters)
+				.map(|level| (shapefile.arcs(), pixel_size(SIMPLIFICATION_ZOOMS[level])));
+
+			Ok(encoding.encode_with(|writer| shapefile.write_geojson(writer, positions, simplified)))
+		},
+	)
+}
+
 pub fn show(
-	shapefiles: &'static std::collections::HashMap<String, Shapefile>,
+	shapefiles: &Lazy<Shapefile>,
+	id: &str,
+	parameters: ShowParameters,
+	conditions: &Conditions,
+	encoding: Encoding,
+) -> hyper::Response<Body> {
+	let shapefile: Arc<Shapefile> = match shapefiles.fetch(id) {
+		Ok(shapefile) => shapefile,
+		Err(response) => return *response,
+	};
+
+	if parameters.bbox.is_some() || parameters.geoid.is_some() {
+		return query(&shapefile, &parameters, conditions, encoding);
+	}
+
+	let etag: ETag = conditional::derived_etag(&(
+		shapefile.hash,
+		parameters.format,
+		Shapefile::level(&parameters),
+		encoding,
+		id,
+	));
+
+	let content_type: &str = match parameters.format {
+		Format::GeoJson => "application/vnd.geo+json",
+		Format::TopoJson => "application/json",
+	};
+
+	conditional::respond_with(
+		conditions,
+		encoding.headers(
+			http::response::Builder::new()
+				.status(hyper::StatusCode::OK)
+				.header(hyper::header::CONTENT_TYPE, content_type)
+				.header(hyper::header::CACHE_CONTROL, "public"),
+		),
+		&etag,
+		shapefile.last_modified,
+		|| Ok(shapefile.data(&parameters, id, encoding)),
+	)
+}
+
+/// Serve the feature of a shapefile with a GeoID
+pub fn feature(
+	shapefiles: &Lazy<Shapefile>,
 	id: &str,
+	geoid: &str,
+	conditions: &Conditions,
 ) -> hyper::Response<Body> {
-	if let Some(shapefile) = shapefiles.get(id) {
-		let data: &str = &shapefile.data;
+	let shapefile: Arc<Shapefile> = match shapefiles.fetch(id) {
+		Ok(shapefile) => shapefile,
+		Err(response) => return *response,
+	};
 
+	conditional::respond_with(
+		conditions,
 		http::response::Builder::new()
 			.status(hyper::StatusCode::OK)
 			.header(hyper::header::CONTENT_TYPE, "application/vnd.geo+json")
-			.header(hyper::header::CACHE_CONTROL, "public")
-			// TODO(rye): Clean up error path
-			.body(Body::wrap_stream(ByteChunkStream::new(data, 4096)))
-			.unwrap()
-	} else {
-		log::debug!("{:?}", shapefiles);
+			.header(hyper::header::CACHE_CONTROL, "public"),
+		&conditional::derived_etag(&(shapefile.hash, geoid)),
+		shapefile.last_modified,
+		|| match shapefile.feature(geoid) {
+			Some(feature) => Ok(feature.to_string().into()),
+			None => Err(empty_response(hyper::StatusCode::NOT_FOUND).into()),
+		},
+	)
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct LocateParameters {
+	lat: f64,
+	lon: f64,
+	/// A plan to look up the district of the feature in
+	plan: Option<PlanId>,
+	/// A dataset to join the feature's tables from
+	dataset: Option<String>,
+}
+
+/// The length of a block's GeoID, and of the tract it is in
+const BLOCK_GEOID_LENGTH: usize = 15;
+const TRACT_GEOID_LENGTH: usize = 11;
+
+#[derive(Debug, serde::Serialize)]
+struct Location<'a> {
+	geoid: &'a str,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	tract: Option<&'a str>,
+	/// The district the feature is assigned to, if a plan was given
+	#[serde(skip_serializing_if = "Option::is_none")]
+	district: Option<Option<District>>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	tables: Option<TableValues>,
+}
 
+/// Find the feature of a shapefile under a point, along with its district in
+/// a plan and its values in a dataset
+///
+/// Points outside every feature are not found.  If the dataset doesn't have
+/// the feature, its tables are left out.
+pub fn locate(
+	shapefiles: &Lazy<Shapefile>,
+	datasets: &Lazy<Dataset>,
+	plans: &PlanStore,
+	id: &str,
+	parameters: LocateParameters,
+	conditions: &Conditions,
+) -> hyper::Response<Body> {
+	let shapefile: Arc<Shapefile> = match shapefiles.fetch(id) {
+		Ok(shapefile) => shapefile,
+		Err(response) => return *response,
+	};
+
+	if !(-90.0..=90.0).contains(&parameters.lat) || !(-180.0..=180.0).contains(&parameters.lon) {
+		return unprocessable("lat and lon must be a position in degrees");
+	}
+
+	let plan: Option<Plan> = match parameters.plan {
+		Some(plan_id) => match plans.get(plan_id) {
+			Some(plan) => Some(plan),
+			None => return empty_response(hyper::StatusCode::NOT_FOUND),
+		},
+		None => None,
+	};
+
+	let dataset: Option<Arc<Dataset>> = match &parameters.dataset {
+		Some(dataset_id) => match datasets.fetch(dataset_id) {
+			Ok(dataset) => Some(dataset),
+			Err(response) => return *response,
+		},
+		None => None,
+	};
+
+	let geoid: &str = match shapefile.locate(Coordinate {
+		x: parameters.lon,
+		y: parameters.lat,
+	}) {
+		Some(geoid) => geoid,
+		None => return empty_response(hyper::StatusCode::NOT_FOUND),
+	};
+
+	let etag: ETag = conditional::derived_etag(&(
+		shapefile.hash,
+		geoid,
+		plan.as_ref().map(|plan| (plan.id(), plan.version())),
+		dataset.as_ref().map(|dataset| dataset.hash()),
+	));
+
+	conditional::respond_with(
+		conditions,
 		http::response::Builder::new()
-			.status(hyper::StatusCode::NOT_FOUND)
-			.body("{}".into())
-			.unwrap()
+			.status(hyper::StatusCode::OK)
+			.header(hyper::header::CONTENT_TYPE, "application/json"),
+		&etag,
+		None,
+		|| {
+			let tables: Option<TableValues> = match dataset.map(|dataset| {
+				let (logrecno, _) = dataset.header_for_geoid(geoid)?;
+				dataset.tables_for_logrecno(logrecno)
+			}) {
+				Some(Ok(tables)) => Some(tables),
+				Some(Err(distringo::Error::InvalidGeoId)) | None => None,
+				Some(Err(error)) => {
+					log::warn!("Error reading dataset: {}", error);
+					return Err(empty_response(hyper::StatusCode::INTERNAL_SERVER_ERROR).into());
+				}
+			};
+
+			let location = Location {
+				geoid,
+				tract: geoid
+					.get(..TRACT_GEOID_LENGTH)
+					.filter(|_| geoid.len() == BLOCK_GEOID_LENGTH),
+				district: plan.map(|plan| plan.assignments().get(geoid).copied()),
+				tables,
+			};
+
+			Ok(
+				serde_json::to_vec(&location)
+					.expect("failed to serialize location")
+					.into(),
+			)
+		},
+	)
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct TileParameters {
+	/// A dataset to join populations from
+	dataset: Option<String>,
+}
+
+/// Serve a Mapbox Vector Tile of a shapefile
+///
+/// `y` is the last segment of the path, which is suffixed with `.mvt`.
+pub fn tile(
+	shapefiles: &Lazy<Shapefile>,
+	datasets: &Lazy<Dataset>,
+	id: &str,
+	(z, x, y): (u8, u32, &str),
+	parameters: TileParameters,
+	conditions: &Conditions,
+) -> hyper::Response<Body> {
+	let tile: Option<TileId> = y
+		.strip_suffix(".mvt")
+		.and_then(|y| y.parse().ok())
+		.and_then(|y| TileId::new(z, x, y));
+
+	let dataset: Option<Arc<Dataset>> = match &parameters.dataset {
+		Some(dataset_id) => match datasets.fetch(dataset_id) {
+			Ok(dataset) => Some(dataset),
+			Err(response) => return *response,
+		},
+		None => None,
+	};
+
+	let shapefile: Arc<Shapefile> = match shapefiles.fetch(id) {
+		Ok(shapefile) => shapefile,
+		Err(response) => return *response,
+	};
+
+	match tile {
+		Some(tile) => {
+			let etag: ETag = conditional::derived_etag(&(
+				shapefile.hash,
+				tile,
+				dataset.as_ref().map(|dataset| dataset.hash()),
+				id,
+			));
+
+			conditional::respond_with(
+				conditions,
+				http::response::Builder::new()
+					.status(hyper::StatusCode::OK)
+					.header(
+						hyper::header::CONTENT_TYPE,
+						"application/vnd.mapbox-vector-tile",
+					)
+					.header(hyper::header::CACHE_CONTROL, "public"),
+				&etag,
+				None,
+				|| Ok(shapefile.tile(id, tile, dataset.as_deref())),
+			)
+		}
+		None => empty_response(hyper::StatusCode::NOT_FOUND),
 	}
 }
 
 #[cfg(test)]
 mod tests {
-	use super::{Shapefile, ShapefileType};
+	use super::{Lazy, Shapefile, ShapefileType};
 	#[cfg(test)]
 	mod show {
-		use super::{Shapefile, ShapefileType};
-		use geojson::{GeoJson, Geometry, Value::Point};
+		use super::{Lazy, Shapefile, ShapefileType};
+		use geojson::GeoJson;
 		use once_cell::sync::OnceCell;
-		use std::collections::HashMap;
+		use std::{collections::HashMap, sync::Arc};
 
-		static CACHE: OnceCell<HashMap<String, Shapefile>> = OnceCell::new();
+		static CACHE: OnceCell<Lazy<Shapefile>> = OnceCell::new();
 
-		fn generate_id_and_shapefiles() -> (String, &'static HashMap<String, Shapefile>) {
-			let contents = GeoJson::Geometry(Geometry::new(Point(vec![0.0_f64, 0.0_f64])));
-			let shapefile = Shapefile {
-				ty: ShapefileType::TabularBlock,
-				data: contents.to_string(),
-				contents,
-			};
+		const CONTENTS: &str = "{\"type\":\"FeatureCollection\",\"features\":[\
+			{\"type\":\"Feature\",\"properties\":{\"name\":\"origin\"},\
+			\"geometry\":{\"type\":\"Point\",\"coordinates\":[0.0,0.0]}}]}";
+
+		fn generate_id_and_shapefiles() -> (String, &'static Lazy<Shapefile>) {
+			let contents: GeoJson = CONTENTS.parse().unwrap();
+			let shapefile = Shapefile::new(ShapefileType::TabularBlock, contents).unwrap();
 
 			let id = "id".to_string();
-			let map: &'static HashMap<String, Shapefile> = {
+			let map: &'static Lazy<Shapefile> = {
 				if CACHE.get().is_none() {
 					let mut map = HashMap::new();
-					map.insert(id.clone(), shapefile);
+					map.insert(id.clone(), Arc::new(shapefile));
 					CACHE
-						.set(map)
+						.set(map.into())
 						.unwrap_or_else(|_| eprintln!("cache already initialized"));
 				}
 
@@ -138,14 +1108,26 @@ mod tests {
 		#[test]
 		fn found_returns_200_ok() {
 			let (id, map) = generate_id_and_shapefiles();
-			let response = super::super::show(map, &id);
+			let response = super::super::show(
+				map,
+				&id,
+				Default::default(),
+				&Default::default(),
+				Default::default(),
+			);
 			assert_eq!(response.status(), hyper::StatusCode::OK);
 		}
 
 		#[test]
 		fn found_returns_correct_headers() {
 			let (id, map) = generate_id_and_shapefiles();
-			let response = super::super::show(map, &id);
+			let response = super::super::show(
+				map,
+				&id,
+				Default::default(),
+				&Default::default(),
+				Default::default(),
+			);
 			assert_eq!(
 				response
 					.headers()
@@ -163,7 +1145,7 @@ mod tests {
 		}
 
 		macro_rules! assert_response_body_eq {
-			($response:ident, $value:literal) => {
+			($response:ident, $value:expr) => {
 				use hyper::body::Bytes;
 				use tokio_stream::StreamExt;
 				assert_eq!(
@@ -182,17 +1164,577 @@ mod tests {
 		#[tokio::test]
 		async fn found_returns_correct_body() {
 			let (id, map) = generate_id_and_shapefiles();
-			let response = super::super::show(map, &id);
-			assert_response_body_eq!(response, "{\"coordinates\":[0.0,0.0],\"type\":\"Point\"}");
+			let response = super::super::show(
+				map,
+				&id,
+				Default::default(),
+				&Default::default(),
+				Default::default(),
+			);
+			// The body is streamed, but comes out just as the GeoJSON would.
+			assert_response_body_eq!(response, CONTENTS.parse::<GeoJson>().unwrap().to_string());
+		}
+
+		#[test]
+		fn cached_copies_are_not_modified() {
+			let (id, map) = generate_id_and_shapefiles();
+			let response = super::super::show(
+				map,
+				&id,
+				Default::default(),
+				&Default::default(),
+				Default::default(),
+			);
+			let etag = response.headers()[hyper::header::ETAG].clone();
+
+			let mut headers = http::HeaderMap::new();
+			headers.insert(hyper::header::IF_NONE_MATCH, etag);
+			let conditions = crate::server::routes::api::conditional::Conditions::from_headers(&headers);
+
+			let response = super::super::show(
+				map,
+				&id,
+				Default::default(),
+				&conditions,
+				Default::default(),
+			);
+			assert_eq!(response.status(), hyper::StatusCode::NOT_MODIFIED);
+		}
+
+		#[test]
+		fn codings_are_negotiated() {
+			use crate::server::routes::api::compression::Encoding;
+
+			let (id, map) = generate_id_and_shapefiles();
+			let respond = |encoding: Encoding| {
+				super::super::show(map, &id, Default::default(), &Default::default(), encoding)
+			};
+
+			let identity = respond(Encoding::Identity);
+			let brotli = respond(Encoding::Brotli);
+
+			assert!(identity
+				.headers()
+				.get(hyper::header::CONTENT_ENCODING)
+				.is_none());
+			assert_eq!(brotli.headers()[hyper::header::CONTENT_ENCODING], "br");
+			assert_eq!(brotli.headers()[hyper::header::VARY], "accept-encoding");
+			assert_ne!(
+				identity.headers()[hyper::header::ETAG],
+				brotli.headers()[hyper::header::ETAG]
+			);
 		}
 
 		#[test]
 		fn not_found_returns_404() {
 			let (_id, map) = generate_id_and_shapefiles();
 
-			let response = super::super::show(map, &"<some unknown id>".to_string());
+			let response = super::super::show(
+				map,
+				"<some unknown id>",
+				Default::default(),
+				&Default::default(),
+				Default::default(),
+			);
 
 			assert_eq!(response.status(), hyper::StatusCode::NOT_FOUND);
 		}
 	}
+
+	mod tile {
+		use super::super::{Cache, TileKey, TILE_CACHE_LIMIT};
+		use super::{Lazy, Shapefile, ShapefileType};
+		use distringo::geometry::TileId;
+		use hyper::body::Bytes;
+		use std::{collections::HashMap, sync::Arc};
+
+		fn shapefiles() -> Lazy<Shapefile> {
+			let blocks: geojson::GeoJson = "{\"type\":\"FeatureCollection\",\"features\":[\
+				{\"type\":\"Feature\",\"properties\":{\"GEOID10\":\"181570052001013\"},\
+				\"geometry\":{\"type\":\"Polygon\",\"coordinates\":\
+				[[[-86.9,40.4],[-86.8,40.4],[-86.8,40.5],[-86.9,40.5],[-86.9,40.4]]]}}]}"
+				.parse()
+				.unwrap();
+
+			let mut shapefiles = HashMap::new();
+			shapefiles.insert(
+				"blocks".to_string(),
+				Arc::new(Shapefile::new(ShapefileType::TabularBlock, blocks).unwrap()),
+			);
+			shapefiles.into()
+		}
+
+		fn tile(z: u8, x: u32, y: &str, dataset: Option<&str>) -> hyper::Response<hyper::Body> {
+			super::super::tile(
+				&shapefiles(),
+				&Lazy::default(),
+				"blocks",
+				(z, x, y),
+				super::super::TileParameters {
+					dataset: dataset.map(ToString::to_string),
+				},
+				&Default::default(),
+			)
+		}
+
+		#[test]
+		fn least_recently_used_tiles_are_dropped() {
+			let mut cache = Cache::new(TILE_CACHE_LIMIT);
+			let key = |x: u32| -> TileKey { (TileId::new(13, x, 0).unwrap(), None) };
+
+			for x in 0..TILE_CACHE_LIMIT as u32 {
+				cache.insert(key(x), Bytes::from_static(b"tile"));
+			}
+			assert!(cache.get(&key(0)).is_some());
+
+			cache.insert(key(4095), Bytes::from_static(b"tile!"));
+			assert_eq!(cache.bodies.len(), TILE_CACHE_LIMIT);
+			cache.insert(key(4096), Bytes::from_static(b"tile"));
+
+			assert!(cache.get(&key(0)).is_some());
+			assert!(cache.get(&key(1)).is_none());
+			assert!(cache.get(&key(4096)).is_some());
+			assert_eq!(cache.size, 4 * TILE_CACHE_LIMIT + 1);
+		}
+
+		#[tokio::test]
+		async fn tiles_are_encoded() {
+			// The block covers a few pixels of a tile at zoom 10.
+			let response = tile(10, 264, "386.mvt", None);
+			assert_eq!(response.status(), hyper::StatusCode::OK);
+			assert_eq!(
+				response.headers()[hyper::header::CONTENT_TYPE],
+				"application/vnd.mapbox-vector-tile"
+			);
+
+			let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+			assert!(body
+				.windows(b"181570052001013".len())
+				.any(|window| window == b"181570052001013"));
+		}
+
+		#[test]
+		fn missing_tiles_are_not_found() {
+			for (z, x, y) in &[(1, 2, "0.mvt"), (0, 0, "0.png"), (0, 0, "x.mvt")] {
+				assert_eq!(tile(*z, *x, y, None).status(), hyper::StatusCode::NOT_FOUND);
+			}
+
+			assert_eq!(
+				tile(0, 0, "0.mvt", Some("unknown")).status(),
+				hyper::StatusCode::NOT_FOUND
+			);
+		}
+	}
+
+	mod simplified {
+		use super::super::{BodyKey, Encoding, Format, BODY_CACHE_LIMIT};
+		use super::{Lazy, Shapefile, ShapefileType};
+		use std::{collections::HashMap, sync::Arc};
+
+		/// A block with a jog in its eastern edge that's smaller than a pixel at
+		/// all but the finest zoom levels
+		fn shapefiles() -> Lazy<Shapefile> {
+			let blocks: geojson::GeoJson = "{\"type\":\"FeatureCollection\",\"features\":[\
+				{\"type\":\"Feature\",\"properties\":{\"GEOID10\":\"181570052001013\"},\
+				\"geometry\":{\"type\":\"Polygon\",\"coordinates\":\
+				[[[-86.9,40.4],[-86.8,40.4],[-86.79999,40.45],[-86.8,40.5],[-86.9,40.5],[-86.9,40.4]]]}}]}"
+				.parse()
+				.unwrap();
+
+			let mut shapefiles = HashMap::new();
+			shapefiles.insert(
+				"blocks".to_string(),
+				Arc::new(Shapefile::new(ShapefileType::TabularBlock, blocks).unwrap()),
+			);
+			shapefiles.into()
+		}
+
+		async fn vertices(parameters: super::super::ShowParameters) -> usize {
+			let response = super::super::show(
+				&shapefiles(),
+				"blocks",
+				parameters,
+				&Default::default(),
+				Default::default(),
+			);
+			let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+			let collection: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+			collection["features"][0]["geometry"]["coordinates"][0]
+				.as_array()
+				.unwrap()
+				.len()
+		}
+
+		#[tokio::test]
+		async fn zoom_picks_simplified_geometry() {
+			let parameters = |zoom| super::super::ShowParameters {
+				zoom: Some(zoom),
+				..Default::default()
+			};
+
+			assert_eq!(vertices(parameters(8)).await, 5);
+			assert_eq!(vertices(parameters(18)).await, 6);
+		}
+
+		#[test]
+		fn only_the_levels_asked_for_are_simplified() {
+			let shapefile = shapefiles().get("blocks").unwrap();
+			let parameters = super::super::ShowParameters {
+				zoom: Some(7),
+				..Default::default()
+			};
+			shapefile.data(&parameters, "blocks", Encoding::Identity);
+
+			let made: Vec<BodyKey> = shapefile
+				.bodies
+				.lock()
+				.unwrap()
+				.bodies
+				.keys()
+				.copied()
+				.collect();
+			assert_eq!(made, vec![(Format::GeoJson, Some(2), Encoding::Identity)]);
+		}
+
+		#[tokio::test]
+		async fn tolerance_picks_simplified_geometry() {
+			let parameters = |tolerance| super::super::ShowParameters {
+				tolerance: Some(tolerance),
+				..Default::default()
+			};
+
+			assert_eq!(vertices(parameters(0.01)).await, 5);
+			assert_eq!(vertices(parameters(0.000_001)).await, 6);
+		}
+
+		async fn topojson(parameters: super::super::ShowParameters) -> serde_json::Value {
+			let response = super::super::show(
+				&shapefiles(),
+				"blocks",
+				parameters,
+				&Default::default(),
+				Default::default(),
+			);
+			assert_eq!(
+				response.headers()[hyper::header::CONTENT_TYPE],
+				"application/json"
+			);
+
+			let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+			serde_json::from_slice(&body).unwrap()
+		}
+
+		#[tokio::test]
+		async fn topojson_is_quantized() {
+			let topology = topojson(super::super::ShowParameters {
+				format: super::super::Format::TopoJson,
+				..Default::default()
+			})
+			.await;
+
+			assert_eq!(topology["type"], "Topology");
+			assert_eq!(topology["transform"]["translate"][0], -86.9);
+
+			let geometry = &topology["objects"]["blocks"]["geometries"][0];
+			assert_eq!(geometry["id"], "181570052001013");
+			assert_eq!(geometry["properties"]["GEOID10"], "181570052001013");
+			assert_eq!(geometry["arcs"], serde_json::json!([[[0]]]));
+
+			// The first position is absolute, and the rest are deltas.
+			let arc = topology["arcs"][0].as_array().unwrap();
+			assert_eq!(arc.len(), 6);
+			assert_eq!(arc[0], serde_json::json!([0, 0]));
+		}
+
+		#[test]
+		fn topojson_counts_towards_the_footprint() {
+			let shapefile = shapefiles().get("blocks").unwrap();
+			let before: usize = shapefile.footprint();
+
+			let parameters = super::super::ShowParameters {
+				format: Format::TopoJson,
+				..Default::default()
+			};
+			let topojson: usize = shapefile
+				.data(&parameters, "blocks", Encoding::Identity)
+				.len();
+
+			// The arcs are built for the TopoJSON, and count too.
+			assert_eq!(
+				shapefile.footprint(),
+				before + shapefile.arcs().size() + topojson
+			);
+		}
+
+		#[tokio::test]
+		async fn topojson_can_be_simplified() {
+			let topology = topojson(super::super::ShowParameters {
+				format: super::super::Format::TopoJson,
+				zoom: Some(8),
+				..Default::default()
+			})
+			.await;
+
+			assert_eq!(topology["arcs"][0].as_array().unwrap().len(), 5);
+		}
+
+		#[test]
+		fn bodies_are_kept_in_each_coding_up_to_a_limit() {
+			let shapefile = shapefiles().get("blocks").unwrap();
+
+			for zoom in &[4, 6, 8, 10, 12] {
+				let parameters = super::super::ShowParameters {
+					zoom: Some(*zoom),
+					..Default::default()
+				};
+
+				for encoding in &[Encoding::Identity, Encoding::Gzip] {
+					shapefile.data(&parameters, "blocks", *encoding);
+				}
+			}
+
+			let bodies = shapefile.bodies.lock().unwrap();
+			assert_eq!(bodies.bodies.len(), BODY_CACHE_LIMIT);
+			assert!(!bodies
+				.bodies
+				.contains_key(&(Format::GeoJson, Some(0), Encoding::Identity)));
+			assert!(bodies
+				.bodies
+				.contains_key(&(Format::GeoJson, Some(4), Encoding::Identity)));
+			assert!(bodies
+				.bodies
+				.contains_key(&(Format::GeoJson, Some(4), Encoding::Gzip)));
+		}
+	}
+
+	mod check {
+		use super::super::ShapefileConfiguration;
+		use super::ShapefileType;
+		use crate::server::routes::api::{plans::tests::directory, validation::Report};
+
+		fn check(contents: &str) -> Report {
+			let file = directory("shapefile-check").join("blocks.geojson");
+			std::fs::write(&file, contents).unwrap();
+
+			let configuration = ShapefileConfiguration {
+				ty: ShapefileType::TabularBlock,
+				file: file.to_string_lossy().into_owned(),
+			};
+
+			let mut report = Report::default();
+			configuration.check("shapefiles.blocks", &mut report);
+			report
+		}
+
+		#[test]
+		fn only_the_header_is_checked() {
+			assert!(check("{\"type\":\"FeatureCollection\",\"features\":[]}").is_empty());
+			assert!(check("{\n  \"type\": \"FeatureCollection\",\n  \"features\": [").is_empty());
+
+			let report = check("not GeoJSON");
+			assert_eq!(report.problems().len(), 1);
+			assert!(report.problems()[0].starts_with("shapefiles.blocks.file: "));
+		}
+
+		#[test]
+		fn shapefiles_must_be_feature_collections() {
+			let report = check("{\"type\":\"Point\",\"coordinates\":[0.0,0.0]}");
+
+			assert_eq!(report.problems().len(), 1);
+			assert!(report.problems()[0].contains("FeatureCollection"));
+		}
+	}
+
+	mod query {
+		use super::{Lazy, Shapefile, ShapefileType};
+		use std::{collections::HashMap, sync::Arc};
+
+		/// Two side-by-side one-degree blocks
+		fn shapefiles() -> Lazy<Shapefile> {
+			let blocks: geojson::GeoJson = "{\"type\":\"FeatureCollection\",\"features\":[\
+				{\"type\":\"Feature\",\"properties\":{\"GEOID10\":\"a\"},\"geometry\":{\"type\":\"Polygon\",\
+				\"coordinates\":[[[0.0,0.0],[1.0,0.0],[1.0,1.0],[0.0,1.0],[0.0,0.0]]]}},\
+				{\"type\":\"Feature\",\"properties\":{\"GEOID10\":\"b\"},\"geometry\":{\"type\":\"Polygon\",\
+				\"coordinates\":[[[1.0,0.0],[2.0,0.0],[2.0,1.0],[1.0,1.0],[1.0,0.0]]]}}\
+				]}"
+				.parse()
+				.unwrap();
+
+			let mut shapefiles = HashMap::new();
+			shapefiles.insert(
+				"blocks".to_string(),
+				Arc::new(Shapefile::new(ShapefileType::TabularBlock, blocks).unwrap()),
+			);
+			shapefiles.into()
+		}
+
+		async fn geoids(bbox: Option<&str>, geoid: Option<&str>) -> Vec<String> {
+			let parameters = super::super::ShowParameters {
+				bbox: bbox.map(ToString::to_string),
+				geoid: geoid.map(ToString::to_string),
+				..Default::default()
+			};
+
+			let response = super::super::show(
+				&shapefiles(),
+				"blocks",
+				parameters,
+				&Default::default(),
+				Default::default(),
+			);
+			assert_eq!(response.status(), hyper::StatusCode::OK);
+
+			let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+			let collection: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+			collection["features"]
+				.as_array()
+				.unwrap()
+				.iter()
+				.map(|feature| {
+					feature["properties"]["GEOID10"]
+						.as_str()
+						.unwrap()
+						.to_string()
+				})
+				.collect()
+		}
+
+		#[tokio::test]
+		async fn features_can_be_filtered() {
+			assert_eq!(geoids(Some("0.1,0.1,0.2,0.2"), None).await, vec!["a"]);
+			assert_eq!(geoids(Some("0.5,0.5,1.5,0.5"), None).await, vec!["a", "b"]);
+			assert_eq!(geoids(Some("5,5,6,6"), None).await, Vec::<String>::new());
+
+			assert_eq!(geoids(None, Some("b,c")).await, vec!["b"]);
+			assert_eq!(geoids(None, Some("b,a")).await, vec!["a", "b"]);
+
+			assert_eq!(geoids(Some("0.5,0.5,1.5,0.5"), Some("b")).await, vec!["b"]);
+		}
+
+		#[test]
+		fn malformed_bounding_boxes_are_unprocessable() {
+			for bbox in &["0,0,1", "0,0,1,a", "0,0,1,1,1"] {
+				let parameters = super::super::ShowParameters {
+					bbox: Some(bbox.to_string()),
+					..Default::default()
+				};
+
+				assert_eq!(
+					super::super::show(
+						&shapefiles(),
+						"blocks",
+						parameters,
+						&Default::default(),
+						Default::default()
+					)
+					.status(),
+					hyper::StatusCode::UNPROCESSABLE_ENTITY
+				);
+			}
+		}
+
+		#[tokio::test]
+		async fn single_features_can_be_fetched() {
+			let response = super::super::feature(&shapefiles(), "blocks", "b", &Default::default());
+			assert_eq!(response.status(), hyper::StatusCode::OK);
+
+			let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+			let feature: serde_json::Value = serde_json::from_slice(&body).unwrap();
+			assert_eq!(feature["properties"]["GEOID10"], "b");
+			assert_eq!(feature["geometry"]["type"], "Polygon");
+			assert_eq!(
+				feature["geometry"]["coordinates"][0][1],
+				serde_json::json!([2.0, 0.0])
+			);
+
+			assert_eq!(
+				super::super::feature(&shapefiles(), "blocks", "c", &Default::default()).status(),
+				hyper::StatusCode::NOT_FOUND
+			);
+			assert_eq!(
+				super::super::feature(&shapefiles(), "tracts", "b", &Default::default()).status(),
+				hyper::StatusCode::NOT_FOUND
+			);
+		}
+
+		#[test]
+		fn features_are_revalidated_without_hashing_them() {
+			let shapefiles = shapefiles();
+			let response = super::super::feature(&shapefiles, "blocks", "b", &Default::default());
+			let etag = response.headers()[hyper::header::ETAG].clone();
+
+			let mut headers = http::HeaderMap::new();
+			headers.insert(hyper::header::IF_NONE_MATCH, etag.clone());
+			let conditions = super::super::Conditions::from_headers(&headers);
+
+			let response = super::super::feature(&shapefiles, "blocks", "b", &conditions);
+			assert_eq!(response.status(), hyper::StatusCode::NOT_MODIFIED);
+
+			// Other features have other tags.
+			let response = super::super::feature(&shapefiles, "blocks", "a", &conditions);
+			assert_eq!(response.status(), hyper::StatusCode::OK);
+			assert_ne!(response.headers()[hyper::header::ETAG], etag);
+		}
+
+		async fn locate(
+			lon: f64,
+			lat: f64,
+			plan: Option<u64>,
+			store: &super::super::PlanStore,
+		) -> (hyper::StatusCode, serde_json::Value) {
+			let parameters = super::super::LocateParameters {
+				lat,
+				lon,
+				plan,
+				dataset: None,
+			};
+
+			let response = super::super::locate(
+				&shapefiles(),
+				&Lazy::default(),
+				store,
+				"blocks",
+				parameters,
+				&Default::default(),
+			);
+			let status = response.status();
+			let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+
+			(status, serde_json::from_slice(&body).unwrap())
+		}
+
+		#[tokio::test]
+		async fn points_are_located() {
+			use crate::server::routes::api::plans::tests::{directory, parameters};
+
+			let store = super::super::PlanStore::open(directory("locate")).unwrap();
+			let plan = store.create(parameters(2, &[("b", 2)])).unwrap();
+
+			let (status, location) = locate(1.5, 0.5, None, &store).await;
+			assert_eq!(status, hyper::StatusCode::OK);
+			assert_eq!(location, serde_json::json!({ "geoid": "b" }));
+
+			let (_, location) = locate(1.5, 0.5, Some(plan.id()), &store).await;
+			assert_eq!(location["district"], 2);
+
+			let (_, location) = locate(0.5, 0.5, Some(plan.id()), &store).await;
+			assert_eq!(location["geoid"], "a");
+			assert_eq!(location["district"], serde_json::Value::Null);
+
+			assert_eq!(
+				locate(5.0, 5.0, None, &store).await.0,
+				hyper::StatusCode::NOT_FOUND
+			);
+			assert_eq!(
+				locate(0.5, 0.5, Some(plan.id() + 1), &store).await.0,
+				hyper::StatusCode::NOT_FOUND
+			);
+			assert_eq!(
+				locate(0.5, 95.0, None, &store).await.0,
+				hyper::StatusCode::UNPROCESSABLE_ENTITY
+			);
+		}
+	}
 }
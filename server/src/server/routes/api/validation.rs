@@ -0,0 +1,70 @@
+//! Checking a configuration before anything is loaded from it
+
+use std::path::Path;
+
+/// Everything found to be wrong with a configuration
+///
+/// Problems are collected rather than returned one at a time, so that they can
+/// all be fixed in one go.
+#[derive(Debug, Default)]
+pub struct Report {
+	problems: Vec<String>,
+}
+
+impl Report {
+	/// Note a problem with the value at `key`
+	pub fn push<D: core::fmt::Display>(&mut self, key: &str, problem: D) {
+		let problem: String = format!("{}: {}", key, problem);
+		self.problems.push(problem.trim_end().to_string());
+	}
+
+	/// Note a problem unless `path` is a file that can be read
+	pub fn require_file<P: AsRef<Path>>(&mut self, key: &str, path: P) {
+		let path: &Path = path.as_ref();
+
+		match std::fs::metadata(path) {
+			Ok(metadata) if metadata.is_file() => {}
+			Ok(_) => self.push(key, format_args!("{} is not a file", path.display())),
+			Err(error) => self.push(key, format_args!("{}: {}", path.display(), error)),
+		}
+	}
+
+	pub fn problems(&self) -> &[String] {
+		&self.problems
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.problems.is_empty()
+	}
+
+	pub fn into_result(self) -> distringo::Result<()> {
+		if self.is_empty() {
+			Ok(())
+		} else {
+			Err(distringo::Error::InvalidConfiguration(self.problems))
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Report;
+
+	#[test]
+	fn problems_are_collected() {
+		let mut report = Report::default();
+		assert!(report.is_empty());
+
+		report.require_file("shapefiles.missing.file", "<some missing file>");
+		report.require_file("shapefiles.directory.file", ".");
+		report.push("server.port", "not a port");
+
+		assert_eq!(report.problems().len(), 3);
+		assert!(report.problems()[1].starts_with("shapefiles.directory.file: . is not a file"));
+
+		match report.into_result() {
+			Err(distringo::Error::InvalidConfiguration(problems)) => assert_eq!(problems.len(), 3),
+			result => panic!("expected invalid configuration, got {:?}", result),
+		}
+	}
+}
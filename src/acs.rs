@@ -0,0 +1,184 @@
+//! American Community Survey summary files
+//!
+//! Unlike the decennial census, the ACS publishes samples, so every value
+//! comes as an estimate and its margin of error, from parallel estimate
+//! (`e…`) and margin (`m…`) files.  Tables are laid out in numbered
+//! sequences, as described by the Census Bureau's sequence/table lookup
+//! file, and areas by the geography file (`g…`).
+
+use serde::{Deserialize, Serialize};
+
+mod geographical_header;
+pub use geographical_header::GeographicalHeader;
+
+mod lookup;
+pub use lookup::*;
+
+mod summary_file;
+pub use summary_file::*;
+
+/// An ACS table, by its ID, e.g. `B01001` or `C16001`
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct Table(String);
+
+impl core::fmt::Display for Table {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		f.write_str(&self.0)
+	}
+}
+
+impl core::str::FromStr for Table {
+	type Err = crate::Error;
+
+	/// Parse a table ID, ignoring case
+	///
+	/// IDs start with `B` for detailed tables or `C` for collapsed ones, then
+	/// the table number, e.g. `B01001A` or `C16001`.
+	fn from_str(s: &str) -> crate::Result<Self> {
+		let id: String = s.trim().to_uppercase();
+		let number: &str = id
+			.strip_prefix(&['B', 'C'][..])
+			.ok_or(crate::Error::InvalidTableName)?;
+
+		if number.len() >= 5
+			&& number.bytes().take(5).all(|byte| byte.is_ascii_digit())
+			&& number.bytes().all(|byte| byte.is_ascii_alphanumeric())
+		{
+			Ok(Self(id))
+		} else {
+			Err(crate::Error::InvalidTableName)
+		}
+	}
+}
+
+/// The margin of error of estimates that are controlled to official counts,
+/// and so have no sampling error
+const CONTROLLED: &str = "-555555555";
+
+/// An estimate, and its margin of error at the 90% confidence level
+///
+/// Either is `None` where the Census Bureau didn't publish one, e.g. for
+/// too few sample observations.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Estimate {
+	pub estimate: Option<f64>,
+	pub margin: Option<f64>,
+}
+
+impl Estimate {
+	/// Interpret a cell of an estimate file and its cell of the margin file
+	///
+	/// Negative values are the Census Bureau's annotation codes rather than
+	/// values, except for the margin of controlled estimates, which is zero.
+	pub fn parse(estimate: &str, margin: &str) -> Self {
+		let value = |cell: &str| -> Option<f64> {
+			cell
+				.trim()
+				.parse::<f64>()
+				.ok()
+				.filter(|value| *value >= 0.0)
+		};
+
+		Self {
+			estimate: value(estimate),
+			margin: if margin.trim() == CONTROLLED {
+				Some(0.0)
+			} else {
+				value(margin)
+			},
+		}
+	}
+
+	/// Combine the estimates of disjoint areas into one for their union
+	///
+	/// Margins are combined as the square root of the sum of their squares,
+	/// the Census Bureau's approximation for sums.  Following its guidance,
+	/// only the largest margin of the zero estimates counts, so that many
+	/// empty areas don't inflate the margin of the sum.
+	pub fn sum<I: IntoIterator<Item = Estimate>>(estimates: I) -> Self {
+		let mut total: Option<f64> = Some(0.0);
+		let mut squares: Option<f64> = Some(0.0);
+		let mut zero: Option<f64> = Some(0.0);
+
+		for Estimate { estimate, margin } in estimates {
+			total = total
+				.zip(estimate)
+				.map(|(total, estimate)| total + estimate);
+
+			match (estimate, margin) {
+				(Some(0.0), Some(margin)) => {
+					zero = zero.map(|zero| zero.max(margin));
+				}
+				(_, Some(margin)) => {
+					squares = squares.map(|squares| squares + margin * margin);
+				}
+				(_, None) => {
+					squares = None;
+				}
+			}
+		}
+
+		Self {
+			estimate: total,
+			margin: squares
+				.zip(zero)
+				.map(|(squares, zero)| (squares + zero * zero).sqrt()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Estimate, Table};
+
+	#[test]
+	fn table_ids_are_validated() {
+		assert_eq!("b01001".parse::<Table>().unwrap().to_string(), "B01001");
+		assert_eq!("C16001".parse::<Table>().unwrap().to_string(), "C16001");
+		assert_eq!("B01001A".parse::<Table>().unwrap().to_string(), "B01001A");
+
+		for id in &["", "P1", "B0100", "B01001-"] {
+			assert!(id.parse::<Table>().is_err(), "{} parsed", id);
+		}
+	}
+
+	#[test]
+	fn margins_are_combined_by_root_sum_of_squares() {
+		let estimate = |estimate: &str, margin: &str| Estimate::parse(estimate, margin);
+
+		assert_eq!(
+			Estimate::sum(vec![estimate("100", "30"), estimate("50", "40")]),
+			Estimate {
+				estimate: Some(150.0),
+				margin: Some(50.0)
+			}
+		);
+
+		// Only the largest margin of the zero estimates counts.
+		assert_eq!(
+			Estimate::sum(vec![
+				estimate("100", "30"),
+				estimate("0", "11"),
+				estimate("0", "40"),
+			]),
+			Estimate {
+				estimate: Some(100.0),
+				margin: Some(50.0)
+			}
+		);
+
+		// Controlled estimates have no sampling error.
+		assert_eq!(estimate("42", "-555555555").margin, Some(0.0));
+
+		let suppressed = estimate("-666666666", "-222222222");
+		assert_eq!(suppressed.estimate, None);
+		assert_eq!(suppressed.margin, None);
+		assert_eq!(
+			Estimate::sum(vec![estimate("100", "30"), suppressed]),
+			Estimate {
+				estimate: None,
+				margin: None
+			}
+		);
+	}
+}
@@ -0,0 +1,102 @@
+/// A record of the geography file, e.g. `g20195in.csv`
+///
+/// The file is comma-separated, with fields at fixed positions.  It has no
+/// blocks nor voting districts, so those codes are always blank.
+pub struct GeographicalHeader {
+	fields: Vec<String>,
+}
+
+const SUMLEVEL: usize = 2;
+const COMPONENT: usize = 3;
+const LOGRECNO: usize = 4;
+const STATE: usize = 9;
+const COUNTY: usize = 10;
+const PLACE: usize = 12;
+const TRACT: usize = 13;
+const BLKGRP: usize = 14;
+const GEOID: usize = 48;
+const NAME: usize = 49;
+
+impl GeographicalHeader {
+	/// Read a record of the geography file
+	///
+	/// The file is Latin-1, so names outside of ASCII are replaced rather than
+	/// failing the whole record.
+	pub fn new(record: &csv::ByteRecord) -> crate::Result<Self> {
+		let fields: Vec<String> = record
+			.iter()
+			.map(|field| String::from_utf8_lossy(field).into_owned())
+			.collect();
+
+		if fields.len() <= NAME || fields[LOGRECNO].trim().parse::<u64>().is_err() {
+			return Err(crate::Error::InvalidLogicalRecordNumber);
+		}
+
+		Ok(Self { fields })
+	}
+
+	fn field(&self, idx: usize) -> &str {
+		&self.fields[idx]
+	}
+
+	/// The full GeoID, e.g. `15000US181570052001` for a block group
+	pub fn geoid(&self) -> &str {
+		self.field(GEOID)
+	}
+
+	/// The GeoID without its summary level and component, e.g. `181570052001`
+	pub fn geocode(&self) -> &str {
+		self.geoid().split_once("US").map_or("", |(_, code)| code)
+	}
+}
+
+impl crate::GeographicalHeader for GeographicalHeader {
+	fn name(&self) -> &str {
+		self.field(NAME)
+	}
+
+	fn logrecno(&self) -> crate::LogicalRecordNumber {
+		self.field(LOGRECNO).trim().parse().unwrap()
+	}
+
+	/// The geography file has no population count; it's table `B01003`.
+	fn population(&self) -> crate::Result<u64> {
+		Err(crate::Error::MissingPopulation)
+	}
+
+	fn summary_level(&self) -> &str {
+		self.field(SUMLEVEL)
+	}
+
+	fn geographic_component(&self) -> &str {
+		self.field(COMPONENT)
+	}
+
+	fn state(&self) -> &str {
+		self.field(STATE)
+	}
+
+	fn county(&self) -> &str {
+		self.field(COUNTY)
+	}
+
+	fn tract(&self) -> &str {
+		self.field(TRACT)
+	}
+
+	fn block_group(&self) -> &str {
+		self.field(BLKGRP)
+	}
+
+	fn block(&self) -> &str {
+		""
+	}
+
+	fn voting_district(&self) -> &str {
+		""
+	}
+
+	fn place(&self) -> &str {
+		self.field(PLACE)
+	}
+}
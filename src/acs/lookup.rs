@@ -0,0 +1,136 @@
+use std::{collections::BTreeMap, io::Read, path::Path};
+
+use fnv::FnvHashMap;
+
+use super::Table;
+use crate::{Result, TableLocations, TableSegmentLocation};
+
+/// Where each table is in the sequence files
+///
+/// Read from the sequence/table lookup file, e.g.
+/// `ACS_5yr_Seq_Table_Number_Lookup.txt`.  Its rows give, for each table and
+/// sequence, the position the table starts at, then one row per line
+/// (cell) of the table.  Each sequence is a "file" of the table locations.
+pub struct Lookup {
+	tables: FnvHashMap<Table, TableLocations>,
+}
+
+const TABLE_ID: usize = 1;
+const SEQUENCE_NUMBER: usize = 2;
+const LINE_NUMBER: usize = 3;
+const START_POSITION: usize = 4;
+
+impl Lookup {
+	pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+		Self::from_reader(std::fs::File::open(path)?)
+	}
+
+	pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+		// The start and the number of cells of each table in each sequence
+		let mut segments: BTreeMap<(Table, u32), (Option<usize>, usize)> = BTreeMap::new();
+
+		let mut reader = csv::ReaderBuilder::new()
+			.has_headers(true)
+			.flexible(true)
+			.from_reader(reader);
+
+		for record in reader.byte_records() {
+			let record: csv::ByteRecord = record?;
+			let field = |idx: usize| -> &str {
+				record
+					.get(idx)
+					.and_then(|field| core::str::from_utf8(field).ok())
+					.map_or("", str::trim)
+			};
+
+			let table: Table = match field(TABLE_ID).parse() {
+				Ok(table) => table,
+				Err(_) => continue,
+			};
+			let sequence: u32 = field(SEQUENCE_NUMBER).parse()?;
+			let segment = segments.entry((table, sequence)).or_insert((None, 0));
+
+			// Headings have fractional line numbers, like `0.5`, and no cell.
+			if field(LINE_NUMBER).parse::<u32>().is_ok() {
+				segment.1 += 1;
+			} else if let Ok(start) = field(START_POSITION).parse::<usize>() {
+				// Positions count from 1, including the record's leading fields.
+				segment.0 = start.checked_sub(1);
+			}
+		}
+
+		let mut tables: FnvHashMap<Table, TableLocations> = FnvHashMap::default();
+		for ((table, sequence), (start, cells)) in segments {
+			if let Some(start) = start {
+				tables.entry(table).or_default().push(TableSegmentLocation {
+					file: sequence,
+					range: start..start + cells,
+				});
+			}
+		}
+
+		Ok(Self { tables })
+	}
+
+	pub fn table_locations(&self, table: &Table) -> Option<&TableLocations> {
+		self.tables.get(table)
+	}
+
+	/// Iterate over the tables this lookup knows of
+	pub fn tables(&self) -> impl Iterator<Item = &Table> {
+		self.tables.keys()
+	}
+
+	/// The sequences any table is in
+	pub(super) fn sequences(&self) -> impl Iterator<Item = u32> {
+		let mut sequences: Vec<u32> = self
+			.tables
+			.values()
+			.flatten()
+			.map(|location| location.file)
+			.collect();
+		sequences.sort_unstable();
+		sequences.dedup();
+
+		sequences.into_iter()
+	}
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+	use super::Lookup;
+	use crate::TableSegmentLocation;
+
+	/// A lookup of two tables in the first sequence
+	pub(in crate::acs) const LOOKUP: &str = "\
+File ID,Table ID,Sequence Number,Line Number,Start Position,Total Cells in Table,Total Cells in Sequence,Table Title,Subject Area
+ACSSF,B01003,0001,,7,1 CELL,,TOTAL POPULATION,Age-Sex
+ACSSF,B01003,0001,,,,,Universe:  Total population,Age-Sex
+ACSSF,B01003,0001,1,,,,Total,
+ACSSF,B29001,0001,,8,2 CELLS,,\"CITIZEN, VOTING-AGE POPULATION BY AGE\",Citizen Voting-Age Population
+ACSSF,B29001,0001,,,,,Universe:  Citizen 18 years and over,Citizen Voting-Age Population
+ACSSF,B29001,0001,0.5,,,,Citizen voting-age population:,
+ACSSF,B29001,0001,1,,,,Total:,
+ACSSF,B29001,0001,2,,,,18 to 29 years,
+";
+
+	#[test]
+	fn tables_are_located_by_their_lines() {
+		let lookup = Lookup::from_reader(LOOKUP.as_bytes()).unwrap();
+		assert_eq!(
+			lookup.table_locations(&"B01003".parse().unwrap()),
+			Some(&vec![TableSegmentLocation {
+				file: 1,
+				range: 6..7
+			}])
+		);
+		assert_eq!(
+			lookup.table_locations(&"B29001".parse().unwrap()),
+			Some(&vec![TableSegmentLocation {
+				file: 1,
+				range: 7..9
+			}])
+		);
+		assert_eq!(lookup.sequences().collect::<Vec<u32>>(), vec![1]);
+	}
+}
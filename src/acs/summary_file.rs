@@ -0,0 +1,435 @@
+use std::{
+	collections::BTreeMap,
+	fs::File,
+	io::{BufReader, Seek},
+	path::{Path, PathBuf},
+};
+
+use fnv::FnvHashMap;
+
+use super::{Estimate, GeographicalHeader, Lookup, Table};
+use crate::{
+	Error, FileBackedLogicalRecord, GeoId, GeographicalHeader as _, Geography, LogicalRecordNumber,
+	LogicalRecordPositionIndex, Result, TableSegmentLocation,
+};
+
+/// The summary level of block groups, the smallest areas the ACS publishes
+const BLOCK_GROUP: &str = "150";
+
+/// The geographic component of a record for the whole of its area
+const WHOLE: &str = "00";
+
+/// The field of a sequence file's records with their logical record number
+const LOGRECNO: usize = 5;
+
+/// An estimate or margin file of one sequence, and where its records are
+struct Sequence {
+	file: File,
+	index: LogicalRecordPositionIndex,
+}
+
+impl Sequence {
+	fn open(path: &Path, rows: usize) -> Result<Self> {
+		let file: File = File::open(path)?;
+		let mut index = LogicalRecordPositionIndex::new_with_size(rows);
+
+		let mut reader = csv::ReaderBuilder::new()
+			.has_headers(false)
+			.flexible(true)
+			.from_reader(BufReader::new(&file));
+		let mut record = csv::ByteRecord::new();
+
+		while reader.read_byte_record(&mut record)? {
+			let offset: u64 = record.position().map_or(0, csv::Position::byte);
+			let logrecno: Option<LogicalRecordNumber> = record
+				.get(LOGRECNO)
+				.and_then(|field| core::str::from_utf8(field).ok())
+				.and_then(|field| field.trim().parse().ok());
+
+			match logrecno {
+				Some(logrecno) if logrecno as usize <= rows => index.insert(logrecno, offset),
+				_ => return Err(Error::InvalidLogicalRecordNumber),
+			}
+		}
+
+		Ok(Self { file, index })
+	}
+
+	fn get(&self, logrecno: LogicalRecordNumber) -> Result<csv::StringRecord> {
+		let mut reader = BufReader::new(&self.file);
+		reader.seek(std::io::SeekFrom::Start(self.index[logrecno]))?;
+
+		let mut reader = csv::ReaderBuilder::new()
+			.has_headers(false)
+			.flexible(true)
+			.from_reader(reader);
+		let mut record = csv::StringRecord::new();
+		reader.read_record(&mut record)?;
+
+		// Records missing from the file are at offset zero, i.e. the first one.
+		match record
+			.get(LOGRECNO)
+			.map(|field| field.trim().parse::<LogicalRecordNumber>())
+		{
+			Some(Ok(number)) if number == logrecno => Ok(record),
+			_ => Err(Error::InvalidLogicalRecordNumber),
+		}
+	}
+}
+
+/// The estimate and margin records of some sequences for one area
+pub struct LogicalRecord {
+	estimates: FileBackedLogicalRecord,
+	margins: FileBackedLogicalRecord,
+}
+
+impl crate::LogicalRecord for LogicalRecord {
+	fn number(&self) -> LogicalRecordNumber {
+		self.estimates.number()
+	}
+}
+
+impl LogicalRecord {
+	/// Collect the estimates of a table, with their margins
+	///
+	/// Returns `None` if any segment is missing.
+	pub fn table(&self, locations: &[TableSegmentLocation]) -> Option<Vec<Estimate>> {
+		let estimates: Vec<&str> = self.estimates.table(locations)?;
+		let margins: Vec<&str> = self.margins.table(locations)?;
+
+		Some(
+			estimates
+				.into_iter()
+				.zip(margins)
+				.map(|(estimate, margin)| Estimate::parse(estimate, margin))
+				.collect(),
+		)
+	}
+}
+
+/// The totals of a table for each area of a geography, summed from block
+/// groups
+///
+/// Unlike the decennial census, these aren't checked against the published
+/// estimates for the areas, which are estimated from the sample on their own
+/// and so rarely match the sums.
+#[derive(Clone, Debug)]
+pub struct Aggregate {
+	pub geography: Geography,
+	pub table: Table,
+	pub totals: BTreeMap<String, Vec<Estimate>>,
+}
+
+/// An ACS summary file for one state
+///
+/// Files are found next to the geography file, named after it, e.g.
+/// `e20195in0001000.txt` and `m20195in0001000.txt` for the first sequence of
+/// `g20195in.csv`.
+pub struct SummaryFile {
+	lookup: Lookup,
+	directory: PathBuf,
+	stem: String,
+	headers: Vec<GeographicalHeader>,
+	geoids: BTreeMap<GeoId, usize>,
+	estimates: FnvHashMap<u32, Sequence>,
+	margins: FnvHashMap<u32, Sequence>,
+}
+
+impl SummaryFile {
+	pub fn open<P: AsRef<Path>>(geography: P, lookup: Lookup) -> Result<Self> {
+		let geography: &Path = geography.as_ref();
+		let stem: String = geography
+			.file_stem()
+			.and_then(|stem| stem.to_str())
+			.and_then(|stem| stem.strip_prefix('g'))
+			.ok_or_else(|| Error::InvalidReference(geography.display().to_string()))?
+			.to_string();
+		let directory: PathBuf = geography
+			.parent()
+			.map(ToOwned::to_owned)
+			.unwrap_or_default();
+
+		let mut reader = csv::ReaderBuilder::new()
+			.has_headers(false)
+			.flexible(true)
+			.from_path(geography)?;
+
+		let mut headers: Vec<GeographicalHeader> = Vec::new();
+		let mut geoids: BTreeMap<GeoId, usize> = BTreeMap::new();
+		for record in reader.byte_records() {
+			let header = GeographicalHeader::new(&record?)?;
+
+			geoids.insert(header.geoid().to_string(), headers.len());
+			headers.push(header);
+		}
+
+		Ok(Self {
+			lookup,
+			directory,
+			stem,
+			headers,
+			geoids,
+			estimates: FnvHashMap::default(),
+			margins: FnvHashMap::default(),
+		})
+	}
+
+	/// Index the estimate and margin files of every sequence that's present
+	///
+	/// Downloads are often split, e.g. into tracts and block groups and the
+	/// rest, so missing sequences are skipped rather than failing.
+	pub fn index(mut self) -> Result<Self> {
+		let rows: usize = self
+			.headers
+			.iter()
+			.map(|header| header.logrecno() as usize)
+			.max()
+			.unwrap_or(0);
+
+		for sequence in self.lookup.sequences() {
+			let estimates: PathBuf = self.sequence_path('e', sequence);
+			let margins: PathBuf = self.sequence_path('m', sequence);
+
+			if !estimates.exists() || !margins.exists() {
+				log::debug!("Skipping missing sequence {}", sequence);
+				continue;
+			}
+
+			log::debug!("Indexing sequence {}", sequence);
+
+			self
+				.estimates
+				.insert(sequence, Sequence::open(&estimates, rows)?);
+			self
+				.margins
+				.insert(sequence, Sequence::open(&margins, rows)?);
+		}
+
+		Ok(self)
+	}
+
+	fn sequence_path(&self, kind: char, sequence: u32) -> PathBuf {
+		self
+			.directory
+			.join(format!("{}{}{:04}000.txt", kind, self.stem, sequence))
+	}
+
+	pub fn lookup(&self) -> &Lookup {
+		&self.lookup
+	}
+
+	/// Iterate over the geographical headers, in the order of the file
+	pub fn headers(&self) -> impl Iterator<Item = &GeographicalHeader> {
+		self.headers.iter()
+	}
+
+	/// The header of an area, by its full GeoID, e.g. `15000US181570052001`
+	pub fn header(&self, geoid: &str) -> Result<&GeographicalHeader> {
+		self
+			.geoids
+			.get(geoid)
+			.map(|idx| &self.headers[*idx])
+			.ok_or(Error::InvalidGeoId)
+	}
+
+	/// Read the records of the sequences a table is in
+	pub fn get_logical_record(
+		&self,
+		number: LogicalRecordNumber,
+		table: &Table,
+	) -> Result<LogicalRecord> {
+		let locations = self
+			.lookup
+			.table_locations(table)
+			.ok_or(Error::InvalidTableName)?;
+
+		let mut estimates = FnvHashMap::default();
+		let mut margins = FnvHashMap::default();
+		for location in locations {
+			let (estimate, margin) = match (
+				self.estimates.get(&location.file),
+				self.margins.get(&location.file),
+			) {
+				(Some(estimate), Some(margin)) => (estimate, margin),
+				_ => return Err(Error::InvalidTableName),
+			};
+
+			estimates.insert(location.file, estimate.get(number)?);
+			margins.insert(location.file, margin.get(number)?);
+		}
+
+		Ok(LogicalRecord {
+			estimates: FileBackedLogicalRecord::new(number, estimates),
+			margins: FileBackedLogicalRecord::new(number, margins),
+		})
+	}
+
+	fn values(&self, number: LogicalRecordNumber, table: &Table) -> Result<Vec<Estimate>> {
+		let locations = self
+			.lookup
+			.table_locations(table)
+			.ok_or(Error::InvalidTableName)?;
+
+		self
+			.get_logical_record(number, table)?
+			.table(locations)
+			.ok_or(Error::InvalidLogicalRecordNumber)
+	}
+
+	/// The estimates of a table for an area, by its full GeoID
+	pub fn estimates(&self, geoid: &str, table: &Table) -> Result<Vec<Estimate>> {
+		self.values(self.header(geoid)?.logrecno(), table)
+	}
+
+	/// Sum the block groups of a table up to the areas of a geography
+	///
+	/// Margins are combined as for any sum of estimates; see [`Estimate::sum`].
+	pub fn aggregate(&self, table: &Table, geography: Geography) -> Result<Aggregate> {
+		let mut parts: BTreeMap<String, Vec<Vec<Estimate>>> = BTreeMap::new();
+
+		for header in &self.headers {
+			if header.summary_level() != BLOCK_GROUP || header.geographic_component() != WHOLE {
+				continue;
+			}
+
+			if let Some(code) = geography.code(header) {
+				let values: Vec<Estimate> = self.values(header.logrecno(), table)?;
+				parts.entry(code).or_default().push(values);
+			}
+		}
+
+		let totals: BTreeMap<String, Vec<Estimate>> = parts
+			.into_iter()
+			.map(|(code, parts)| {
+				let cells: usize = parts.iter().map(Vec::len).min().unwrap_or(0);
+				let total: Vec<Estimate> = (0..cells)
+					.map(|cell| Estimate::sum(parts.iter().map(|part| part[cell])))
+					.collect();
+
+				(code, total)
+			})
+			.collect();
+
+		Ok(Aggregate {
+			geography,
+			table: table.clone(),
+			totals,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{super::Lookup, SummaryFile};
+	use crate::{acs::Estimate, Geography};
+	use std::path::Path;
+
+	/// A record of the geography file, with only the fields we read
+	fn geography(sumlev: &str, logrecno: &str, tract: &str, blkgrp: &str) -> String {
+		let mut fields: Vec<String> = vec![String::new(); 53];
+		fields[0] = "ACSSF".to_string();
+		fields[1] = "IN".to_string();
+		fields[2] = sumlev.to_string();
+		fields[3] = "00".to_string();
+		fields[4] = logrecno.to_string();
+		fields[9] = "18".to_string();
+		fields[10] = "157".to_string();
+		fields[13] = tract.to_string();
+		fields[14] = blkgrp.to_string();
+		fields[48] = format!("{}00US18157{}{}", sumlev, tract, blkgrp);
+		fields[49] = format!("\"Area {}, Indiana\"", logrecno);
+
+		fields.join(",") + "\n"
+	}
+
+	/// A summary file of one tract and its block groups, and the directory it
+	/// was written to, which is removed when dropped
+	fn summary_file() -> (tempfile::TempDir, SummaryFile) {
+		let temporary = tempfile::tempdir().unwrap();
+		let directory: &Path = temporary.path();
+
+		std::fs::write(
+			directory.join("g20195in.csv"),
+			[
+				geography("140", "0000001", "005200", ""),
+				geography("150", "0000002", "005200", "1"),
+				geography("150", "0000003", "005200", "2"),
+				geography("150", "0000004", "005300", "1"),
+			]
+			.concat(),
+		)
+		.unwrap();
+
+		let sequence = |rows: &[&str]| -> String {
+			rows
+				.iter()
+				.enumerate()
+				.map(|(idx, cells)| format!("ACSSF,20195in,in,000,0001,{:07},{}\n", idx + 1, cells))
+				.collect()
+		};
+		std::fs::write(
+			directory.join("e20195in0001000.txt"),
+			sequence(&["150,120,30", "100,80,20", "50,40,0", "7,.,0"]),
+		)
+		.unwrap();
+		std::fs::write(
+			directory.join("m20195in0001000.txt"),
+			sequence(&["45,40,12", "30,-555555555,11", "40,25,13", "5,-222222222,9"]),
+		)
+		.unwrap();
+
+		let lookup = Lookup::from_reader(super::super::lookup::tests::LOOKUP.as_bytes()).unwrap();
+
+		let summary_file = SummaryFile::open(directory.join("g20195in.csv"), lookup)
+			.and_then(SummaryFile::index)
+			.unwrap();
+
+		(temporary, summary_file)
+	}
+
+	#[test]
+	fn block_groups_are_summed_with_their_margins() {
+		let (_directory, summary_file) = summary_file();
+		let population = "B01003".parse().unwrap();
+		let cvap = "B29001".parse().unwrap();
+
+		assert_eq!(
+			summary_file
+				.estimates("15000US181570052001", &cvap)
+				.unwrap(),
+			vec![
+				Estimate::parse("80", "-555555555"),
+				Estimate::parse("20", "11")
+			]
+		);
+		assert!(summary_file
+			.estimates("15000US181570099001", &cvap)
+			.is_err());
+
+		let tracts = summary_file
+			.aggregate(&population, Geography::Tract)
+			.unwrap();
+		assert_eq!(tracts.totals.len(), 2);
+		assert_eq!(
+			tracts.totals["18157005200"],
+			vec![Estimate {
+				estimate: Some(150.0),
+				margin: Some(50.0)
+			}]
+		);
+
+		// A zero estimate's margin only counts if it's the largest of them.
+		let tracts = summary_file.aggregate(&cvap, Geography::Tract).unwrap();
+		assert_eq!(
+			tracts.totals["18157005200"][1],
+			Estimate {
+				estimate: Some(20.0),
+				margin: Some(13.0_f64.hypot(11.0))
+			}
+		);
+
+		// Suppressed estimates can't be summed.
+		assert_eq!(tracts.totals["18157005300"][0].estimate, None);
+		assert_eq!(tracts.totals["18157005300"][0].margin, None);
+	}
+}
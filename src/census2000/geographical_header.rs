@@ -0,0 +1,190 @@
+macro_rules! generate_field_getter {
+	($container_type:ty, $container_data_field:ident, $name:ident, [$vis:vis , $getter_name:ident #> $pty:ty]) => {
+		#[allow(dead_code)]
+		impl $container_type {
+			#[must_use]
+			$vis fn $getter_name(&self) -> $pty {
+				self.$container_data_field[$name].parse::<$pty>().unwrap()
+			}
+		}
+	};
+
+	($container_type:ty, $container_data_field:ident, $name:ident, [$vis:vis , $getter_name:ident]) => {
+		#[allow(dead_code)]
+		impl $container_type {
+			#[must_use]
+			$vis fn $getter_name(&self) -> &str {
+				&self.$container_data_field[$name]
+			}
+		}
+	};
+
+	($container_type:ty, $container_data_field:ident, $name:ident, [ $vis:vis , | $getter_name:ident | ]) => {
+		#[allow(dead_code)]
+		impl $container_type {
+			#[must_use]
+			$vis fn $getter_name(&self) -> &str {
+				&self.$container_data_field[$name].trim()
+			}
+		}
+	};
+}
+
+macro_rules! generate_fields {
+	($container_type:ident, $container_data_field:ident, $($name:ident @ { $loc:expr } - $rest:tt),+) => {
+		$(
+			#[allow(dead_code)]
+			const $name: core::ops::Range<usize> = $loc;
+
+			generate_field_getter!($container_type, $container_data_field, $name, $rest);
+		)+
+	};
+}
+
+generate_fields!(
+	GeographicalHeader, data,
+	FILEID @ { 0..6 } - [pub, fileid],
+	STUSAB @ { 6..8 } - [pub, stusab],
+	SUMLEV @ { 8..11 } - [pub, sumlev],
+	GEOCOMP @ { 11..13 } - [pub, geocomp],
+	CHARITER @ { 13..16 } - [pub, chariter],
+	CIFSN @ { 16..18 } - [pub, cifsn],
+	LOGRECNO @ { 18..25 } - [pub, logrecno #> crate::LogicalRecordNumber],
+	REGION @ { 25..26 } - [pub, region],
+	DIVISION @ { 26..27 } - [pub, division],
+	STATECE @ { 27..29 } - [pub, statece],
+	STATE @ { 29..31 } - [pub, state],
+	COUNTY @ { 31..34 } - [pub, county],
+	COUNTYSC @ { 34..36 } - [pub, countysc],
+	COUSUB @ { 36..41 } - [pub, cousub],
+	COUSUBCC @ { 41..43 } - [pub, cousubcc],
+	COUSUBSC @ { 43..45 } - [pub, cousubsc],
+	PLACE @ { 45..50 } - [pub, place],
+	PLACECC @ { 50..52 } - [pub, placecc],
+	PLACEDC @ { 52..53 } - [pub, placedc],
+	PLACESC @ { 53..55 } - [pub, placesc],
+	TRACT @ { 55..61 } - [pub, tract],
+	BLKGRP @ { 61..62 } - [pub, blkgrp],
+	BLOCK @ { 62..66 } - [pub, block],
+	IUC @ { 66..68 } - [pub, iuc],
+	CONCIT @ { 68..73 } - [pub, concit],
+	CONCITCC @ { 73..75 } - [pub, concitcc],
+	CONCITSC @ { 75..77 } - [pub, concitsc],
+	AIANHH @ { 77..81 } - [pub, aianhh],
+	AIANHHFP @ { 81..86 } - [pub, aianhhfp],
+	AIANHHCC @ { 86..88 } - [pub, aianhhcc],
+	AIHHTLI @ { 88..89 } - [pub, aihhtli],
+	AITSCE @ { 89..92 } - [pub, aitsce],
+	AITS @ { 92..97 } - [pub, aits],
+	AITSCC @ { 97..99 } - [pub, aitscc],
+	ANRC @ { 99..104 } - [pub, anrc],
+	ANRCCC @ { 104..106 } - [pub, anrccc],
+	MSACMSA @ { 106..110 } - [pub, msacmsa],
+	MASC @ { 110..112 } - [pub, masc],
+	CMSA @ { 112..114 } - [pub, cmsa],
+	MACCI @ { 114..115 } - [pub, macci],
+	PMSA @ { 115..119 } - [pub, pmsa],
+	NECMA @ { 119..123 } - [pub, necma],
+	NECMACCI @ { 123..124 } - [pub, necmacci],
+	NECMASC @ { 124..126 } - [pub, necmasc],
+	EXI @ { 126..127 } - [pub, exi],
+	UA @ { 127..132 } - [pub, ua],
+	UASC @ { 132..134 } - [pub, uasc],
+	UATYPE @ { 134..135 } - [pub, uatype],
+	UR @ { 135..136 } - [pub, ur],
+	CD106 @ { 136..138 } - [pub, cd106],
+	CD108 @ { 138..140 } - [pub, cd108],
+	CD109 @ { 140..142 } - [pub, cd109],
+	CD110 @ { 142..144 } - [pub, cd110],
+	SLDU @ { 144..147 } - [pub, sldu],
+	SLDL @ { 147..150 } - [pub, sldl],
+	VTD @ { 150..156 } - [pub, vtd],
+	VTDI @ { 156..157 } - [pub, vtdi],
+	ZCTA3 @ { 157..160 } - [pub, zcta3],
+	ZCTA5 @ { 160..165 } - [pub, zcta5],
+	SUBMCD @ { 165..170 } - [pub, submcd],
+	SUBMCDCC @ { 170..172 } - [pub, submcdcc],
+	AREALAND @ { 172..186 } - [pub, arealand],
+	AREAWATR @ { 186..200 } - [pub, areawatr],
+	NAME @ { 200..290 } - [pub, |name|],
+	FUNCSTAT @ { 290..291 } - [pub, funcstat],
+	GCUNI @ { 291..292 } - [pub, gcuni],
+	POP100 @ { 292..301 } - [pub, pop100],
+	HU100 @ { 301..310 } - [pub, hu100],
+	INTPTLAT @ { 310..319 } - [pub, intptlat],
+	INTPTLON @ { 319..329 } - [pub, intptlon],
+	LSADC @ { 329..331 } - [pub, lsadc],
+	PARTFLAG @ { 331..332 } - [pub, partflag],
+	SDELM @ { 332..337 } - [pub, sdelm],
+	SDSEC @ { 337..342 } - [pub, sdsec],
+	SDUNI @ { 342..347 } - [pub, sduni],
+	TAZ @ { 347..353 } - [pub, taz],
+	UGA @ { 353..358 } - [pub, uga],
+	PUMA5 @ { 358..363 } - [pub, puma5],
+	PUMA1 @ { 363..368 } - [pub, puma1],
+	RESERVE2 @ { 368..383 } - [, reserve2],
+	MACC @ { 383..388 } - [pub, macc],
+	UACP @ { 388..393 } - [pub, uacp],
+	RESERVED @ { 393..400 } - [, reserved]
+);
+
+/// The geographical header record of the 2000 redistricting file
+pub struct GeographicalHeader {
+	data: String,
+}
+
+impl GeographicalHeader {
+	pub fn new(data: String) -> Self {
+		Self { data }
+	}
+}
+
+impl crate::GeographicalHeader for GeographicalHeader {
+	fn name(&self) -> &str {
+		self.name()
+	}
+
+	fn logrecno(&self) -> crate::LogicalRecordNumber {
+		self.logrecno()
+	}
+
+	fn population(&self) -> crate::Result<u64> {
+		Ok(self.pop100().trim().parse()?)
+	}
+
+	fn summary_level(&self) -> &str {
+		self.sumlev()
+	}
+
+	fn geographic_component(&self) -> &str {
+		self.geocomp()
+	}
+
+	fn state(&self) -> &str {
+		self.state()
+	}
+
+	fn county(&self) -> &str {
+		self.county()
+	}
+
+	fn tract(&self) -> &str {
+		self.tract()
+	}
+
+	fn block_group(&self) -> &str {
+		self.blkgrp()
+	}
+
+	fn block(&self) -> &str {
+		self.block()
+	}
+
+	fn voting_district(&self) -> &str {
+		self.vtd()
+	}
+
+	fn place(&self) -> &str {
+		self.place()
+	}
+}
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+/// A table of the 2000 redistricting file
+///
+/// Unlike 2010's, there's no housing table; PL3 and PL4 repeat PL1 and PL2
+/// for the population 18 years and over.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Table {
+	PL1,
+	PL2,
+	PL3,
+	PL4,
+}
+
+pub use Table::{PL1, PL2, PL3, PL4};
+
+impl core::fmt::Display for Table {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		f.write_str(match self {
+			Table::PL1 => "pl1",
+			Table::PL2 => "pl2",
+			Table::PL3 => "pl3",
+			Table::PL4 => "pl4",
+		})
+	}
+}
+
+impl core::str::FromStr for Table {
+	type Err = crate::Error;
+
+	fn from_str(s: &str) -> crate::Result<Self> {
+		match s.to_lowercase().as_str() {
+			"pl1" => Ok(Table::PL1),
+			"pl2" => Ok(Table::PL2),
+			"pl3" => Ok(Table::PL3),
+			"pl4" => Ok(Table::PL4),
+			_ => Err(crate::Error::InvalidTableName),
+		}
+	}
+}
+
+pub use super::GeographicalHeader;
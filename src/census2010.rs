@@ -1,11 +1,49 @@
+use serde::Serialize;
+
+mod geographical_header;
 pub mod pl94_171;
+pub mod sf1;
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub use geographical_header::GeographicalHeader;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Schema {
 	Pl94_171,
+	Sf1,
+}
+
+impl Schema {
+	pub fn table(&self, name: &str) -> Option<Table> {
+		match self {
+			Schema::Pl94_171 => name.parse().ok().map(Table::Pl94_171),
+			Schema::Sf1 => name.parse().ok().map(Table::Sf1),
+		}
+	}
+
+	/// The summary level of block records
+	///
+	/// The redistricting file puts blocks under voting districts, while SF1
+	/// puts them under county subdivisions and places.
+	pub fn block_summary_level(&self) -> &'static str {
+		match self {
+			Schema::Pl94_171 => "750",
+			Schema::Sf1 => "101",
+		}
+	}
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum Table {
 	Pl94_171(pl94_171::Table),
+	Sf1(sf1::Table),
+}
+
+impl core::fmt::Display for Table {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		match self {
+			Table::Pl94_171(table) => table.fmt(f),
+			Table::Sf1(table) => table.fmt(f),
+		}
+	}
 }
@@ -0,0 +1,4 @@
+/// A table of Summary File 1, e.g. `p12a`
+pub use crate::schema::SegmentedTable as Table;
+
+pub use super::GeographicalHeader;
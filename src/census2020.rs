@@ -1,11 +1,49 @@
+use serde::Serialize;
+
+pub mod dhc;
+mod geographical_header;
 pub mod pl94_171;
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub use geographical_header::GeographicalHeader;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Schema {
 	Pl94_171,
+	Dhc,
+}
+
+impl Schema {
+	pub fn table(&self, name: &str) -> Option<Table> {
+		match self {
+			Schema::Pl94_171 => name.parse().ok().map(Table::Pl94_171),
+			Schema::Dhc => name.parse().ok().map(Table::Dhc),
+		}
+	}
+
+	/// The summary level of block records
+	///
+	/// Unlike in 2010, every 2020 file puts its tabulation blocks under the
+	/// same summary level.
+	pub fn block_summary_level(&self) -> &'static str {
+		match self {
+			Schema::Pl94_171 => "750",
+			Schema::Dhc => "750",
+		}
+	}
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum Table {
 	Pl94_171(pl94_171::Table),
+	Dhc(dhc::Table),
+}
+
+impl core::fmt::Display for Table {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		match self {
+			Table::Pl94_171(table) => table.fmt(f),
+			Table::Dhc(table) => table.fmt(f),
+		}
+	}
 }
@@ -0,0 +1,5 @@
+/// A table of the Demographic and Housing Characteristics File, named as
+/// SF1's are, e.g. `pct12a`
+pub use crate::schema::SegmentedTable as Table;
+
+pub use super::GeographicalHeader;
@@ -0,0 +1,356 @@
+//! Carrying plans and counts from 2010 blocks onto 2020 blocks
+//!
+//! The Census Bureau's tabulation block relationship files, e.g.
+//! `TAB2010_TAB2020_ST18.txt`, have a row for every piece of land or water
+//! a 2010 block shares with a 2020 block, with the areas of both blocks and
+//! of the piece.  Blocks are weighed by land area, or by water area for
+//! blocks without any land.
+
+use std::{collections::BTreeMap, io::Read, path::Path};
+
+use crate::{Error, GeoId, Result};
+
+/// The piece a 2010 block shares with a 2020 block
+#[derive(Clone, Debug, PartialEq)]
+pub struct Relationship {
+	pub from: GeoId,
+	pub to: GeoId,
+	/// The land area of the piece, in square meters
+	pub land: u64,
+	/// The water area of the piece, in square meters
+	pub water: u64,
+}
+
+/// The columns of a relationship file making up each block's GeoID
+const FROM: [&str; 4] = ["STATE_2010", "COUNTY_2010", "TRACT_2010", "BLK_2010"];
+const TO: [&str; 4] = ["STATE_2020", "COUNTY_2020", "TRACT_2020", "BLK_2020"];
+
+/// The relationships between 2010 and 2020 blocks
+pub struct Crosswalk {
+	relationships: Vec<Relationship>,
+	from: BTreeMap<GeoId, Vec<usize>>,
+	to: BTreeMap<GeoId, Vec<usize>>,
+}
+
+/// The shares of a block's area among some of its relationships
+///
+/// Pieces are weighed by land, or by water if none of them has any land, or
+/// equally if they have neither.
+fn shares(relationships: &[&Relationship]) -> Vec<f64> {
+	let land: u64 = relationships
+		.iter()
+		.map(|relationship| relationship.land)
+		.sum();
+	let water: u64 = relationships
+		.iter()
+		.map(|relationship| relationship.water)
+		.sum();
+
+	relationships
+		.iter()
+		.map(|relationship| {
+			if land > 0 {
+				relationship.land as f64 / land as f64
+			} else if water > 0 {
+				relationship.water as f64 / water as f64
+			} else {
+				1.0 / relationships.len() as f64
+			}
+		})
+		.collect()
+}
+
+impl Crosswalk {
+	pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+		Self::from_reader(std::fs::File::open(path)?)
+	}
+
+	/// Read a pipe-delimited relationship file, with its header row
+	pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+		let mut reader = csv::ReaderBuilder::new()
+			.delimiter(b'|')
+			.has_headers(true)
+			.from_reader(reader);
+
+		let columns: csv::StringRecord = reader.headers()?.clone();
+		let column = |name: &str| -> Result<usize> {
+			columns
+				.iter()
+				.position(|column| column.trim().eq_ignore_ascii_case(name))
+				.ok_or_else(|| Error::MissingColumn(name.to_string()))
+		};
+		let from: Vec<usize> = FROM
+			.iter()
+			.map(|name| column(name))
+			.collect::<Result<_>>()?;
+		let to: Vec<usize> = TO.iter().map(|name| column(name)).collect::<Result<_>>()?;
+		let (land, water) = (column("AREALAND_INT")?, column("AREAWATER_INT")?);
+
+		let mut crosswalk = Self {
+			relationships: Vec::new(),
+			from: BTreeMap::new(),
+			to: BTreeMap::new(),
+		};
+
+		for row in reader.records() {
+			let row: csv::StringRecord = row?;
+			let geoid = |columns: &[usize]| -> GeoId {
+				columns
+					.iter()
+					.map(|idx| row.get(*idx).unwrap_or("").trim())
+					.collect()
+			};
+			let area = |idx: usize| -> Result<u64> { Ok(row.get(idx).unwrap_or("").trim().parse()?) };
+
+			let relationship = Relationship {
+				from: geoid(&from),
+				to: geoid(&to),
+				land: area(land)?,
+				water: area(water)?,
+			};
+
+			let idx: usize = crosswalk.relationships.len();
+			crosswalk
+				.from
+				.entry(relationship.from.clone())
+				.or_default()
+				.push(idx);
+			crosswalk
+				.to
+				.entry(relationship.to.clone())
+				.or_default()
+				.push(idx);
+			crosswalk.relationships.push(relationship);
+		}
+
+		Ok(crosswalk)
+	}
+
+	fn relationships(&self, indices: Option<&Vec<usize>>) -> Vec<&Relationship> {
+		indices
+			.into_iter()
+			.flatten()
+			.map(|idx| &self.relationships[*idx])
+			.collect()
+	}
+
+	/// The pieces of a 2010 block, by the 2020 blocks they're in
+	pub fn from(&self, geoid: &str) -> Vec<&Relationship> {
+		self.relationships(self.from.get(geoid))
+	}
+
+	/// The pieces of a 2020 block, by the 2010 blocks they were in
+	pub fn to(&self, geoid: &str) -> Vec<&Relationship> {
+		self.relationships(self.to.get(geoid))
+	}
+
+	/// The 2010 blocks split among several 2020 blocks
+	pub fn split_blocks(&self) -> impl Iterator<Item = &GeoId> {
+		self
+			.from
+			.iter()
+			.filter(|(_, indices)| indices.len() > 1)
+			.map(|(geoid, _)| geoid)
+	}
+
+	/// Carry a plan's assignments of 2010 blocks onto 2020 blocks
+	///
+	/// Each 2020 block gets the share of its area in each district.  Pieces
+	/// of 2010 blocks the plan doesn't assign count towards no district, so
+	/// shares can sum to less than one.
+	pub fn translate<'a, D, I>(&self, assignments: I) -> Translation<D>
+	where
+		D: Copy + Ord + 'a,
+		I: IntoIterator<Item = (&'a GeoId, &'a D)>,
+	{
+		let assignments: BTreeMap<&str, D> = assignments
+			.into_iter()
+			.map(|(geoid, district)| (geoid.as_str(), *district))
+			.collect();
+
+		let shares: BTreeMap<GeoId, BTreeMap<D, f64>> = self
+			.to
+			.iter()
+			.filter_map(|(geoid, indices)| {
+				let pieces: Vec<&Relationship> = self.relationships(Some(indices));
+				let mut districts: BTreeMap<D, f64> = BTreeMap::new();
+
+				for (piece, share) in pieces.iter().zip(shares(&pieces)) {
+					if let Some(district) = assignments.get(piece.from.as_str()) {
+						*districts.entry(*district).or_insert(0.0) += share;
+					}
+				}
+
+				if districts.is_empty() {
+					None
+				} else {
+					Some((geoid.clone(), districts))
+				}
+			})
+			.collect();
+
+		Translation { shares }
+	}
+
+	/// Reallocate counts of 2010 blocks onto 2020 blocks
+	///
+	/// Each 2010 block's count is split among its pieces by their share of its
+	/// area, which assumes it's spread evenly over the block.  Counts of blocks
+	/// the crosswalk doesn't have are left over, rather than lost.
+	pub fn reallocate<'a, I>(&self, counts: I) -> Reallocation
+	where
+		I: IntoIterator<Item = (&'a GeoId, &'a u64)>,
+	{
+		let mut reallocation = Reallocation {
+			counts: BTreeMap::new(),
+			unmatched: BTreeMap::new(),
+		};
+
+		for (geoid, count) in counts {
+			let pieces: Vec<&Relationship> = self.from(geoid);
+			if pieces.is_empty() {
+				reallocation.unmatched.insert(geoid.clone(), *count);
+			}
+
+			for (piece, share) in pieces.iter().zip(shares(&pieces)) {
+				*reallocation.counts.entry(piece.to.clone()).or_insert(0.0) += *count as f64 * share;
+			}
+		}
+
+		reallocation
+	}
+}
+
+/// Counts carried onto 2020 blocks
+#[derive(Clone, Debug)]
+pub struct Reallocation {
+	/// The count of each 2020 block
+	pub counts: BTreeMap<GeoId, f64>,
+	/// The counts of 2010 blocks the crosswalk doesn't have
+	pub unmatched: BTreeMap<GeoId, u64>,
+}
+
+/// A plan carried onto 2020 blocks
+#[derive(Clone, Debug)]
+pub struct Translation<D> {
+	/// The share of each 2020 block's area in each district
+	pub shares: BTreeMap<GeoId, BTreeMap<D, f64>>,
+}
+
+impl<D: Copy + Ord> Translation<D> {
+	/// Assign each 2020 block to the district with the most of its area
+	///
+	/// Ties go to the lowest district.
+	pub fn majority(&self) -> BTreeMap<GeoId, D> {
+		self
+			.shares
+			.iter()
+			.filter_map(|(geoid, districts)| {
+				districts
+					.iter()
+					.fold(
+						None,
+						|best: Option<(D, f64)>, (district, share)| match best {
+							Some((_, most)) if most >= *share => best,
+							_ => Some((*district, *share)),
+						},
+					)
+					.map(|(district, _)| (geoid.clone(), district))
+			})
+			.collect()
+	}
+
+	/// The 2020 blocks that the plan's districts split
+	pub fn splits(&self) -> impl Iterator<Item = &GeoId> {
+		self
+			.shares
+			.iter()
+			.filter(|(_, districts)| districts.len() > 1)
+			.map(|(geoid, _)| geoid)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Crosswalk;
+	use std::collections::BTreeMap;
+
+	/// Block 1001 split between two 2020 blocks, one of which also takes in
+	/// block 1002, and a water block renumbered
+	const RELATIONSHIPS: &str = "\
+STATE_2010|COUNTY_2010|TRACT_2010|BLK_2010|BLKSF_2010|AREALAND_2010|AREAWATER_2010|BLOCK_PART_FLAG_O|STATE_2020|COUNTY_2020|TRACT_2020|BLK_2020|BLKSF_2020|AREALAND_2020|AREAWATER_2020|BLOCK_PART_FLAG_R|AREALAND_INT|AREAWATER_INT
+18|157|005200|1001||100|0|P|18|157|005200|2001||60|0||60|0
+18|157|005200|1001||100|0|P|18|157|005200|2002||90|0|P|40|0
+18|157|005200|1002||50|0||18|157|005200|2002||90|0|P|50|0
+18|157|005200|1003||0|10||18|157|005200|2003||0|10||0|10
+";
+
+	fn geoid(block: &str) -> String {
+		format!("18157005200{}", block)
+	}
+
+	#[test]
+	fn plans_are_carried_onto_2020_blocks() {
+		let crosswalk = Crosswalk::from_reader(RELATIONSHIPS.as_bytes()).unwrap();
+		assert_eq!(crosswalk.from(&geoid("1001")).len(), 2);
+		assert_eq!(crosswalk.to(&geoid("2002")).len(), 2);
+		assert_eq!(
+			crosswalk.split_blocks().collect::<Vec<_>>(),
+			vec![&geoid("1001")]
+		);
+
+		let mut plan: BTreeMap<String, u32> = BTreeMap::new();
+		plan.insert(geoid("1001"), 1);
+		plan.insert(geoid("1002"), 2);
+		plan.insert(geoid("1003"), 2);
+
+		let translation = crosswalk.translate(&plan);
+		assert_eq!(translation.shares[&geoid("2001")][&1], 1.0);
+		assert!((translation.shares[&geoid("2002")][&1] - 40.0 / 90.0).abs() < 1e-9);
+		assert!((translation.shares[&geoid("2002")][&2] - 50.0 / 90.0).abs() < 1e-9);
+		// Without land, the water block is weighed by water.
+		assert_eq!(translation.shares[&geoid("2003")][&2], 1.0);
+
+		let majority = translation.majority();
+		assert_eq!(majority[&geoid("2001")], 1);
+		assert_eq!(majority[&geoid("2002")], 2);
+		assert_eq!(majority[&geoid("2003")], 2);
+
+		assert_eq!(
+			translation.splits().collect::<Vec<_>>(),
+			vec![&geoid("2002")]
+		);
+	}
+
+	#[test]
+	fn counts_are_reallocated_by_area() {
+		let crosswalk = Crosswalk::from_reader(RELATIONSHIPS.as_bytes()).unwrap();
+
+		let mut counts: BTreeMap<String, u64> = BTreeMap::new();
+		counts.insert(geoid("1001"), 10);
+		counts.insert(geoid("1002"), 5);
+		counts.insert(geoid("1003"), 0);
+		counts.insert(geoid("1004"), 7);
+
+		let reallocation = crosswalk.reallocate(&counts);
+		let reallocated = &reallocation.counts;
+		assert!((reallocated[&geoid("2001")] - 6.0).abs() < 1e-9);
+		assert!((reallocated[&geoid("2002")] - 9.0).abs() < 1e-9);
+		assert_eq!(reallocated[&geoid("2003")], 0.0);
+		assert_eq!(reallocated.values().sum::<f64>(), 15.0);
+
+		// A block the crosswalk doesn't have keeps its count.
+		assert_eq!(reallocation.unmatched.len(), 1);
+		assert_eq!(reallocation.unmatched[&geoid("1004")], 7);
+	}
+
+	#[test]
+	fn missing_columns_are_named() {
+		let relationships: String = RELATIONSHIPS.replacen("AREALAND_INT", "AREALAND", 1);
+
+		match Crosswalk::from_reader(relationships.as_bytes()) {
+			Err(crate::Error::MissingColumn(column)) => assert_eq!(column, "AREALAND_INT"),
+			other => panic!("expected a missing column, got {:?}", other.map(|_| ())),
+		}
+	}
+}
@@ -13,6 +13,15 @@ pub trait Dataset<LogicalRecord, LogicalRecordNumber> {
 	fn get_header_for_geoid(&self, geoid: &str) -> Result<Box<dyn GeographicalHeader>>;
 }
 
+mod aggregation;
+pub use aggregation::*;
+
+mod cvap;
+pub use cvap::*;
+
+mod federated;
+pub use federated::*;
+
 mod indexed;
 pub use indexed::*;
 
@@ -0,0 +1,280 @@
+use std::collections::BTreeMap;
+
+use crate::{
+	dataset::{Dataset, IndexedDataset},
+	Error, GeographicalHeader, LogicalRecordNumber, Result, Table, TableLocations,
+};
+
+/// The geographic component of a record for the whole of its area
+const WHOLE: &str = "00";
+
+/// A geography that blocks can be summed up to
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Geography {
+	County,
+	Tract,
+	BlockGroup,
+	VotingDistrict,
+	Place,
+}
+
+/// A code that isn't blank, nor all nines for "not applicable"
+fn applicable(code: &str) -> Option<&str> {
+	let code: &str = code.trim();
+
+	if code.is_empty() || code.bytes().all(|byte| byte == b'9') {
+		None
+	} else {
+		Some(code)
+	}
+}
+
+impl Geography {
+	/// The summary level of the Census's own records for areas of this
+	/// geography
+	pub fn summary_level(&self) -> &'static str {
+		match self {
+			Geography::County => "050",
+			Geography::Tract => "140",
+			Geography::BlockGroup => "150",
+			Geography::Place => "160",
+			Geography::VotingDistrict => "700",
+		}
+	}
+
+	/// The code of the area of this geography a header's area is in
+	///
+	/// Codes are qualified by the state, and by the county where they're only
+	/// unique within one, so they're unique within a dataset.  Areas outside
+	/// of any place or voting district have none.
+	pub fn code(&self, header: &dyn GeographicalHeader) -> Option<String> {
+		let state: &str = header.state().trim();
+		let county: &str = header.county().trim();
+
+		let parts: Vec<&str> = match self {
+			Geography::County => vec![state, county],
+			Geography::Tract => vec![state, county, header.tract().trim()],
+			Geography::BlockGroup => vec![
+				state,
+				county,
+				header.tract().trim(),
+				header.block_group().trim(),
+			],
+			Geography::VotingDistrict => vec![state, county, applicable(header.voting_district())?],
+			Geography::Place => vec![state, applicable(header.place())?],
+		};
+
+		if parts.iter().any(|part| part.is_empty()) {
+			None
+		} else {
+			Some(parts.concat())
+		}
+	}
+}
+
+/// An area whose blocks don't sum to the Census's own record for it
+#[derive(Clone, Debug, PartialEq)]
+pub struct Discrepancy {
+	pub code: String,
+	pub summed: Vec<u64>,
+	pub published: Vec<u64>,
+}
+
+/// The totals of a table for each area of a geography
+#[derive(Clone, Debug)]
+pub struct Aggregate {
+	pub geography: Geography,
+	pub table: Table,
+	/// The sum of the table over each area's blocks, by area code
+	pub totals: BTreeMap<String, Vec<u64>>,
+	/// Areas whose totals differ from the Census's own records
+	///
+	/// Areas the file has no record of its own for aren't checked.
+	pub discrepancies: Vec<Discrepancy>,
+}
+
+impl IndexedDataset {
+	/// The values of a table in a logical record
+	pub(super) fn values(
+		&self,
+		logrecno: LogicalRecordNumber,
+		locations: &TableLocations,
+	) -> Result<Vec<u64>> {
+		let record = self.get_logical_record(logrecno)?;
+
+		record
+			.table(locations)
+			.ok_or(Error::InvalidLogicalRecordNumber)?
+			.iter()
+			.map(|value| value.trim().parse::<u64>().map_err(Into::into))
+			.collect()
+	}
+
+	/// Sum the block records of a table up to the areas of a geography
+	///
+	/// Blocks are assigned to areas by the codes in their geographical headers,
+	/// so only the block records need to be present.  Where the file also has
+	/// the Census's own record for an area, the total is checked against it.
+	pub fn aggregate(&self, table: &Table, geography: Geography) -> Result<Aggregate> {
+		let locations: &TableLocations = self.table_locations(table).ok_or(Error::InvalidTableName)?;
+
+		let mut totals: BTreeMap<String, Vec<u64>> = BTreeMap::new();
+		let mut published: BTreeMap<String, LogicalRecordNumber> = BTreeMap::new();
+		let block: &str = self.schema().block_summary_level();
+
+		self.for_each_header(|header| {
+			if header.geographic_component() != WHOLE {
+				return Ok(());
+			}
+
+			let code: String = match geography.code(header.as_ref()) {
+				Some(code) => code,
+				None => return Ok(()),
+			};
+
+			if header.summary_level() == block {
+				let values: Vec<u64> = self.values(header.logrecno(), locations)?;
+				let total: &mut Vec<u64> = totals.entry(code).or_insert_with(|| vec![0; values.len()]);
+
+				for (total, value) in total.iter_mut().zip(values) {
+					*total += value;
+				}
+			} else if header.summary_level() == geography.summary_level() {
+				published.insert(code, header.logrecno());
+			}
+
+			Ok(())
+		})?;
+
+		let discrepancies: Vec<Discrepancy> = published
+			.into_iter()
+			.map(|(code, logrecno)| -> Result<Option<Discrepancy>> {
+				let published: Vec<u64> = self.values(logrecno, locations)?;
+				let summed: Vec<u64> = totals
+					.get(&code)
+					.cloned()
+					.unwrap_or_else(|| vec![0; published.len()]);
+
+				if summed == published {
+					Ok(None)
+				} else {
+					Ok(Some(Discrepancy {
+						code,
+						summed,
+						published,
+					}))
+				}
+			})
+			.filter_map(Result::transpose)
+			.collect::<Result<_>>()?;
+
+		Ok(Aggregate {
+			geography,
+			table: *table,
+			totals,
+			discrepancies,
+		})
+	}
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+	use super::Geography;
+	use crate::IndexedDataset;
+	use std::path::Path;
+
+	const TRACT: &str = "18157005200";
+
+	fn p1() -> crate::Table {
+		crate::Table::Census2010(crate::census2010::Table::Pl94_171(
+			crate::census2010::pl94_171::P1,
+		))
+	}
+
+	/// A copy of the test dataset with other geographical headers, and the
+	/// directory it was copied to, which is removed when dropped
+	///
+	/// Every logical record of the trimmed test files reads as the same one.
+	pub(in crate::dataset) fn dataset(headers: &[String]) -> (tempfile::TempDir, IndexedDataset) {
+		let temporary = tempfile::tempdir().unwrap();
+		let directory: &Path = temporary.path();
+
+		let data = Path::new("tests/data");
+		for file in &[
+			"in2010.pl.prd.packinglist.txt.trim",
+			"in000012010.pl.trim",
+			"in000022010.pl.trim",
+		] {
+			std::fs::copy(data.join(file), directory.join(file)).unwrap();
+		}
+		std::fs::write(directory.join("ingeo2010.pl.trim"), headers.concat()).unwrap();
+
+		let dataset =
+			IndexedDataset::from_packing_list_file(directory.join("in2010.pl.prd.packinglist.txt.trim"))
+				.and_then(IndexedDataset::index)
+				.expect("failed to load test dataset");
+
+		(temporary, dataset)
+	}
+
+	/// The test block's header, changed at some columns
+	pub(in crate::dataset) fn header(changes: &[(usize, &str)]) -> String {
+		let mut header: String = std::fs::read_to_string("tests/data/ingeo2010.pl.trim").unwrap();
+
+		for (at, change) in changes {
+			header.replace_range(*at..at + change.len(), change);
+		}
+
+		header
+	}
+
+	#[test]
+	fn blocks_are_coded_by_their_headers() {
+		let block = crate::census2010::pl94_171::GeographicalHeader::new(header(&[]));
+
+		assert_eq!(Geography::County.code(&block), Some("18157".to_string()));
+		assert_eq!(Geography::Tract.code(&block), Some(TRACT.to_string()));
+		assert_eq!(
+			Geography::BlockGroup.code(&block),
+			Some("181570052001".to_string())
+		);
+		assert_eq!(
+			Geography::VotingDistrict.code(&block),
+			Some("1815700650".to_string())
+		);
+		assert_eq!(Geography::Place.code(&block), Some("1882862".to_string()));
+
+		let outside = crate::census2010::pl94_171::GeographicalHeader::new(header(&[(45, "     ")]));
+		assert_eq!(Geography::Place.code(&outside), None);
+	}
+
+	#[test]
+	fn blocks_are_summed_and_checked() {
+		let (_directory, dataset) = dataset(&[
+			header(&[]),
+			header(&[(18, "0335181"), (61, "1014")]),
+			// The tract's own record, which is the same as one block's.
+			header(&[(8, "140"), (18, "0335182"), (60, "     ")]),
+		]);
+
+		let block: Vec<u64> = dataset
+			.values(335_180, dataset.table_locations(&p1()).unwrap())
+			.unwrap();
+		assert_eq!(block[0], 53);
+
+		let tracts = dataset.aggregate(&p1(), Geography::Tract).unwrap();
+		assert_eq!(tracts.totals.len(), 1);
+		assert_eq!(tracts.discrepancies.len(), 1);
+		assert_eq!(tracts.discrepancies[0].code, TRACT);
+		assert_eq!(tracts.discrepancies[0].published, block);
+		assert_eq!(
+			tracts.discrepancies[0].summed,
+			block.iter().map(|value| value * 2).collect::<Vec<u64>>()
+		);
+
+		// There's no county record to check against.
+		let counties = dataset.aggregate(&p1(), Geography::County).unwrap();
+		assert_eq!(counties.totals["18157"], tracts.totals[TRACT]);
+		assert!(counties.discrepancies.is_empty());
+	}
+}
@@ -0,0 +1,465 @@
+use std::{collections::BTreeMap, io::Read, path::Path};
+
+use crate::{
+	acs::Estimate,
+	census2000, census2010, census2020,
+	dataset::{Dataset, IndexedDataset},
+	Error, GeoId, GeographicalHeader, LogicalRecord, LogicalRecordNumber, Result, Schema, Table,
+	TableLocations,
+};
+
+/// One line of the CVAP tabulation of an area, e.g. "Hispanic or Latino"
+#[derive(Clone, Debug, PartialEq)]
+pub struct CvapLine {
+	pub number: u32,
+	pub title: String,
+	/// The total population
+	pub total: Estimate,
+	/// The population 18 years and over
+	pub adults: Estimate,
+	/// The citizen population
+	pub citizens: Estimate,
+	/// The citizen population 18 years and over
+	pub cvap: Estimate,
+}
+
+/// The CVAP tabulation of one area
+///
+/// The tabulation has no geographical header of its own, so the record also
+/// serves as one, with the codes taken from its GeoID.
+#[derive(Clone, Debug)]
+pub struct CvapRecord {
+	number: LogicalRecordNumber,
+	geoid: String,
+	name: String,
+	lines: Vec<CvapLine>,
+}
+
+impl CvapRecord {
+	/// The full GeoID, e.g. `1500000US181570052001`
+	pub fn geoid(&self) -> &str {
+		&self.geoid
+	}
+
+	/// The GeoID without its summary level and component
+	pub fn geocode(&self) -> &str {
+		geocode(&self.geoid)
+	}
+
+	pub fn lines(&self) -> &[CvapLine] {
+		&self.lines
+	}
+
+	/// A part of the geocode, if the summary level has it
+	fn code(&self, levels: &[&str], range: core::ops::Range<usize>) -> &str {
+		if levels.contains(&self.summary_level()) {
+			self.geocode().get(range).unwrap_or("")
+		} else {
+			""
+		}
+	}
+}
+
+impl LogicalRecord for CvapRecord {
+	fn number(&self) -> LogicalRecordNumber {
+		self.number
+	}
+}
+
+impl GeographicalHeader for CvapRecord {
+	fn name(&self) -> &str {
+		&self.name
+	}
+
+	fn logrecno(&self) -> LogicalRecordNumber {
+		self.number
+	}
+
+	/// The estimated total population, from the first line
+	fn population(&self) -> Result<u64> {
+		self
+			.lines
+			.first()
+			.and_then(|line| line.total.estimate)
+			.map(|estimate| estimate.round() as u64)
+			.ok_or(Error::MissingPopulation)
+	}
+
+	fn summary_level(&self) -> &str {
+		self.geoid.get(0..3).unwrap_or("")
+	}
+
+	fn geographic_component(&self) -> &str {
+		self.geoid.get(3..5).unwrap_or("")
+	}
+
+	fn state(&self) -> &str {
+		self.geocode().get(0..2).unwrap_or("")
+	}
+
+	fn county(&self) -> &str {
+		self.code(&["050", "140", "150"], 2..5)
+	}
+
+	fn tract(&self) -> &str {
+		self.code(&["140", "150"], 5..11)
+	}
+
+	fn block_group(&self) -> &str {
+		self.code(&["150"], 11..12)
+	}
+
+	fn block(&self) -> &str {
+		""
+	}
+
+	fn voting_district(&self) -> &str {
+		""
+	}
+
+	fn place(&self) -> &str {
+		self.code(&["160"], 2..7)
+	}
+}
+
+/// The part of a GeoID after its summary level and component, if it has them
+fn geocode(geoid: &str) -> &str {
+	geoid.split_once("US").map_or(geoid, |(_, code)| code)
+}
+
+/// The length of a block group's geocode
+const BLOCK_GROUP_LENGTH: usize = 12;
+
+/// The length of a block's geocode
+const BLOCK_LENGTH: usize = 15;
+
+/// A Citizen Voting Age Population special tabulation
+///
+/// The Census Bureau publishes these from the ACS as one CSV per summary
+/// level, e.g. `BlockGr.csv`, with a row per area and line of the
+/// tabulation.  Records are numbered in the order of the file, and can be
+/// looked up by full GeoID or by geocode.
+pub struct CvapDataset {
+	records: Vec<CvapRecord>,
+	geocodes: BTreeMap<GeoId, usize>,
+}
+
+impl CvapDataset {
+	pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+		Self::from_reader(std::fs::File::open(path)?)
+	}
+
+	pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+		let mut reader = csv::ReaderBuilder::new()
+			.has_headers(true)
+			.from_reader(reader);
+
+		// The files are Latin-1, so names outside of ASCII are replaced.
+		let columns: Vec<String> = reader
+			.byte_headers()?
+			.iter()
+			.map(|column| String::from_utf8_lossy(column).trim().to_lowercase())
+			.collect();
+		let column = |name: &str| -> Result<usize> {
+			columns
+				.iter()
+				.position(|column| column == name)
+				.ok_or_else(|| Error::MissingColumn(name.to_string()))
+		};
+		let (geoname, lntitle, geoid, lnnumber) = (
+			column("geoname")?,
+			column("lntitle")?,
+			column("geoid")?,
+			column("lnnumber")?,
+		);
+		let estimates: Vec<(usize, usize)> = ["tot", "adu", "cit", "cvap"]
+			.iter()
+			.map(|prefix| {
+				Ok((
+					column(&format!("{}_est", prefix))?,
+					column(&format!("{}_moe", prefix))?,
+				))
+			})
+			.collect::<Result<_>>()?;
+
+		let mut records: Vec<CvapRecord> = Vec::new();
+		let mut geocodes: BTreeMap<GeoId, usize> = BTreeMap::new();
+
+		for row in reader.byte_records() {
+			let row: csv::ByteRecord = row?;
+			let field = |idx: usize| -> String {
+				row
+					.get(idx)
+					.map(|field| String::from_utf8_lossy(field).trim().to_string())
+					.unwrap_or_default()
+			};
+			let estimate = |(estimate, margin): (usize, usize)| -> Estimate {
+				Estimate::parse(&field(estimate), &field(margin))
+			};
+
+			let line = CvapLine {
+				number: field(lnnumber).parse()?,
+				title: field(lntitle),
+				total: estimate(estimates[0]),
+				adults: estimate(estimates[1]),
+				citizens: estimate(estimates[2]),
+				cvap: estimate(estimates[3]),
+			};
+
+			let geoid: String = field(geoid);
+			let idx: usize = *geocodes
+				.entry(geocode(&geoid).to_string())
+				.or_insert_with(|| {
+					records.push(CvapRecord {
+						number: records.len() as LogicalRecordNumber + 1,
+						geoid: geoid.clone(),
+						name: field(geoname),
+						lines: Vec::new(),
+					});
+					records.len() - 1
+				});
+
+			records[idx].lines.push(line);
+		}
+
+		Ok(Self { records, geocodes })
+	}
+
+	pub fn rows(&self) -> usize {
+		self.records.len()
+	}
+
+	/// Iterate over the records, in the order of the file
+	pub fn records(&self) -> impl Iterator<Item = &CvapRecord> {
+		self.records.iter()
+	}
+
+	fn record(&self, geoid: &str) -> Result<&CvapRecord> {
+		self
+			.geocodes
+			.get(geocode(geoid))
+			.map(|idx| &self.records[*idx])
+			.ok_or(Error::InvalidGeoId)
+	}
+
+	/// Spread the CVAP of each block group over its blocks, in proportion to
+	/// their voting age population
+	///
+	/// The blocks come from a PL 94-171 dataset of the same vintage of
+	/// geography as the tabulation, e.g. 2010 blocks for the 2015-2019
+	/// tabulation.  Each block gets one value per line of its block group.
+	/// Block groups without any voting age population give nothing to their
+	/// blocks, and blocks outside of the tabulation are left out.  The 2020
+	/// datasets index every summary level, so only GeoIDs as long as a
+	/// block's are taken for blocks.
+	pub fn disaggregate(&self, blocks: &IndexedDataset) -> Result<BTreeMap<GeoId, Vec<f64>>> {
+		let table: Table = voting_age_population(blocks.schema()).ok_or(Error::InvalidTableName)?;
+		let locations: &TableLocations = blocks
+			.table_locations(&table)
+			.ok_or(Error::InvalidTableName)?;
+
+		let mut populations: Vec<(&GeoId, u64)> = Vec::new();
+		let mut totals: BTreeMap<&str, u64> = BTreeMap::new();
+
+		for geoid in blocks.geoids().filter(|geoid| geoid.len() == BLOCK_LENGTH) {
+			let block_group: &str = &geoid[..BLOCK_GROUP_LENGTH];
+
+			let logrecno: LogicalRecordNumber = blocks.get_logical_record_number_for_geoid(geoid)?;
+			let population: u64 = blocks
+				.values(logrecno, locations)?
+				.first()
+				.copied()
+				.ok_or(Error::MissingPopulation)?;
+
+			populations.push((geoid, population));
+			*totals.entry(block_group).or_insert(0) += population;
+		}
+
+		let mut shares: BTreeMap<GeoId, Vec<f64>> = BTreeMap::new();
+
+		for (geoid, population) in populations {
+			let block_group: &str = &geoid[..BLOCK_GROUP_LENGTH];
+			let record: &CvapRecord = match self.record(block_group) {
+				Ok(record) => record,
+				Err(_) => continue,
+			};
+
+			let share: f64 = match totals[block_group] {
+				0 => 0.0,
+				total => population as f64 / total as f64,
+			};
+
+			shares.insert(
+				geoid.clone(),
+				record
+					.lines
+					.iter()
+					.map(|line| line.cvap.estimate.unwrap_or(0.0) * share)
+					.collect(),
+			);
+		}
+
+		Ok(shares)
+	}
+}
+
+/// The table whose first cell is the voting age population, for schemas
+/// that have one
+fn voting_age_population(schema: Schema) -> Option<Table> {
+	match schema {
+		Schema::Census2000(census2000::Schema::Pl94_171) => Some(Table::Census2000(
+			census2000::Table::Pl94_171(census2000::pl94_171::PL3),
+		)),
+		Schema::Census2010(census2010::Schema::Pl94_171) => Some(Table::Census2010(
+			census2010::Table::Pl94_171(census2010::pl94_171::P3),
+		)),
+		Schema::Census2020(census2020::Schema::Pl94_171) => Some(Table::Census2020(
+			census2020::Table::Pl94_171(census2020::pl94_171::P3),
+		)),
+		_ => None,
+	}
+}
+
+impl Dataset<CvapRecord, LogicalRecordNumber> for CvapDataset {
+	fn get_logical_record(&self, number: LogicalRecordNumber) -> Result<CvapRecord> {
+		number
+			.checked_sub(1)
+			.and_then(|idx| self.records.get(idx as usize))
+			.cloned()
+			.ok_or(Error::InvalidLogicalRecordNumber)
+	}
+
+	fn get_logical_record_number_for_geoid(&self, geoid: &str) -> Result<LogicalRecordNumber> {
+		Ok(self.record(geoid)?.number)
+	}
+
+	fn get_header_for_geoid(&self, geoid: &str) -> Result<Box<dyn GeographicalHeader>> {
+		Ok(Box::new(self.record(geoid)?.clone()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::CvapDataset;
+	use crate::{
+		dataset::aggregation::tests::{dataset, header},
+		Dataset,
+	};
+
+	const BLOCK_GROUPS: &str = "\
+geoname,lntitle,geoid,lnnumber,tot_est,tot_moe,adu_est,adu_moe,cit_est,cit_moe,cvap_est,cvap_moe
+\"Block Group 1, Census Tract 52, Shelby County, Indiana\",Total,1500000US181570052001,1,1020,150,800,120,1000,150,790,118
+\"Block Group 1, Census Tract 52, Shelby County, Indiana\",Hispanic or Latino,1500000US181570052001,13,40,35,25,20,30,30,20,18
+\"Block Group 2, Census Tract 52, Shelby County, Indiana\",Total,1500000US181570052002,1,0,12,0,12,0,12,0,12
+";
+
+	#[test]
+	fn records_are_keyed_by_geoid() {
+		let cvap = CvapDataset::from_reader(BLOCK_GROUPS.as_bytes()).unwrap();
+		assert_eq!(cvap.rows(), 2);
+
+		let logrecno = cvap
+			.get_logical_record_number_for_geoid("181570052001")
+			.unwrap();
+		assert_eq!(
+			cvap
+				.get_logical_record_number_for_geoid("1500000US181570052001")
+				.unwrap(),
+			logrecno
+		);
+
+		let record = cvap.get_logical_record(logrecno).unwrap();
+		assert_eq!(record.lines().len(), 2);
+		assert_eq!(record.lines()[1].title, "Hispanic or Latino");
+		assert_eq!(record.lines()[1].cvap.estimate, Some(20.0));
+		assert_eq!(record.lines()[1].cvap.margin, Some(18.0));
+
+		let header = cvap.get_header_for_geoid("181570052002").unwrap();
+		assert_eq!(header.summary_level(), "150");
+		assert_eq!(header.tract(), "005200");
+		assert_eq!(header.block_group(), "2");
+		assert_eq!(header.place(), "");
+		assert_eq!(header.population().unwrap(), 0);
+
+		assert!(cvap.get_logical_record(3).is_err());
+		assert!(cvap.get_header_for_geoid("181570052003").is_err());
+	}
+
+	#[test]
+	fn block_groups_are_spread_over_blocks_by_voting_age_population() {
+		let cvap = CvapDataset::from_reader(BLOCK_GROUPS.as_bytes()).unwrap();
+
+		// Every block of the test dataset has the same voting age population.
+		let (_directory, blocks) = dataset(&[
+			header(&[]),
+			header(&[(18, "0335181"), (61, "1014")]),
+			header(&[(18, "0335182"), (54, "005300"), (61, "1015")]),
+		]);
+
+		let shares = cvap.disaggregate(&blocks).unwrap();
+		assert_eq!(shares.len(), 2);
+		assert_eq!(shares["181570052001013"], vec![395.0, 10.0]);
+		assert_eq!(shares["181570052001014"], vec![395.0, 10.0]);
+	}
+
+	/// A packing list for the 2020-style test files
+	const PL2020: &str = "\
+       STUSAB: RI
+p1|1:71|
+p2|1:73|
+p3|2:71|
+p4|2:73|
+rigeo2020.pl|2021-08-12 00:00:00|7052|19346|
+ri000012020.pl|2021-08-12 00:00:00|434|19346|
+ri000022020.pl|2021-08-12 00:00:00|379|19346|
+";
+
+	#[test]
+	fn only_blocks_are_given_shares() {
+		use crate::IndexedDataset;
+		use std::path::Path;
+
+		let cvap = CvapDataset::from_reader(
+			"\
+geoname,lntitle,geoid,lnnumber,tot_est,tot_moe,adu_est,adu_moe,cit_est,cit_moe,cvap_est,cvap_moe
+\"Block Group 1, Census Tract 184, Providence County, Rhode Island\",Total,1500000US440070184001,1,30,10,24,8,30,10,20,8
+"
+			.as_bytes(),
+		)
+		.unwrap();
+
+		// The 2020 files index every summary level, so the test block is
+		// joined by its block group, with the same voting age population.
+		let temporary = tempfile::tempdir().unwrap();
+		let directory = temporary.path();
+		std::fs::write(directory.join("ri2020.pl.packinglist.txt"), PL2020).unwrap();
+
+		let data = Path::new("tests/data");
+		let block: String = std::fs::read_to_string(data.join("rigeo2018_2020Style.pl.trim")).unwrap();
+		let block_group: String = block
+			.replacen("PLST|RI|750|", "PLST|RI|150|", 1)
+			.replacen("7500000US440070184001012", "1500000US440070184001", 1)
+			.replacen("|440070184001012|", "|440070184001|", 1);
+		std::fs::write(
+			directory.join("rigeo2020.pl"),
+			[block, block_group].concat(),
+		)
+		.unwrap();
+		for (from, to) in &[
+			("ri000012018_2020Style.pl.trim", "ri000012020.pl"),
+			("ri000022018_2020Style.pl.trim", "ri000022020.pl"),
+		] {
+			std::fs::copy(data.join(from), directory.join(to)).unwrap();
+		}
+
+		let blocks =
+			IndexedDataset::from_packing_list_file(directory.join("ri2020.pl.packinglist.txt"))
+				.and_then(IndexedDataset::index)
+				.unwrap();
+		assert_eq!(blocks.geoids().count(), 2);
+
+		let shares = cvap.disaggregate(&blocks).unwrap();
+		assert_eq!(shares.len(), 1);
+		assert_eq!(shares["440070184001012"], vec![20.0]);
+	}
+}
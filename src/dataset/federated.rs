@@ -0,0 +1,294 @@
+use std::collections::BTreeMap;
+
+use crate::{
+	dataset::{Dataset, IndexedDataset},
+	Error, FileBackedLogicalRecord, GeoId, GeographicalHeader, LogicalRecordNumber, Result, Schema,
+	Table,
+};
+
+/// The state FIPS code a GeoID starts with
+pub fn state_fips(geoid: &str) -> Option<&str> {
+	geoid
+		.get(..2)
+		.filter(|state| state.bytes().all(|byte| byte.is_ascii_digit()))
+}
+
+/// A logical record number, and the state whose dataset it is in
+///
+/// Logical record numbers start over in each state, so they are only unique
+/// within one.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct FederatedRecordNumber {
+	pub state: String,
+	pub logrecno: LogicalRecordNumber,
+}
+
+/// Several indexed datasets, one per state, treated as one
+///
+/// Lookups by GeoID are routed to the dataset of the state the GeoID starts
+/// with.  Every member has the same schema.
+pub struct FederatedDataset {
+	schema: Schema,
+	members: BTreeMap<String, IndexedDataset>,
+}
+
+impl FederatedDataset {
+	/// Federate some indexed datasets
+	///
+	/// Each dataset must cover exactly one state, and no two the same one.
+	pub fn new<I: IntoIterator<Item = IndexedDataset>>(datasets: I) -> Result<Self> {
+		let mut schema: Option<Schema> = None;
+		let mut members: BTreeMap<String, IndexedDataset> = BTreeMap::new();
+
+		for dataset in datasets {
+			match schema {
+				Some(schema) if schema != dataset.schema() => {
+					return Err(Error::InvalidFederation(format!(
+						"cannot federate {:?} with {:?}",
+						dataset.schema(),
+						schema
+					)))
+				}
+				_ => schema = Some(dataset.schema()),
+			}
+
+			let state: String = dataset
+				.geoids()
+				.next()
+				.and_then(|geoid| state_fips(geoid))
+				.ok_or_else(|| Error::InvalidFederation("a dataset has no indexed GeoIDs".to_string()))?
+				.to_string();
+
+			if dataset
+				.geoids()
+				.any(|geoid| state_fips(geoid) != Some(state.as_str()))
+			{
+				return Err(Error::InvalidFederation(format!(
+					"the dataset for state {} covers other states too",
+					state
+				)));
+			}
+
+			if members.contains_key(&state) {
+				return Err(Error::InvalidFederation(format!(
+					"more than one dataset covers state {}",
+					state
+				)));
+			}
+
+			members.insert(state, dataset);
+		}
+
+		let schema: Schema =
+			schema.ok_or_else(|| Error::InvalidFederation("there are no datasets".to_string()))?;
+
+		Ok(Self { schema, members })
+	}
+
+	pub fn schema(&self) -> Schema {
+		self.schema
+	}
+
+	/// The total number of logical records across every state
+	pub fn rows(&self) -> usize {
+		self.members.values().map(IndexedDataset::rows).sum()
+	}
+
+	/// Iterate over the state FIPS codes covered, in order
+	pub fn states(&self) -> impl Iterator<Item = &str> {
+		self.members.keys().map(String::as_str)
+	}
+
+	/// The dataset of one state
+	pub fn member(&self, state: &str) -> Option<&IndexedDataset> {
+		self.members.get(state)
+	}
+
+	/// Iterate over the tables every state's dataset contains
+	pub fn tables(&self) -> impl Iterator<Item = &Table> + '_ {
+		let mut members = self.members.values();
+		let first: Option<&IndexedDataset> = members.next();
+		let rest: Vec<&IndexedDataset> = members.collect();
+
+		first
+			.into_iter()
+			.flat_map(IndexedDataset::tables)
+			.filter(move |table| {
+				rest
+					.iter()
+					.all(|member| member.table_locations(table).is_some())
+			})
+	}
+
+	/// Iterate over the GeoIDs of every state, in order
+	pub fn geoids(&self) -> impl Iterator<Item = &GeoId> {
+		self.members.values().flat_map(IndexedDataset::geoids)
+	}
+
+	/// Roughly how many bytes the indices of every state take up
+	pub fn index_size(&self) -> usize {
+		self.members.values().map(IndexedDataset::index_size).sum()
+	}
+
+	/// The dataset a GeoID's lookups are routed to
+	fn route<'a>(&self, geoid: &'a str) -> Result<(&'a str, &IndexedDataset)> {
+		let state: &str = state_fips(geoid).ok_or(Error::InvalidGeoId)?;
+		let member: &IndexedDataset = self.members.get(state).ok_or(Error::InvalidGeoId)?;
+
+		Ok((state, member))
+	}
+
+	/// Sum the values of a table over some GeoIDs, in whichever states they are
+	///
+	/// Yields nothing if there are no GeoIDs.
+	pub fn sum_table<'a, I>(&self, table: &Table, geoids: I) -> Result<Vec<u64>>
+	where
+		I: IntoIterator<Item = &'a str>,
+	{
+		let mut totals: Vec<u64> = Vec::new();
+
+		for geoid in geoids {
+			let (_, member) = self.route(geoid)?;
+			let locations = member
+				.table_locations(table)
+				.ok_or(Error::InvalidTableName)?;
+			let record = member.get_logical_record(member.get_logical_record_number_for_geoid(geoid)?)?;
+			let fields: Vec<&str> = record
+				.table(locations)
+				.ok_or(Error::InvalidLogicalRecordNumber)?;
+
+			if totals.is_empty() {
+				totals = vec![0; fields.len()];
+			}
+
+			for (total, field) in totals.iter_mut().zip(fields) {
+				*total += field.parse::<u64>()?;
+			}
+		}
+
+		Ok(totals)
+	}
+
+	/// The total population of some GeoIDs, in whichever states they are
+	pub fn population<'a, I>(&self, geoids: I) -> Result<u64>
+	where
+		I: IntoIterator<Item = &'a str>,
+	{
+		geoids
+			.into_iter()
+			.map(|geoid| self.get_header_for_geoid(geoid)?.population())
+			.sum()
+	}
+}
+
+impl Dataset<FileBackedLogicalRecord, FederatedRecordNumber> for FederatedDataset {
+	fn get_logical_record(&self, number: FederatedRecordNumber) -> Result<FileBackedLogicalRecord> {
+		self
+			.members
+			.get(&number.state)
+			.ok_or(Error::InvalidLogicalRecordNumber)?
+			.get_logical_record(number.logrecno)
+	}
+
+	fn get_logical_record_number_for_geoid(&self, geoid: &str) -> Result<FederatedRecordNumber> {
+		let (state, member) = self.route(geoid)?;
+
+		Ok(FederatedRecordNumber {
+			state: state.to_string(),
+			logrecno: member.get_logical_record_number_for_geoid(geoid)?,
+		})
+	}
+
+	fn get_header_for_geoid(&self, geoid: &str) -> Result<Box<dyn GeographicalHeader>> {
+		self.route(geoid)?.1.get_header_for_geoid(geoid)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{FederatedDataset, FederatedRecordNumber};
+	use crate::{Dataset, Error, IndexedDataset, LogicalRecord};
+
+	const BLOCK: &str = "181570052001013";
+
+	fn indiana() -> IndexedDataset {
+		IndexedDataset::from_packing_list_file("tests/data/in2010.pl.prd.packinglist.txt.trim")
+			.and_then(IndexedDataset::index)
+			.expect("failed to load test dataset")
+	}
+
+	fn p1() -> crate::Table {
+		crate::Table::Census2010(crate::census2010::Table::Pl94_171(
+			crate::census2010::pl94_171::P1,
+		))
+	}
+
+	#[test]
+	fn lookups_are_routed_by_state() {
+		let federation = FederatedDataset::new(vec![indiana()]).unwrap();
+		assert_eq!(federation.states().collect::<Vec<&str>>(), vec!["18"]);
+
+		let number = federation
+			.get_logical_record_number_for_geoid(BLOCK)
+			.unwrap();
+		assert_eq!(
+			number,
+			FederatedRecordNumber {
+				state: "18".to_string(),
+				logrecno: 335_180,
+			}
+		);
+		assert_eq!(
+			federation.get_logical_record(number).unwrap().number(),
+			335_180
+		);
+		assert_eq!(
+			federation
+				.get_header_for_geoid(BLOCK)
+				.unwrap()
+				.population()
+				.unwrap(),
+			53
+		);
+
+		assert!(matches!(
+			federation.get_logical_record_number_for_geoid("440070184001012"),
+			Err(Error::InvalidGeoId)
+		));
+		assert!(matches!(
+			federation.get_header_for_geoid("x"),
+			Err(Error::InvalidGeoId)
+		));
+	}
+
+	#[test]
+	fn states_are_federated_once() {
+		assert!(matches!(
+			FederatedDataset::new(vec![indiana(), indiana()]),
+			Err(Error::InvalidFederation(_))
+		));
+		assert!(matches!(
+			FederatedDataset::new(Vec::new()),
+			Err(Error::InvalidFederation(_))
+		));
+	}
+
+	#[test]
+	fn tables_are_summed_across_the_union() {
+		let federation = FederatedDataset::new(vec![indiana()]).unwrap();
+		assert!(federation.tables().any(|table| *table == p1()));
+
+		let once: Vec<u64> = federation.sum_table(&p1(), vec![BLOCK]).unwrap();
+		assert_eq!(once.len(), 71);
+		assert_eq!(once[0], 53);
+
+		let twice: Vec<u64> = federation.sum_table(&p1(), vec![BLOCK, BLOCK]).unwrap();
+		assert_eq!(
+			twice,
+			once.iter().map(|value| value * 2).collect::<Vec<u64>>()
+		);
+
+		assert_eq!(federation.population(vec![BLOCK, BLOCK]).unwrap(), 106);
+		assert!(federation.sum_table(&p1(), Vec::new()).unwrap().is_empty());
+	}
+}
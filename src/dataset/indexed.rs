@@ -6,7 +6,7 @@ use std::{
 };
 
 use crate::{
-	census2010, census2020,
+	census2000, census2010, census2020,
 	dataset::{packing_list::PackingList, Dataset},
 	FileBackedLogicalRecord, GeoId, GeographicalHeader, LogicalRecordNumber,
 	LogicalRecordPositionIndex, Result, Schema, Table, TableLocations,
@@ -34,6 +34,10 @@ pub(crate) type TabularIndex = FnvHashMap<u32, LogicalRecordPositionIndex>;
 impl Dataset<FileBackedLogicalRecord, LogicalRecordNumber> for IndexedDataset {
 	/// Retrieve the logical record by number and by table
 	fn get_logical_record(&self, number: LogicalRecordNumber) -> Result<FileBackedLogicalRecord> {
+		if number == 0 || number > self.rows as LogicalRecordNumber {
+			return Err(crate::Error::InvalidLogicalRecordNumber);
+		}
+
 		match &self.tabular_index {
 			Some(index) => {
 				let records_from_file: FnvHashMap<u32, csv::StringRecord> = self
@@ -51,7 +55,7 @@ impl Dataset<FileBackedLogicalRecord, LogicalRecordNumber> for IndexedDataset {
 						let mut reader = csv::ReaderBuilder::new()
 							.has_headers(false)
 							.delimiter(match self.schema {
-								Schema::Census2010(_) => b',',
+								Schema::Census2000(_) | Schema::Census2010(_) => b',',
 								Schema::Census2020(_) => b'|',
 							})
 							.from_reader(reader);
@@ -88,7 +92,8 @@ impl Dataset<FileBackedLogicalRecord, LogicalRecordNumber> for IndexedDataset {
 
 	fn get_header_for_geoid(&self, geoid: &str) -> Result<Box<dyn GeographicalHeader>> {
 		if let Some(index) = &self.header_index {
-			let result: &(LogicalRecordNumber, u64) = index.get(geoid).unwrap();
+			let result: &(LogicalRecordNumber, u64) =
+				index.get(geoid).ok_or(crate::Error::InvalidGeoId)?;
 
 			let line_offset = result.1;
 
@@ -101,15 +106,7 @@ impl Dataset<FileBackedLogicalRecord, LogicalRecordNumber> for IndexedDataset {
 			let mut line = String::new();
 			reader.read_line(&mut line)?;
 
-			match self.schema {
-				Schema::Census2010(census2010::Schema::Pl94_171) => Ok(Box::new(
-					crate::census2010::pl94_171::GeographicalHeader::new(line),
-				)),
-
-				Schema::Census2020(census2020::Schema::Pl94_171) => Ok(Box::new(
-					crate::census2020::pl94_171::GeographicalHeader::new(&line),
-				)),
-			}
+			Ok(self.header(line))
 		} else {
 			unimplemented!()
 		}
@@ -117,6 +114,79 @@ impl Dataset<FileBackedLogicalRecord, LogicalRecordNumber> for IndexedDataset {
 }
 
 impl IndexedDataset {
+	pub fn schema(&self) -> Schema {
+		self.schema
+	}
+
+	pub fn rows(&self) -> usize {
+		self.rows
+	}
+
+	/// Iterate over the tables this dataset contains
+	pub fn tables(&self) -> impl Iterator<Item = &Table> {
+		self.table_locations.keys()
+	}
+
+	pub fn table_locations(&self, table: &Table) -> Option<&TableLocations> {
+		self.table_locations.get(table)
+	}
+
+	/// Roughly how many bytes the indices take up
+	///
+	/// This is zero if the dataset has not been indexed.
+	pub fn index_size(&self) -> usize {
+		let headers: usize = self
+			.geoids()
+			.map(|geoid| geoid.len() + core::mem::size_of::<(GeoId, (LogicalRecordNumber, u64))>())
+			.sum();
+		let tabular: usize = self
+			.tabular_index
+			.iter()
+			.flat_map(|index| index.values())
+			.map(LogicalRecordPositionIndex::size)
+			.sum();
+
+		headers + tabular
+	}
+
+	/// Interpret a line of the geographical header file
+	fn header(&self, mut line: String) -> Box<dyn GeographicalHeader> {
+		line.truncate(line.trim_end_matches(&['\r', '\n'][..]).len());
+
+		match self.schema {
+			Schema::Census2000(_) => Box::new(census2000::GeographicalHeader::new(line)),
+			Schema::Census2010(_) => Box::new(census2010::GeographicalHeader::new(line)),
+
+			Schema::Census2020(_) => Box::new(census2020::GeographicalHeader::new(&line)),
+		}
+	}
+
+	/// Call `f` with every geographical header, in the order of the file
+	///
+	/// Unlike the header index, this includes the records of every summary
+	/// level, not just blocks.  Stops at the first error `f` returns.
+	pub fn for_each_header<F>(&self, mut f: F) -> Result<()>
+	where
+		F: FnMut(Box<dyn GeographicalHeader>) -> Result<()>,
+	{
+		let mut reader = BufReader::new(&self.geographical_header);
+		reader.seek(std::io::SeekFrom::Start(0))?;
+
+		let mut line = String::new();
+		while reader.read_line(&mut line)? > 0 {
+			f(self.header(core::mem::take(&mut line)))?;
+		}
+
+		Ok(())
+	}
+
+	/// Iterate over the GeoIDs known to the header index, in order
+	///
+	/// Yields nothing if the dataset has not been indexed.
+	pub fn geoids(&self) -> impl Iterator<Item = &GeoId> {
+		self.header_index.iter().flat_map(BTreeMap::keys)
+	}
+
 	pub fn from_packing_list(packing_list: PackingList) -> Result<Self> {
 		let schema = packing_list.schema();
 		let geographical_header: File = File::open(
@@ -220,6 +290,31 @@ impl IndexedDataset {
 
 			if bytes_read > 0 {
 				if let Some((logrecno, geoid, pos)) = match self.schema {
+					// Files like SF1 repeat blocks under several hierarchies, so only
+					// those of the schema's block summary level are indexed.
+					Schema::Census2000(_) | Schema::Census2010(_)
+						if &buf[8..11] != self.schema.block_summary_level() =>
+					{
+						None
+					}
+					Schema::Census2000(_) => {
+						let (logrecno, state_fips, county, tract, block) = (
+							&buf[18..25],
+							&buf[29..31],
+							&buf[31..34],
+							&buf[55..61],
+							&buf[62..66],
+						);
+
+						match block {
+							"    " => None,
+							b => Some((
+								logrecno.parse()?,
+								[state_fips, county, tract, b].concat(),
+								pos,
+							)),
+						}
+					}
 					Schema::Census2010(_) => {
 						let (logrecno, state_fips, county, tract, block) = (
 							&buf[18..25],
@@ -23,14 +23,18 @@ lazy_static::lazy_static! {
 		Regex::new(r"(?m)^(?P<filename>(?P<stusab>[a-z]{2})(?P<ident>\w+)(?P<year>\d{4})\.(?P<ds>.+))\|(?P<date>.+)\|(?P<size>\d+)\|(?P<lines>\d+)\|$")
 			.expect("regex parse failed");
 
+	pub(super) static ref CENSUS2000_HEADER_RE: Regex =
+		Regex::new(r"^(?P<stusab>[a-z]{2})geo\.upl$")
+			.expect("regex parse failed");
+
 	pub(super) static ref STUSAB_RE: Regex =
 		Regex::new(r"(?m)STUSAB: (?P<stusab>[A-Z]{2})$")
 			.expect("regex parse failed");
 }
 
 use crate::{
-	census2010, census2020, Result, Schema, Table, TableLocations, TableSegmentLocation,
-	TableSegmentSpecifier,
+	census2000, census2010, census2020, Error, Result, Schema, Table, TableLocations,
+	TableSegmentLocation, TableSegmentSpecifier,
 };
 
 pub struct PackingList {
@@ -42,6 +46,15 @@ pub struct PackingList {
 	rows: usize,
 }
 
+/// Where the tables of the 2000 redistricting files are: the file and the
+/// number of columns of each
+const CENSUS2000_LAYOUT: [(census2000::pl94_171::Table, u32, usize); 4] = [
+	(census2000::pl94_171::PL1, 1, 71),
+	(census2000::pl94_171::PL2, 1, 73),
+	(census2000::pl94_171::PL3, 2, 71),
+	(census2000::pl94_171::PL4, 2, 73),
+];
+
 fn read_file_to_string<P: AsRef<Path>>(path: P) -> Result<String> {
 	use std::io::Read;
 
@@ -53,7 +66,20 @@ fn read_file_to_string<P: AsRef<Path>>(path: P) -> Result<String> {
 }
 
 impl PackingList {
+	/// Read the packing list at `file_path`
+	///
+	/// The 2000 redistricting files have no packing list, so their
+	/// geographical header file, e.g. `ingeo.upl`, stands in for one.
 	pub fn from_file<P: AsRef<Path>>(file_path: P) -> Result<Self> {
+		if let Some(captures) = file_path
+			.as_ref()
+			.file_name()
+			.and_then(std::ffi::OsStr::to_str)
+			.and_then(|name| CENSUS2000_HEADER_RE.captures(name))
+		{
+			return Self::census2000(file_path.as_ref(), &captures["stusab"]);
+		}
+
 		use core::str::FromStr;
 
 		// It's generally quite a bit faster to just load the entire packing list
@@ -67,6 +93,46 @@ impl PackingList {
 		Ok(parsed)
 	}
 
+	/// The 2000 redistricting files of the state `stusab`, found from their
+	/// geographical header file
+	///
+	/// The tabular files sit next to it, e.g. `in00001.upl` and `in00002.upl`,
+	/// and always lay out their tables the same way.
+	fn census2000(geographical_header_file: &Path, stusab: &str) -> Result<Self> {
+		use std::io::BufRead;
+
+		let rows: usize = std::io::BufReader::new(File::open(geographical_header_file)?)
+			.lines()
+			.count();
+
+		let mut current_columns: FnvHashMap<u32, usize> = FnvHashMap::default();
+		let table_locations: FnvHashMap<Table, TableLocations> = CENSUS2000_LAYOUT
+			.iter()
+			.map(|(table, file, columns)| {
+				let start: &mut usize = current_columns.entry(*file).or_insert(5_usize);
+				let range = *start..*start + columns;
+				*start = range.end;
+
+				(
+					Table::Census2000(census2000::Table::Pl94_171(*table)),
+					vec![TableSegmentLocation { file: *file, range }],
+				)
+			})
+			.collect();
+
+		Ok(Self {
+			schema: Schema::Census2000(census2000::Schema::Pl94_171),
+			directory: geographical_header_file.parent().map(ToOwned::to_owned),
+			table_locations,
+			tabular_files: current_columns
+				.keys()
+				.map(|file| (*file, format!("{}{:05}.upl", stusab, file).into()))
+				.collect(),
+			geographical_header_file: format!("{}geo.upl", stusab).into(),
+			rows,
+		})
+	}
+
 	pub fn new(
 		schema: Schema,
 		directory: Option<PathBuf>,
@@ -115,29 +181,35 @@ impl PackingList {
 	}
 }
 
-fn get_stusab(s: &str) -> String {
-	let stusab_all_caps: &str = &STUSAB_RE.captures(s).expect("failed to find STUSAB")["stusab"];
-	let stusab: String = stusab_all_caps.to_lowercase();
-	stusab
+fn get_stusab(s: &str) -> Result<String> {
+	let captures = STUSAB_RE
+		.captures(s)
+		.ok_or_else(|| Error::InvalidPackingList("missing STUSAB".to_string()))?;
+	Ok(captures["stusab"].to_lowercase())
 }
 
-fn extract_schemas(re: &Regex, s: &str) -> Vec<Schema> {
+fn extract_schemas(re: &Regex, s: &str) -> Result<Vec<Schema>> {
 	re.captures_iter(s)
 		.map(|captures| {
-			log::trace!(
-				"Processing filename regex match: {}",
-				captures.get(0).unwrap().as_str()
-			);
+			let filename: &str = captures.get(0).unwrap().as_str();
+
+			log::trace!("Processing filename regex match: {}", filename);
 
-			let captures: (Option<&str>, Option<&str>, Option<&str>) = (
+			let parts: (Option<&str>, Option<&str>, Option<&str>) = (
 				captures.name("inner").as_ref().map(regex::Match::as_str),
 				captures.name("year").as_ref().map(regex::Match::as_str),
 				captures.name("ext").as_ref().map(regex::Match::as_str),
 			);
 
-			match captures {
-				(Some(_), Some("2010"), Some("pl")) => Schema::Census2010(census2010::Schema::Pl94_171),
-				_ => unimplemented!(),
+			match parts {
+				(Some(_), Some("2010"), Some("pl")) => Ok(Schema::Census2010(census2010::Schema::Pl94_171)),
+				(Some(_), Some("2010"), Some("sf1")) => Ok(Schema::Census2010(census2010::Schema::Sf1)),
+				(Some(_), Some("2020"), Some("pl")) => Ok(Schema::Census2020(census2020::Schema::Pl94_171)),
+				(Some(_), Some("2020"), Some("dhc")) => Ok(Schema::Census2020(census2020::Schema::Dhc)),
+				_ => Err(Error::InvalidPackingList(format!(
+					"unknown schema of {}",
+					filename
+				))),
 			}
 		})
 		.collect()
@@ -149,11 +221,6 @@ enum FileType {
 	GeographicalHeader,
 }
 
-struct DataSegmentation {
-	table_name: String,
-	table_locations: Vec<TableLocations>,
-}
-
 #[derive(Debug)]
 struct FileInformation {
 	filename: PathBuf,
@@ -163,7 +230,7 @@ struct FileInformation {
 	ty: FileType,
 }
 
-fn extract_file_information(s: &str) -> Vec<FileInformation> {
+fn extract_file_information(s: &str) -> Result<Vec<FileInformation>> {
 	FILE_INFORMATION_RE_ML
 		.captures_iter(s)
 		.map(|captures| {
@@ -192,43 +259,39 @@ fn extract_file_information(s: &str) -> Vec<FileInformation> {
 			);
 			let filename = filename.to_string().into();
 			let date = date.to_string();
-			let file_size: usize = size.parse().expect("couldn't parse size as usize");
-			let rows: usize = rows.parse().expect("couldn't parse rows as usize");
+			let file_size: usize = size.parse()?;
+			let rows: usize = rows.parse()?;
 			let ty: FileType = match captures
 				.name("ident")
 				.expect("missing capture group ident")
 				.as_str()
 			{
 				"geo" => FileType::GeographicalHeader,
-				n => {
-					if let Ok(idx) = n.parse::<u32>() {
-						FileType::Tabular(idx)
-					} else {
-						unimplemented!()
-					}
-				}
+				n => FileType::Tabular(n.parse::<u32>().map_err(|_| {
+					Error::InvalidPackingList(format!("unknown file type of {:?}", filename))
+				})?),
 			};
 
 			log::trace!("Inferred filetype {:?} for {:?}", ty, filename);
 
-			FileInformation {
+			Ok(FileInformation {
 				filename,
 				date,
 				file_size,
 				rows,
 				ty,
-			}
+			})
 		})
 		.collect()
 }
 
 fn partition_file_information(
 	file_informations: &[FileInformation],
-) -> (FnvHashMap<u32, &FileInformation>, &FileInformation) {
+) -> Result<(FnvHashMap<u32, &FileInformation>, &FileInformation)> {
 	let header: &FileInformation = file_informations
 		.iter()
 		.find(|fi| fi.ty == FileType::GeographicalHeader)
-		.expect("missing geographical header");
+		.ok_or_else(|| Error::InvalidPackingList("missing geographical header".to_string()))?;
 	let tabular_files: FnvHashMap<u32, &FileInformation> = file_informations
 		.iter()
 		.filter_map(|fi| match fi.ty {
@@ -237,12 +300,22 @@ fn partition_file_information(
 		})
 		.collect();
 
-	(tabular_files, header)
+	Ok((tabular_files, header))
 }
 
 fn convert_file_information(
 	partition: &(FnvHashMap<u32, &FileInformation>, &FileInformation),
 ) -> (FnvHashMap<u32, PathBuf>, PathBuf, usize) {
+	for fi in partition.0.values().chain(core::iter::once(&partition.1)) {
+		log::trace!(
+			"{:?} has {} rows in {} bytes, as of {}",
+			fi.filename,
+			fi.rows,
+			fi.file_size,
+			fi.date
+		);
+	}
+
 	(
 		partition
 			.0
@@ -253,8 +326,8 @@ fn convert_file_information(
 		{
 			let mut vec: Vec<usize> = partition
 				.0
-				.iter()
-				.map(|(_idx, fi)| fi.rows)
+				.values()
+				.map(|fi| fi.rows)
 				.collect::<Vec<usize>>();
 			vec.push(partition.1.rows);
 			vec.dedup();
@@ -264,12 +337,12 @@ fn convert_file_information(
 	)
 }
 
-fn extract_table_locations(schema: Schema, s: &str) -> FnvHashMap<Table, TableLocations> {
+fn extract_table_locations(schema: Schema, s: &str) -> Result<FnvHashMap<Table, TableLocations>> {
 	let mut current_columns: FnvHashMap<u32, usize> = FnvHashMap::default();
 
 	TABLE_INFORMATION_RE_ML
 		.captures_iter(s)
-		.map(|captures| -> (Table, TableLocations) {
+		.map(|captures| -> Result<(Table, TableLocations)> {
 			log::trace!(
 				"Processing table segmentation regex match: {}",
 				captures.get(0).unwrap().as_str()
@@ -288,56 +361,19 @@ fn extract_table_locations(schema: Schema, s: &str) -> FnvHashMap<Table, TableLo
 			let specs: Vec<&str> = specs.split(' ').collect();
 			let specs: Vec<TableSegmentSpecifier> = specs.iter().filter_map(|s| s.parse().ok()).collect();
 
-			let table: Table = match (schema, name) {
-				(Schema::Census2010(census2010::Schema::Pl94_171), "p1") => {
-					Table::Census2010(census2010::Table::Pl94_171(census2010::pl94_171::P1))
-				}
-				(Schema::Census2010(census2010::Schema::Pl94_171), "p2") => {
-					Table::Census2010(census2010::Table::Pl94_171(census2010::pl94_171::P2))
-				}
-				(Schema::Census2010(census2010::Schema::Pl94_171), "p3") => {
-					Table::Census2010(census2010::Table::Pl94_171(census2010::pl94_171::P3))
-				}
-				(Schema::Census2010(census2010::Schema::Pl94_171), "p4") => {
-					Table::Census2010(census2010::Table::Pl94_171(census2010::pl94_171::P4))
-				}
-				(Schema::Census2010(census2010::Schema::Pl94_171), "h1") => {
-					Table::Census2010(census2010::Table::Pl94_171(census2010::pl94_171::H1))
-				}
-				(Schema::Census2010(census2010::Schema::Pl94_171), _) => unimplemented!(),
-
-				(Schema::Census2020(census2020::Schema::Pl94_171), "p1") => {
-					Table::Census2020(census2020::Table::Pl94_171(census2020::pl94_171::P1))
-				}
-				(Schema::Census2020(census2020::Schema::Pl94_171), "p2") => {
-					Table::Census2020(census2020::Table::Pl94_171(census2020::pl94_171::P2))
-				}
-				(Schema::Census2020(census2020::Schema::Pl94_171), "p3") => {
-					Table::Census2020(census2020::Table::Pl94_171(census2020::pl94_171::P3))
-				}
-				(Schema::Census2020(census2020::Schema::Pl94_171), "p4") => {
-					Table::Census2020(census2020::Table::Pl94_171(census2020::pl94_171::P4))
-				}
-				(Schema::Census2020(census2020::Schema::Pl94_171), "h1") => {
-					Table::Census2020(census2020::Table::Pl94_171(census2020::pl94_171::H1))
-				}
-				(Schema::Census2020(census2020::Schema::Pl94_171), "p5") => {
-					Table::Census2020(census2020::Table::Pl94_171(census2020::pl94_171::P5))
-				}
-				(Schema::Census2020(census2020::Schema::Pl94_171), _) => unimplemented!(),
-			};
+			let table: Table = schema.table(name).ok_or_else(|| {
+				Error::InvalidPackingList(format!("unknown table {} in {:?}", name, schema))
+			})?;
 
 			let locations: TableLocations = specs
 				.iter()
 				.map(|specifier| {
-					if current_columns.get(&specifier.file).is_none() {
-						current_columns.insert(specifier.file, 5_usize);
-					}
+					let current: &mut usize = current_columns.entry(specifier.file).or_insert(5_usize);
 
-					let start: usize = *current_columns.get(&specifier.file).unwrap();
+					let start: usize = *current;
 					let end: usize = start + specifier.columns;
 
-					current_columns.insert(specifier.file, end);
+					*current = end;
 
 					TableSegmentLocation {
 						file: specifier.file,
@@ -348,7 +384,7 @@ fn extract_table_locations(schema: Schema, s: &str) -> FnvHashMap<Table, TableLo
 
 			log::trace!("Table {:?} is found at {:?}", table, locations);
 
-			(table, locations)
+			Ok((table, locations))
 		})
 		.collect()
 }
@@ -363,7 +399,7 @@ impl core::str::FromStr for PackingList {
 
 		log::debug!("Parsing STUSAB field from packing list data");
 
-		let stusab: String = get_stusab(s);
+		let stusab: String = get_stusab(s)?;
 
 		log::debug!("Inferred STUSAB: {}", stusab);
 
@@ -382,7 +418,7 @@ impl core::str::FromStr for PackingList {
 
 		log::debug!("Inferring schema");
 
-		let mut schemas: Vec<Schema> = extract_schemas(&filename_re, s);
+		let mut schemas: Vec<Schema> = extract_schemas(&filename_re, s)?;
 
 		log::trace!("Deduplicating {} schemas", schemas.len());
 
@@ -390,7 +426,12 @@ impl core::str::FromStr for PackingList {
 
 		log::trace!("Now have {} schema(s)", schemas.len());
 
-		assert_eq!(schemas.len(), 1);
+		if schemas.len() != 1 {
+			return Err(Error::InvalidPackingList(format!(
+				"expected one schema, found {:?}",
+				schemas
+			)));
+		}
 
 		let schema: Schema = schemas.remove(0);
 
@@ -405,8 +446,8 @@ impl core::str::FromStr for PackingList {
 			PathBuf,
 			usize,
 		) = {
-			let file_informations: Vec<FileInformation> = extract_file_information(s);
-			convert_file_information(&partition_file_information(&file_informations))
+			let file_informations: Vec<FileInformation> = extract_file_information(s)?;
+			convert_file_information(&partition_file_information(&file_informations)?)
 		};
 
 		log::debug!(
@@ -422,7 +463,7 @@ impl core::str::FromStr for PackingList {
 
 		// TODO consider just hard-coding the table locations in our spec
 
-		let table_locations: FnvHashMap<Table, TableLocations> = extract_table_locations(schema, s);
+		let table_locations: FnvHashMap<Table, TableLocations> = extract_table_locations(schema, s)?;
 
 		Ok(Self {
 			schema,
@@ -525,6 +566,302 @@ mod tests {
 		};
 	}
 
+	/// A packing list in the layout of SF1's, cut down to a few segments
+	const SF1: &str = "\
+      File: Packing list for zip file.
+       STUSAB: AK
+     Zip File: /dpp/prod/HANDOFF/sf1/ak/ak2010.sf1.prd.zip
+p1|1:1|
+p12|4:49|
+p12a|4:49|
+pct12|10:209|
+pct12a|10:29 11:180|
+h1|44:1|
+akgeo2010.sf1|2011-05-20 10:49:10|3463818|6952|
+ak000012010.sf1|2011-05-20 10:48:23|285220|6952|
+ak000042010.sf1|2011-05-20 10:48:29|3044218|6952|
+ak000102010.sf1|2011-05-20 10:48:35|2953216|6952|
+ak000112010.sf1|2011-05-20 10:48:36|2641932|6952|
+ak000442010.sf1|2011-05-20 10:48:58|285220|6952|
+";
+
+	#[test]
+	fn census2010_sf1() {
+		use crate::census2010::{sf1, Table::Sf1};
+		use crate::TableSegmentLocation;
+
+		let packing_list: PackingList = SF1.parse().unwrap();
+		assert_eq!(
+			packing_list.schema,
+			Schema::Census2010(crate::census2010::Schema::Sf1)
+		);
+		assert_eq!(packing_list.tabular_files.len(), 5);
+		assert_eq!(packing_list.rows, 6952);
+
+		let locations =
+			|table: sf1::Table| &packing_list.table_locations[&Table::Census2010(Sf1(table))];
+
+		assert_eq!(
+			locations(sf1::Table::P(12, Some('a'))),
+			&vec![TableSegmentLocation {
+				file: 4,
+				range: (5 + 49)..(5 + 49 + 49)
+			}]
+		);
+		assert_eq!(
+			locations(sf1::Table::Pct(12, Some('a'))),
+			&vec![
+				TableSegmentLocation {
+					file: 10,
+					range: (5 + 209)..(5 + 209 + 29)
+				},
+				TableSegmentLocation {
+					file: 11,
+					range: 5..(5 + 180)
+				}
+			]
+		);
+		assert_eq!(
+			locations(sf1::Table::H(1, None)),
+			&vec![TableSegmentLocation {
+				file: 44,
+				range: 5..6
+			}]
+		);
+	}
+
+	#[test]
+	fn malformed_packing_lists_are_errors() {
+		use crate::Error;
+
+		let malformed: [(&str, String); 5] = [
+			("unknown schema", DHC.replace(".dhc", ".sf3")),
+			("unknown table", DHC.replace("h1|", "q1|")),
+			(
+				"unknown file",
+				DHC.replace("rigeo2020.dhc", "rixyz2020.dhc"),
+			),
+			("no header", DHC.replace("rigeo2020.dhc|", "")),
+			("no STUSAB", DHC.replace("STUSAB: RI", "")),
+		];
+
+		for (problem, packing_list) in &malformed {
+			assert!(
+				matches!(
+					packing_list.parse::<PackingList>(),
+					Err(Error::InvalidPackingList(_))
+				),
+				"{} was accepted",
+				problem
+			);
+		}
+	}
+
+	/// A packing list for the 2020-style test files, laid out as DHC
+	const DHC: &str = "\
+       STUSAB: RI
+p1|1:1|
+p12a|1:3 2:2|
+h1|3:1|
+rigeo2020.dhc|2023-05-25 00:00:00|7052|19346|
+ri000012020.dhc|2023-05-25 00:00:00|434|19346|
+ri000022020.dhc|2023-05-25 00:00:00|379|19346|
+ri000032020.dhc|2023-05-25 00:00:00|46|19346|
+";
+
+	#[test]
+	fn census2020_dhc() {
+		use crate::census2020::{dhc, Table::Dhc};
+		use crate::{Dataset, IndexedDataset, TableSegmentLocation};
+
+		let packing_list: PackingList = DHC.parse().unwrap();
+		assert_eq!(
+			packing_list.schema,
+			Schema::Census2020(crate::census2020::Schema::Dhc)
+		);
+
+		let p12a = Table::Census2020(Dhc(dhc::Table::P(12, Some('a'))));
+		assert_eq!(
+			packing_list.table_locations[&p12a],
+			vec![
+				TableSegmentLocation {
+					file: 1,
+					range: 6..9
+				},
+				TableSegmentLocation {
+					file: 2,
+					range: 5..7
+				}
+			]
+		);
+
+		// The 2020-style test files, renamed as DHC's would be
+		let temporary = tempfile::tempdir().unwrap();
+		let directory = temporary.path();
+		std::fs::write(directory.join("ri2020.dhc.packinglist.txt"), DHC).unwrap();
+		for (from, to) in &[
+			("rigeo2018_2020Style.pl.trim", "rigeo2020.dhc"),
+			("ri000012018_2020Style.pl.trim", "ri000012020.dhc"),
+			("ri000022018_2020Style.pl.trim", "ri000022020.dhc"),
+			("ri000032018_2020Style.pl.trim", "ri000032020.dhc"),
+		] {
+			std::fs::copy(
+				std::path::Path::new("tests/data").join(from),
+				directory.join(to),
+			)
+			.unwrap();
+		}
+
+		let dataset =
+			IndexedDataset::from_packing_list_file(directory.join("ri2020.dhc.packinglist.txt"))
+				.and_then(IndexedDataset::index)
+				.unwrap();
+
+		let logrecno = dataset
+			.get_logical_record_number_for_geoid("440070184001012")
+			.unwrap();
+		assert_eq!(logrecno, 19_200);
+		assert_eq!(
+			dataset
+				.get_header_for_geoid("440070184001012")
+				.unwrap()
+				.name(),
+			"Block 1012"
+		);
+
+		let record = dataset.get_logical_record(logrecno).unwrap();
+		let p1 = Table::Census2020(Dhc(dhc::Table::P(1, None)));
+		assert_eq!(
+			record.table(dataset.table_locations(&p1).unwrap()),
+			Some(vec!["25"])
+		);
+	}
+
+	/// The 2000 redistricting files as listed in the 2010 layout, which they
+	/// never were
+	const CENSUS2000: &str = "\
+       STUSAB: IN
+pl1|1:71|
+in00001.upl|2001-03-08 00:00:00|900|3|
+";
+
+	#[test]
+	fn census2000_pl94_171() {
+		use crate::census2000::{pl94_171, GeographicalHeader, Table::Pl94_171};
+		use crate::{Dataset, GeographicalHeader as _, IndexedDataset, TableSegmentLocation};
+
+		assert!(matches!(
+			CENSUS2000.parse::<PackingList>(),
+			Err(crate::Error::InvalidPackingList(_))
+		));
+
+		// A state, a block, and a block group, which isn't indexed
+		let header = |sumlev: &str, logrecno: &str, block: &str, population: &str| -> String {
+			let mut line: String = " ".repeat(400);
+			for (at, value) in &[
+				(0, "uPLST"),
+				(6, "IN"),
+				(8, sumlev),
+				(11, "00"),
+				(18, logrecno),
+				(29, "18"),
+				(31, "157"),
+				(55, "005200"),
+				(61, "1"),
+				(62, block),
+				(200, "Block 1013"),
+				(292, population),
+			] {
+				line.replace_range(*at..at + value.len(), value);
+			}
+			line + "\n"
+		};
+		let geo: String = [
+			header("040", "0000001", "    ", "  6080485"),
+			header("101", "0000002", "1013", "       53"),
+			header("150", "0000003", "    ", "     1220"),
+		]
+		.concat();
+		assert_eq!(
+			GeographicalHeader::new(geo.lines().nth(1).unwrap().to_string())
+				.population()
+				.unwrap(),
+			53
+		);
+
+		let data = |file: &str| -> String {
+			(1..=3)
+				.map(|logrecno| {
+					format!(
+						"uPL,IN,000,{},{:07},{}\n",
+						file,
+						logrecno,
+						vec![logrecno.to_string(); 144].join(",")
+					)
+				})
+				.collect()
+		};
+
+		let temporary = tempfile::tempdir().unwrap();
+		let directory = temporary.path();
+		std::fs::write(directory.join("ingeo.upl"), geo).unwrap();
+		std::fs::write(directory.join("in00001.upl"), data("01")).unwrap();
+		std::fs::write(directory.join("in00002.upl"), data("02")).unwrap();
+
+		let packing_list: PackingList = PackingList::from_file(directory.join("ingeo.upl")).unwrap();
+		assert_eq!(
+			packing_list.schema,
+			Schema::Census2000(crate::census2000::Schema::Pl94_171)
+		);
+		assert_eq!(packing_list.rows, 3);
+		assert_eq!(
+			packing_list.locate(&packing_list.tabular_files[&2]),
+			Some(directory.join("in00002.upl"))
+		);
+		assert_eq!(
+			packing_list.geographical_header_file,
+			std::path::PathBuf::from("ingeo.upl")
+		);
+
+		let pl4 = Table::Census2000(Pl94_171(pl94_171::PL4));
+		assert_eq!(
+			packing_list.table_locations[&pl4],
+			vec![TableSegmentLocation {
+				file: 2,
+				range: (5 + 71)..(5 + 71 + 73)
+			}]
+		);
+
+		let dataset = IndexedDataset::from_packing_list_file(directory.join("ingeo.upl"))
+			.and_then(IndexedDataset::index)
+			.unwrap();
+		assert_eq!(
+			dataset.geoids().collect::<Vec<_>>(),
+			vec!["181570052001013"]
+		);
+
+		let logrecno = dataset
+			.get_logical_record_number_for_geoid("181570052001013")
+			.unwrap();
+		assert_eq!(logrecno, 2);
+		assert_eq!(
+			dataset
+				.get_header_for_geoid("181570052001013")
+				.unwrap()
+				.name(),
+			"Block 1013"
+		);
+
+		let record = dataset.get_logical_record(logrecno).unwrap();
+		let pl3 = Table::Census2000(Pl94_171(pl94_171::PL3));
+		assert_eq!(
+			record
+				.table(dataset.table_locations(&pl3).unwrap())
+				.unwrap()[0],
+			"2"
+		);
+	}
+
 	t_census2010_pl94_171!("t/2010/ak2010.pl.prd.packinglist.txt", ak);
 	t_census2010_pl94_171!("t/2010/al2010.pl.prd.packinglist.txt", al);
 	t_census2010_pl94_171!("t/2010/ar2010.pl.prd.packinglist.txt", ar);
@@ -0,0 +1,205 @@
+use std::path::Path;
+
+use serde::Serialize;
+use serde_yaml::Value;
+
+use crate::{Result, Table};
+
+/// A single column of a table, as described by a data dictionary
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Column {
+	id: String,
+	label: String,
+	depth: usize,
+	parent: Option<String>,
+}
+
+impl Column {
+	/// The Census identifier of the column, e.g. `P0010003`
+	pub fn id(&self) -> &str {
+		&self.id
+	}
+
+	pub fn label(&self) -> &str {
+		&self.label
+	}
+
+	/// How deeply nested this column is; top-level columns have depth `0`
+	pub fn depth(&self) -> usize {
+		self.depth
+	}
+
+	/// The identifier of the column this one is a component of, if any
+	pub fn parent(&self) -> Option<&str> {
+		self.parent.as_deref()
+	}
+}
+
+/// The column hierarchy of a table
+///
+/// Dictionaries are written as nested YAML sequences (see `schemas/`) where
+/// each entry is either a bare label or a single-entry mapping from a label to
+/// its children.  Columns are numbered in the order they appear, which is the
+/// order in which they are laid out in the tabular files.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TableDictionary {
+	table: String,
+	columns: Vec<Column>,
+}
+
+impl TableDictionary {
+	pub fn from_yaml_str(table: Table, s: &str) -> Result<Self> {
+		let value: Value = serde_yaml::from_str(s)?;
+
+		let prefix: (String, usize) = column_prefix(&table.to_string());
+		let mut columns: Vec<Column> = Vec::new();
+
+		walk(&prefix, &value, 0, None, &mut columns)?;
+
+		Ok(Self {
+			table: table.to_string(),
+			columns,
+		})
+	}
+
+	pub fn from_file<P: AsRef<Path>>(table: Table, path: P) -> Result<Self> {
+		let data: String = std::fs::read_to_string(path)?;
+		Self::from_yaml_str(table, &data)
+	}
+
+	pub fn table(&self) -> &str {
+		&self.table
+	}
+
+	pub fn columns(&self) -> &[Column] {
+		&self.columns
+	}
+}
+
+/// Build the column identifier prefix for a table name
+///
+/// Identifiers are the uppercased table name with its number zero-padded to
+/// three digits, so `p1` becomes `P001` and `p12a` becomes `P012A`.  Also
+/// returns the width of the column number that follows the prefix.
+fn column_prefix(table: &str) -> (String, usize) {
+	let table: String = table.to_uppercase();
+
+	let letters_end: usize = table
+		.find(|c: char| c.is_ascii_digit())
+		.unwrap_or(table.len());
+	let digits_end: usize = table[letters_end..]
+		.find(|c: char| !c.is_ascii_digit())
+		.map_or(table.len(), |idx| letters_end + idx);
+
+	let prefix: String = format!(
+		"{}{:0>3}{}",
+		&table[..letters_end],
+		&table[letters_end..digits_end],
+		&table[digits_end..]
+	);
+
+	// Iterated tables (those with a suffix, like `p12a`) use one fewer digit
+	// for the column number.
+	let width: usize = if digits_end == table.len() { 4 } else { 3 };
+
+	(prefix, width)
+}
+
+fn column_id((prefix, width): &(String, usize), number: usize) -> String {
+	format!("{}{:0>width$}", prefix, number, width = width)
+}
+
+fn walk(
+	prefix: &(String, usize),
+	value: &Value,
+	depth: usize,
+	parent: Option<&str>,
+	columns: &mut Vec<Column>,
+) -> Result<()> {
+	let entries = match value {
+		Value::Sequence(entries) => entries,
+		Value::Null => return Ok(()),
+		_ => return Err(crate::Error::InvalidDictionary),
+	};
+
+	for entry in entries {
+		let (label, children): (&str, Option<&Value>) = match entry {
+			Value::String(label) => (label, None),
+			Value::Mapping(mapping) if mapping.len() == 1 => {
+				let (label, children) = mapping.iter().next().unwrap();
+				let label: &str = label.as_str().ok_or(crate::Error::InvalidDictionary)?;
+				(label, Some(children))
+			}
+			_ => return Err(crate::Error::InvalidDictionary),
+		};
+
+		let id: String = column_id(prefix, columns.len() + 1);
+
+		columns.push(Column {
+			id: id.clone(),
+			label: label.to_string(),
+			depth,
+			parent: parent.map(ToString::to_string),
+		});
+
+		if let Some(children) = children {
+			walk(prefix, children, depth + 1, Some(&id), columns)?;
+		}
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{column_prefix, TableDictionary};
+	use crate::{census2010, Table};
+
+	const P1: Table = Table::Census2010(census2010::Table::Pl94_171(census2010::pl94_171::P1));
+	const H1: Table = Table::Census2010(census2010::Table::Pl94_171(census2010::pl94_171::H1));
+
+	#[test]
+	fn prefixes_are_padded() {
+		assert_eq!(column_prefix("p1"), ("P001".to_string(), 4));
+		assert_eq!(column_prefix("h1"), ("H001".to_string(), 4));
+		assert_eq!(column_prefix("p12a"), ("P012A".to_string(), 3));
+		assert_eq!(column_prefix("pct12"), ("PCT012".to_string(), 4));
+	}
+
+	#[test]
+	fn h1_parses() {
+		let dictionary =
+			TableDictionary::from_yaml_str(H1, include_str!("../schemas/2010/pl94_171/h1.yml")).unwrap();
+
+		let columns: Vec<(&str, &str, usize, Option<&str>)> = dictionary
+			.columns()
+			.iter()
+			.map(|c| (c.id(), c.label(), c.depth(), c.parent()))
+			.collect();
+
+		assert_eq!(
+			columns,
+			vec![
+				("H0010001", "Total", 0, None),
+				("H0010002", "Occupied", 1, Some("H0010001")),
+				("H0010003", "Vacant", 1, Some("H0010001")),
+			]
+		);
+	}
+
+	#[test]
+	fn p1_has_every_column() {
+		let dictionary =
+			TableDictionary::from_yaml_str(P1, include_str!("../schemas/2010/pl94_171/p1.yml")).unwrap();
+
+		assert_eq!(dictionary.columns().len(), 71);
+		assert_eq!(dictionary.columns()[70].id(), "P0010071");
+		assert_eq!(dictionary.columns()[70].depth(), 3);
+		assert_eq!(dictionary.columns()[70].parent(), Some("P0010070"));
+	}
+
+	#[test]
+	fn scalars_are_rejected() {
+		assert!(TableDictionary::from_yaml_str(P1, "- Total: 3").is_err());
+	}
+}
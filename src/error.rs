@@ -7,10 +7,22 @@ pub enum Error {
 	Io(io::Error),
 	Csv(csv::Error),
 	Config(config::ConfigError),
-	GeoJson(geojson::Error),
+	GeoJson(Box<geojson::Error>),
 	ParseInt(num::ParseIntError),
+	Yaml(serde_yaml::Error),
+	Json(serde_json::Error),
 
 	InvalidGeoId,
+	InvalidLogicalRecordNumber,
+	InvalidTableName,
+	InvalidDictionary,
+	InvalidReference(String),
+	InvalidGeometry,
+	InvalidConfiguration(Vec<String>),
+	InvalidFederation(String),
+	InvalidPackingList(String),
+	MissingPopulation,
+	MissingColumn(String),
 
 	InvalidServerHost,
 	InvalidServerPort,
@@ -23,7 +35,28 @@ impl core::fmt::Display for Error {
 			Error::Csv(inner) => writeln!(f, "csv error: {}", inner),
 			Error::Config(inner) => writeln!(f, "config error: {}", inner),
 			Error::GeoJson(inner) => writeln!(f, "geojson error: {}", inner),
-			_ => todo!(),
+			Error::ParseInt(inner) => writeln!(f, "parse error: {}", inner),
+			Error::Yaml(inner) => writeln!(f, "yaml error: {}", inner),
+			Error::Json(inner) => writeln!(f, "json error: {}", inner),
+			Error::InvalidGeoId => writeln!(f, "invalid geoid"),
+			Error::InvalidLogicalRecordNumber => writeln!(f, "invalid logical record number"),
+			Error::InvalidTableName => writeln!(f, "invalid table name"),
+			Error::InvalidDictionary => writeln!(f, "invalid dictionary"),
+			Error::InvalidReference(id) => writeln!(f, "invalid reference to {}", id),
+			Error::InvalidGeometry => writeln!(f, "invalid geometry"),
+			Error::InvalidConfiguration(problems) => {
+				writeln!(f, "invalid configuration:")?;
+				for problem in problems {
+					writeln!(f, "  {}", problem)?;
+				}
+				Ok(())
+			}
+			Error::InvalidFederation(reason) => writeln!(f, "invalid federation: {}", reason),
+			Error::InvalidPackingList(reason) => writeln!(f, "invalid packing list: {}", reason),
+			Error::MissingPopulation => writeln!(f, "missing population"),
+			Error::MissingColumn(name) => writeln!(f, "missing column {}", name),
+			Error::InvalidServerHost => writeln!(f, "invalid server host"),
+			Error::InvalidServerPort => writeln!(f, "invalid server port"),
 		}
 	}
 }
@@ -52,7 +85,7 @@ impl From<config::ConfigError> for Error {
 
 impl From<geojson::Error> for Error {
 	fn from(e: geojson::Error) -> Error {
-		Self::GeoJson(e)
+		Self::GeoJson(Box::new(e))
 	}
 }
 
@@ -61,3 +94,15 @@ impl From<num::ParseIntError> for Error {
 		Self::ParseInt(e)
 	}
 }
+
+impl From<serde_yaml::Error> for Error {
+	fn from(e: serde_yaml::Error) -> Error {
+		Self::Yaml(e)
+	}
+}
+
+impl From<serde_json::Error> for Error {
+	fn from(e: serde_json::Error) -> Error {
+		Self::Json(e)
+	}
+}
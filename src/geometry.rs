@@ -0,0 +1,258 @@
+use core::convert::TryFrom;
+
+use geo::{
+	algorithm::convex_hull::ConvexHull, Coordinate, LineString, MultiPoint, MultiPolygon, Polygon,
+};
+use geojson::GeoJson;
+
+use crate::{GeoId, Result};
+
+mod arcs;
+pub use arcs::*;
+
+mod dissolve;
+pub use dissolve::*;
+
+mod index;
+pub use index::*;
+
+pub mod mvt;
+
+pub mod topojson;
+
+mod tiles;
+pub use tiles::*;
+
+mod topology;
+pub use topology::*;
+
+/// Mean radius of the Earth, in meters
+pub const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// An equirectangular projection from degrees to meters
+///
+/// Distances are only true along the reference latitude, but the error is
+/// small across something the size of a state.
+#[derive(Clone, Copy, Debug)]
+pub struct Equirectangular {
+	cos_latitude: f64,
+}
+
+impl Equirectangular {
+	pub fn new(latitude: f64) -> Self {
+		Self {
+			cos_latitude: latitude.to_radians().cos(),
+		}
+	}
+
+	/// A projection along the middle latitude of some polygons
+	pub fn centered_on<'a, I: IntoIterator<Item = &'a MultiPolygon<f64>>>(polygons: I) -> Self {
+		let (min, max) = polygons
+			.into_iter()
+			.flat_map(|polygons| polygons.iter())
+			.flat_map(|polygon| polygon.exterior().points_iter())
+			.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), point| {
+				(min.min(point.y()), max.max(point.y()))
+			});
+
+		Self::new(if min <= max { (min + max) / 2.0 } else { 0.0 })
+	}
+
+	pub fn project(&self, c: Coordinate<f64>) -> (f64, f64) {
+		(
+			c.x.to_radians() * self.cos_latitude * EARTH_RADIUS,
+			c.y.to_radians() * EARTH_RADIUS,
+		)
+	}
+
+	/// The length of a segment, in meters
+	pub fn distance(&self, a: Coordinate<f64>, b: Coordinate<f64>) -> f64 {
+		let (a, b) = (self.project(a), self.project(b));
+		(b.0 - a.0).hypot(b.1 - a.1)
+	}
+
+	/// The signed area of a ring, in square meters (shoelace formula)
+	pub fn ring_area(&self, ring: &LineString<f64>) -> f64 {
+		ring
+			.lines()
+			.map(|line| {
+				let (a, b) = (self.project(line.start), self.project(line.end));
+				a.0 * b.1 - b.0 * a.1
+			})
+			.sum::<f64>()
+			/ 2.0
+	}
+
+	/// The area of a polygon less its holes, in square meters
+	pub fn polygon_area(&self, polygon: &Polygon<f64>) -> f64 {
+		self.ring_area(polygon.exterior()).abs()
+			- polygon
+				.interiors()
+				.iter()
+				.map(|interior| self.ring_area(interior).abs())
+				.sum::<f64>()
+	}
+
+	pub fn area(&self, polygons: &MultiPolygon<f64>) -> f64 {
+		polygons
+			.iter()
+			.map(|polygon| self.polygon_area(polygon))
+			.sum()
+	}
+}
+
+/// The area of some polygons, in square meters
+pub fn area(polygons: &MultiPolygon<f64>) -> f64 {
+	Equirectangular::centered_on(core::iter::once(polygons)).area(polygons)
+}
+
+/// The smallest convex polygon containing every one of some polygons
+///
+/// Returns `None` if there are no polygons.
+pub fn convex_hull<'a, I: IntoIterator<Item = &'a MultiPolygon<f64>>>(
+	polygons: I,
+) -> Option<Polygon<f64>> {
+	let points: MultiPoint<f64> = polygons
+		.into_iter()
+		.flat_map(|polygons| polygons.iter())
+		.flat_map(|polygon| polygon.exterior().points_iter())
+		.collect();
+
+	if points.0.is_empty() {
+		None
+	} else {
+		Some(points.convex_hull())
+	}
+}
+
+/// A vertex, identified exactly by the bits of its coordinates
+///
+/// Neighboring features in topologically-integrated data share vertices
+/// exactly, so these are used to match up their boundaries.
+type VertexKey = (u64, u64);
+
+fn vertex_key(c: Coordinate<f64>) -> VertexKey {
+	(c.x.to_bits(), c.y.to_bits())
+}
+
+/// A geographical entity and its extent
+pub type Region = (GeoId, MultiPolygon<f64>);
+
+/// Roughly how many bytes a region takes up
+pub fn region_size((geoid, polygons): &Region) -> usize {
+	let rings: usize = polygons
+		.iter()
+		.flat_map(|polygon| core::iter::once(polygon.exterior()).chain(polygon.interiors()))
+		.map(|ring| {
+			core::mem::size_of::<LineString<f64>>()
+				+ ring.0.len() * core::mem::size_of::<Coordinate<f64>>()
+		})
+		.sum();
+
+	core::mem::size_of::<Region>()
+		+ geoid.len()
+		+ polygons.0.len() * core::mem::size_of::<Polygon<f64>>()
+		+ rings
+}
+
+/// Roughly how many bytes the items of some vectors take up
+fn nested_size<T>(vectors: &[Vec<T>]) -> usize {
+	vectors
+		.iter()
+		.map(|items| core::mem::size_of::<Vec<T>>() + items.len() * core::mem::size_of::<T>())
+		.sum()
+}
+
+/// The feature properties that GeoIDs are read from, in order of preference
+///
+/// TIGER/Line files suffix the property with the vintage of the geography.
+pub const GEOID_PROPERTIES: &[&str] = &["GEOID20", "GEOID10", "GEOID"];
+
+/// Read the GeoID of a feature from its properties
+pub fn feature_geoid(feature: &geojson::Feature) -> Option<&str> {
+	GEOID_PROPERTIES
+		.iter()
+		.find_map(|property| feature.property(property))
+		.and_then(|value| value.as_str())
+}
+
+/// Convert a Polygon or MultiPolygon geometry to a `MultiPolygon`
+pub fn multi_polygon(geometry: &geojson::Geometry) -> Option<MultiPolygon<f64>> {
+	match &geometry.value {
+		geojson::Value::Polygon(_) => geo::Polygon::try_from(geometry.value.clone())
+			.ok()
+			.map(|polygon| MultiPolygon(vec![polygon])),
+		geojson::Value::MultiPolygon(_) => MultiPolygon::try_from(geometry.value.clone()).ok(),
+		_ => None,
+	}
+}
+
+/// Collect the GeoID and polygons of each feature in a `FeatureCollection`
+///
+/// Features without a GeoID or without (multi)polygon geometry are skipped.
+pub fn geoid_polygons(geojson: &GeoJson) -> Result<Vec<Region>> {
+	let collection: &geojson::FeatureCollection = match geojson {
+		GeoJson::FeatureCollection(collection) => collection,
+		_ => return Err(crate::Error::InvalidGeometry),
+	};
+
+	Ok(
+		collection
+			.features
+			.iter()
+			.filter_map(|feature| {
+				let geoid: &str = feature_geoid(feature)?;
+				let polygons: MultiPolygon<f64> = multi_polygon(feature.geometry.as_ref()?)?;
+
+				Some((geoid.to_string(), polygons))
+			})
+			.collect(),
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use geo::{polygon, MultiPolygon};
+
+	fn square(x: f64, y: f64) -> MultiPolygon<f64> {
+		MultiPolygon(vec![polygon![
+			(x: x, y: y),
+			(x: x + 1.0, y: y),
+			(x: x + 1.0, y: y + 1.0),
+			(x: x, y: y + 1.0),
+			(x: x, y: y),
+		]])
+	}
+
+	#[test]
+	fn hull_spans_every_polygon() {
+		let squares = vec![square(0.0, 0.0), square(2.0, 2.0)];
+		let hull = super::convex_hull(&squares).unwrap();
+
+		// Two opposite corners of the 3x3 box are cut off, each a triangle with an
+		// area of 2.
+		let degree: f64 = super::area(&square(1.0, 1.0)).sqrt();
+		let area: f64 = super::area(&MultiPolygon(vec![hull]));
+		assert!((area / (degree * degree) - 5.0).abs() < 0.05);
+	}
+
+	#[test]
+	fn no_polygons_have_no_hull() {
+		assert_eq!(super::convex_hull(&[]), None);
+	}
+
+	#[test]
+	fn holes_are_subtracted() {
+		let ring = MultiPolygon(vec![polygon!(
+			exterior: [(x: 0.0, y: 0.0), (x: 3.0, y: 0.0), (x: 3.0, y: 3.0), (x: 0.0, y: 3.0)],
+			interiors: [[(x: 1.0, y: 1.0), (x: 2.0, y: 1.0), (x: 2.0, y: 2.0), (x: 1.0, y: 2.0)]],
+		)]);
+		let solid = MultiPolygon(vec![polygon![
+			(x: 0.0, y: 0.0), (x: 3.0, y: 0.0), (x: 3.0, y: 3.0), (x: 0.0, y: 3.0)
+		]]);
+
+		let projection = super::Equirectangular::new(1.5);
+		let hole: f64 = projection.area(&solid) - projection.area(&ring);
+		assert!((hole / projection.area(&solid) - 1.0 / 9.0).abs() < 0.01);
+	}
+}
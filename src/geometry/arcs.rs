@@ -0,0 +1,465 @@
+use fnv::FnvHashMap;
+use geo::{
+	algorithm::euclidean_distance::EuclideanDistance, Coordinate, Line, LineString, MultiPolygon,
+	Point, Polygon,
+};
+
+use super::{nested_size, vertex_key, Region, VertexKey};
+use crate::GeoId;
+
+/// A reference to an arc, as in TopoJSON: `i` is arc `i`, and `!i` (that is,
+/// `-i - 1`) is arc `i` traversed backwards.
+pub type ArcRef = i64;
+
+/// The rings of a polygon, each as a sequence of arcs
+pub type ArcPolygon = Vec<Vec<ArcRef>>;
+
+/// Polygons decomposed into the arcs their boundaries are made of
+///
+/// An arc is a run of boundary between two junctions, which are vertices
+/// where more than two boundary segments meet.  Neighboring features refer
+/// to the same arc for their shared boundary, so changes made to an arc (like
+/// simplifying it) apply to both sides alike and no gaps or overlaps open up.
+/// Like [`Topology`](super::Topology), this relies on shared boundaries having
+/// identical vertices.
+///
+/// Each vertex of an arc carries its significance, so that the arcs can be
+/// simplified to any tolerance without simplifying them again.
+#[derive(Clone, Debug)]
+pub struct Arcs {
+	arcs: Vec<Vec<Coordinate<f64>>>,
+	significance: Vec<Vec<f64>>,
+	geoids: Vec<GeoId>,
+	features: Vec<Vec<ArcPolygon>>,
+}
+
+/// The largest tolerance at which the Douglas-Peucker algorithm keeps each
+/// vertex of a line
+///
+/// Simplifying with a tolerance keeps exactly the vertices whose
+/// significance is greater than it.  The ends of the line are always kept.
+fn significance(line: &[Coordinate<f64>]) -> Vec<f64> {
+	let mut significance: Vec<f64> = vec![f64::INFINITY; line.len()];
+
+	// Each run of the line left to split, with the significance of the vertex
+	// it was split off at, which none of its own vertices can exceed
+	let mut runs: Vec<(usize, usize, f64)> = Vec::new();
+	if line.len() > 2 {
+		runs.push((0, line.len() - 1, f64::INFINITY));
+	}
+
+	while let Some((first, last, bound)) = runs.pop() {
+		let chord: Line<f64> = Line::new(line[first], line[last]);
+		let (farthest, distance) = (first + 1..last)
+			.map(|idx| (idx, Point(line[idx]).euclidean_distance(&chord)))
+			.fold((first + 1, -1.0), |farthest, (idx, distance)| {
+				if distance > farthest.1 {
+					(idx, distance)
+				} else {
+					farthest
+				}
+			});
+
+		let distance: f64 = distance.min(bound);
+		significance[farthest] = distance;
+
+		for (first, last) in [(first, farthest), (farthest, last)] {
+			if last > first + 1 {
+				runs.push((first, last, distance));
+			}
+		}
+	}
+
+	significance
+}
+
+/// A ring as open list of vertices, without repeated points
+fn open_ring(ring: &LineString<f64>) -> Vec<Coordinate<f64>> {
+	let mut points: Vec<Coordinate<f64>> = Vec::with_capacity(ring.0.len());
+
+	for c in &ring.0 {
+		if points.last().map(|last| vertex_key(*last)) != Some(vertex_key(*c)) {
+			points.push(*c);
+		}
+	}
+
+	while points.len() > 1 && vertex_key(points[0]) == vertex_key(points[points.len() - 1]) {
+		points.pop();
+	}
+
+	points
+}
+
+impl Arcs {
+	pub fn new(regions: &[Region]) -> Self {
+		let rings: Vec<Vec<Vec<Vec<Coordinate<f64>>>>> = regions
+			.iter()
+			.map(|(_, polygons)| {
+				polygons
+					.iter()
+					.map(|polygon| {
+						core::iter::once(polygon.exterior())
+							.chain(polygon.interiors())
+							.map(open_ring)
+							.collect()
+					})
+					.collect()
+			})
+			.collect();
+
+		// Find the junctions: vertices adjacent to more than two others.
+		let mut adjacent: FnvHashMap<VertexKey, Vec<VertexKey>> = FnvHashMap::default();
+		for ring in rings.iter().flatten().flatten() {
+			for (i, c) in ring.iter().enumerate() {
+				let previous = ring[(i + ring.len() - 1) % ring.len()];
+				let next = ring[(i + 1) % ring.len()];
+
+				let neighbors = adjacent.entry(vertex_key(*c)).or_default();
+				for neighbor in &[vertex_key(previous), vertex_key(next)] {
+					if !neighbors.contains(neighbor) {
+						neighbors.push(*neighbor);
+					}
+				}
+			}
+		}
+		let is_junction = |c: &Coordinate<f64>| adjacent.get(&vertex_key(*c)).map_or(0, Vec::len) > 2;
+
+		let mut arcs: Vec<Vec<Coordinate<f64>>> = Vec::new();
+		let mut arc_index: FnvHashMap<Vec<VertexKey>, usize> = FnvHashMap::default();
+
+		let mut arc_ref = |arc: Vec<Coordinate<f64>>| -> ArcRef {
+			let keys: Vec<VertexKey> = arc.iter().map(|c| vertex_key(*c)).collect();
+
+			if let Some(idx) = arc_index.get(&keys) {
+				return *idx as ArcRef;
+			}
+
+			let reversed: Vec<VertexKey> = keys.iter().rev().copied().collect();
+			if let Some(idx) = arc_index.get(&reversed) {
+				return !(*idx as ArcRef);
+			}
+
+			arc_index.insert(keys, arcs.len());
+			arcs.push(arc);
+			(arcs.len() - 1) as ArcRef
+		};
+
+		let features: Vec<Vec<ArcPolygon>> = rings
+			.into_iter()
+			.map(|polygons| {
+				polygons
+					.into_iter()
+					.map(|rings| {
+						rings
+							.into_iter()
+							.filter(|ring| ring.len() >= 3)
+							.map(|ring| {
+								// Start at a junction if there is one, and otherwise at the
+								// south-westernmost vertex, so that rings sharing all of their
+								// boundary agree on where their one arc starts.  That vertex is
+								// a corner, so it would survive simplification anyway.
+								let start: usize = ring.iter().position(&is_junction).unwrap_or_else(|| {
+									(0..ring.len())
+										.min_by(|&i, &j| {
+											ring[i]
+												.x
+												.total_cmp(&ring[j].x)
+												.then(ring[i].y.total_cmp(&ring[j].y))
+										})
+										.unwrap_or(0)
+								});
+
+								let mut refs: Vec<ArcRef> = Vec::new();
+								let mut arc: Vec<Coordinate<f64>> = vec![ring[start]];
+
+								for offset in 1..=ring.len() {
+									let c: Coordinate<f64> = ring[(start + offset) % ring.len()];
+									arc.push(c);
+
+									if offset == ring.len() || is_junction(&c) {
+										refs.push(arc_ref(core::mem::replace(&mut arc, vec![c])));
+									}
+								}
+
+								refs
+							})
+							.collect()
+					})
+					.collect()
+			})
+			.collect();
+
+		Self {
+			significance: arcs.iter().map(|arc| significance(arc)).collect(),
+			arcs,
+			geoids: regions.iter().map(|(geoid, _)| geoid.clone()).collect(),
+			features,
+		}
+	}
+
+	pub fn arcs(&self) -> &[Vec<Coordinate<f64>>] {
+		&self.arcs
+	}
+
+	/// Roughly how many bytes the arcs take up
+	pub fn size(&self) -> usize {
+		let geoids: usize = self
+			.geoids
+			.iter()
+			.map(|geoid| core::mem::size_of::<GeoId>() + geoid.len())
+			.sum();
+		let rings: usize = self
+			.features
+			.iter()
+			.flatten()
+			.map(|polygon| nested_size(polygon))
+			.sum();
+
+		nested_size(&self.arcs)
+			+ nested_size(&self.significance)
+			+ nested_size(&self.features)
+			+ geoids
+			+ rings
+	}
+
+	pub fn geoids(&self) -> &[GeoId] {
+		&self.geoids
+	}
+
+	/// The polygons of feature `idx`, as rings of arcs
+	pub fn polygons(&self, idx: usize) -> &[ArcPolygon] {
+		&self.features[idx]
+	}
+
+	/// Simplify every arc with the Douglas-Peucker algorithm
+	///
+	/// The ends of arcs stay put, so neighbors stay neighbors.  `tolerance` is
+	/// in the units of the coordinates.  Rings that collapse are dropped, so
+	/// features can be left with no polygons at all.
+	pub fn simplify(&self, tolerance: f64) -> Self {
+		let arcs: Vec<Vec<Coordinate<f64>>> = (0..self.arcs.len())
+			.map(|idx| self.simplified_arc(idx, Some(tolerance)).copied().collect())
+			.collect();
+
+		Self {
+			significance: arcs.iter().map(|arc| significance(arc)).collect(),
+			arcs,
+			geoids: self.geoids.clone(),
+			// Drop the rings that have collapsed, and the polygons whose exterior
+			// has.
+			features: (0..self.features.len())
+				.map(|idx| self.simplified_polygons(idx, Some(tolerance)).collect())
+				.collect(),
+		}
+	}
+
+	/// The vertices of arc `idx` that are kept when simplifying to
+	/// `tolerance`, or all of them
+	pub fn simplified_arc(
+		&self,
+		idx: usize,
+		tolerance: Option<f64>,
+	) -> impl Iterator<Item = &Coordinate<f64>> {
+		let tolerance: f64 = tolerance
+			.filter(|tolerance| *tolerance > 0.0)
+			.unwrap_or(f64::NEG_INFINITY);
+
+		self.arcs[idx]
+			.iter()
+			.zip(&self.significance[idx])
+			.filter(move |(_, significance)| **significance > tolerance)
+			.map(|(c, _)| c)
+	}
+
+	/// Whether a ring has no area left after simplifying to `tolerance`
+	fn collapsed(&self, refs: &[ArcRef], tolerance: Option<f64>) -> bool {
+		let ring: Vec<Coordinate<f64>> = self.ring(refs, tolerance);
+
+		ring.len().saturating_sub(1) < 3
+			|| LineString(ring)
+				.lines()
+				.map(|line| line.start.x * line.end.y - line.end.x * line.start.y)
+				.sum::<f64>()
+				== 0.0
+	}
+
+	fn ring(&self, refs: &[ArcRef], tolerance: Option<f64>) -> Vec<Coordinate<f64>> {
+		let mut ring: Vec<Coordinate<f64>> = Vec::new();
+
+		for arc_ref in refs {
+			let (arc, reversed) = if *arc_ref >= 0 {
+				(self.simplified_arc(*arc_ref as usize, tolerance), false)
+			} else {
+				(self.simplified_arc(!*arc_ref as usize, tolerance), true)
+			};
+
+			let points: Box<dyn Iterator<Item = &Coordinate<f64>>> = if reversed {
+				Box::new(arc.collect::<Vec<_>>().into_iter().rev())
+			} else {
+				Box::new(arc)
+			};
+
+			let skip: usize = if ring.is_empty() { 0 } else { 1 };
+			ring.extend(points.skip(skip));
+		}
+
+		ring
+	}
+
+	/// The polygons of feature `idx` that are left after simplifying to
+	/// `tolerance`, with only the rings that are left
+	pub fn simplified_polygons(
+		&self,
+		idx: usize,
+		tolerance: Option<f64>,
+	) -> impl Iterator<Item = ArcPolygon> + '_ {
+		self.features[idx]
+			.iter()
+			.filter(move |rings| {
+				rings
+					.first()
+					.is_some_and(|ring| !self.collapsed(ring, tolerance))
+			})
+			.map(move |rings| {
+				rings
+					.iter()
+					.filter(|ring| !self.collapsed(ring, tolerance))
+					.cloned()
+					.collect()
+			})
+	}
+
+	/// Reassemble the polygons of feature `idx`
+	///
+	/// Rings that have collapsed are left out, as are polygons whose exterior
+	/// has collapsed.
+	pub fn multi_polygon(&self, idx: usize) -> MultiPolygon<f64> {
+		self.simplified_multi_polygon(idx, None)
+	}
+
+	/// Reassemble the polygons of feature `idx` as if the arcs were simplified
+	/// to `tolerance` first, or not at all
+	///
+	/// This is the same as reassembling them from [`Self::simplify`], without
+	/// simplifying every other arc too.
+	pub fn simplified_multi_polygon(&self, idx: usize, tolerance: Option<f64>) -> MultiPolygon<f64> {
+		MultiPolygon(
+			self
+				.simplified_polygons(idx, tolerance)
+				.map(|rings| {
+					let mut rings = rings
+						.iter()
+						.map(|refs| LineString(self.ring(refs, tolerance)));
+
+					let exterior: LineString<f64> = rings.next().unwrap_or_else(|| LineString(Vec::new()));
+					Polygon::new(exterior, rings.collect())
+				})
+				.collect(),
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Arcs;
+	use geo::{polygon, MultiPolygon};
+
+	fn regions() -> Vec<(String, MultiPolygon<f64>)> {
+		// Two squares sharing a jagged edge
+		vec![
+			(
+				"west".to_string(),
+				MultiPolygon(vec![polygon![
+					(x: 0.0, y: 0.0),
+					(x: 1.0, y: 0.0),
+					(x: 1.01, y: 0.5),
+					(x: 1.0, y: 1.0),
+					(x: 0.0, y: 1.0),
+					(x: 0.0, y: 0.0),
+				]]),
+			),
+			(
+				"east".to_string(),
+				MultiPolygon(vec![polygon![
+					(x: 1.0, y: 0.0),
+					(x: 2.0, y: 0.0),
+					(x: 2.0, y: 1.0),
+					(x: 1.0, y: 1.0),
+					(x: 1.01, y: 0.5),
+					(x: 1.0, y: 0.0),
+				]]),
+			),
+		]
+	}
+
+	#[test]
+	fn shared_boundaries_share_arcs() {
+		let arcs = Arcs::new(&regions());
+
+		// The shared edge, and the rest of each square
+		assert_eq!(arcs.arcs().len(), 3);
+
+		let west = &arcs.polygons(0)[0][0];
+		let east = &arcs.polygons(1)[0][0];
+		assert!(west.iter().any(|arc| east.contains(&!arc)));
+	}
+
+	#[test]
+	fn rings_reassemble() {
+		let regions = regions();
+		let arcs = Arcs::new(&regions);
+
+		for (idx, (_, polygons)) in regions.iter().enumerate() {
+			let reassembled = arcs.multi_polygon(idx);
+			let mut expected: Vec<_> = polygons.0[0].exterior().0.clone();
+			let mut actual: Vec<_> = reassembled.0[0].exterior().0.clone();
+			expected.pop();
+			actual.pop();
+
+			assert_eq!(actual.len(), expected.len());
+			assert!(actual.iter().all(|c| expected.contains(c)));
+		}
+	}
+
+	#[test]
+	fn simplification_keeps_neighbors_together() {
+		let simplified = Arcs::new(&regions()).simplify(0.1);
+
+		let west = simplified.multi_polygon(0);
+		let east = simplified.multi_polygon(1);
+
+		// The jog in the shared edge is gone on both sides.
+		for polygons in &[&west, &east] {
+			assert!(!polygons.0[0].exterior().0.iter().any(|c| c.x == 1.01));
+		}
+	}
+
+	#[test]
+	fn simplification_matches_douglas_peucker() {
+		use geo::{algorithm::simplify::Simplify, LineString};
+
+		let arcs = Arcs::new(&regions());
+
+		for tolerance in &[0.001, 0.005, 0.02, 0.1, 0.6] {
+			let simplified = arcs.simplify(*tolerance);
+
+			for (arc, expected) in simplified.arcs().iter().zip(arcs.arcs()) {
+				assert_eq!(arc, &LineString(expected.clone()).simplify(tolerance).0);
+			}
+
+			for idx in 0..2 {
+				assert_eq!(
+					arcs.simplified_multi_polygon(idx, Some(*tolerance)),
+					simplified.multi_polygon(idx)
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn collapsed_polygons_are_dropped() {
+		let simplified = Arcs::new(&regions()).simplify(10.0);
+
+		assert!(simplified.multi_polygon(0).0.is_empty());
+	}
+}
@@ -0,0 +1,197 @@
+use fnv::FnvHashMap;
+use geo::{
+	algorithm::{
+		bounding_rect::BoundingRect,
+		intersects::Intersects,
+		orient::{Direction, Orient},
+	},
+	Coordinate, LineString, MultiPolygon, Point, Polygon, Rect,
+};
+
+use super::{vertex_key, VertexKey};
+
+fn vertex(key: VertexKey) -> Coordinate<f64> {
+	Coordinate {
+		x: f64::from_bits(key.0),
+		y: f64::from_bits(key.1),
+	}
+}
+
+/// Twice the signed planar area of a ring; positive if counter-clockwise
+fn winding(ring: &LineString<f64>) -> f64 {
+	ring
+		.lines()
+		.map(|line| line.start.x * line.end.y - line.end.x * line.start.y)
+		.sum()
+}
+
+fn rect_contains(outer: &Rect<f64>, inner: &Rect<f64>) -> bool {
+	outer.min().x <= inner.min().x
+		&& outer.min().y <= inner.min().y
+		&& outer.max().x >= inner.max().x
+		&& outer.max().y >= inner.max().y
+}
+
+/// Union polygons that share boundary segments into their outline
+///
+/// Like [`Topology`](super::Topology), this relies on neighboring polygons
+/// having identical vertices along their shared boundaries, as they do in the
+/// TIGER/Line shapefiles; it is not a general polygon union.  Every segment
+/// that is traversed in both directions is interior and is dropped, and the
+/// rest are chained back into rings.  Clockwise rings are holes, and are
+/// placed in the smallest outer ring that surrounds them.
+pub fn dissolve<'a, I: IntoIterator<Item = &'a MultiPolygon<f64>>>(
+	regions: I,
+) -> MultiPolygon<f64> {
+	let mut segments: FnvHashMap<(VertexKey, VertexKey), usize> = FnvHashMap::default();
+
+	for polygons in regions {
+		for polygon in polygons {
+			let polygon: Polygon<f64> = polygon.orient(Direction::Default);
+
+			for ring in core::iter::once(polygon.exterior()).chain(polygon.interiors()) {
+				for line in ring.lines() {
+					let (a, b): (VertexKey, VertexKey) = (vertex_key(line.start), vertex_key(line.end));
+					if a == b {
+						continue;
+					}
+
+					match segments.get_mut(&(b, a)) {
+						Some(count) if *count > 1 => *count -= 1,
+						Some(_) => {
+							segments.remove(&(b, a));
+						}
+						None => *segments.entry((a, b)).or_insert(0) += 1,
+					}
+				}
+			}
+		}
+	}
+
+	let mut outgoing: FnvHashMap<VertexKey, Vec<VertexKey>> = FnvHashMap::default();
+	for ((a, b), count) in segments {
+		outgoing
+			.entry(a)
+			.or_default()
+			.extend(core::iter::repeat(b).take(count));
+	}
+
+	let mut starts: Vec<VertexKey> = outgoing.keys().copied().collect();
+	starts.sort_unstable();
+
+	let mut next = |from: VertexKey| -> Option<VertexKey> { outgoing.get_mut(&from)?.pop() };
+
+	let mut exteriors: Vec<(LineString<f64>, f64)> = Vec::new();
+	let mut holes: Vec<LineString<f64>> = Vec::new();
+
+	for start in starts {
+		while let Some(mut current) = next(start) {
+			let mut ring: Vec<Coordinate<f64>> = vec![vertex(start), vertex(current)];
+
+			while current != start {
+				match next(current) {
+					Some(vertex_key) => {
+						current = vertex_key;
+						ring.push(vertex(current));
+					}
+					None => break,
+				}
+			}
+
+			// A ring that doesn't close means the input wasn't consistent; there's
+			// nothing sensible to do with it.
+			if current != start || ring.len() < 4 {
+				continue;
+			}
+
+			let ring = LineString(ring);
+			let winding: f64 = winding(&ring);
+
+			if winding > 0.0 {
+				exteriors.push((ring, winding));
+			} else {
+				holes.push(ring);
+			}
+		}
+	}
+
+	let mut polygons: Vec<(Polygon<f64>, f64)> = exteriors
+		.into_iter()
+		.map(|(ring, winding)| (Polygon::new(ring, Vec::new()), winding))
+		.collect();
+	let bounds: Vec<Option<Rect<f64>>> = polygons
+		.iter()
+		.map(|(polygon, _)| polygon.bounding_rect())
+		.collect();
+
+	for hole in holes {
+		let (hole_bounds, point): (Rect<f64>, Point<f64>) = match (hole.bounding_rect(), hole.0.first())
+		{
+			(Some(bounds), Some(point)) => (bounds, Point(*point)),
+			_ => continue,
+		};
+
+		let container: Option<usize> = polygons
+			.iter()
+			.enumerate()
+			.filter(|(idx, (polygon, _))| {
+				bounds[*idx].is_some_and(|bounds| rect_contains(&bounds, &hole_bounds))
+					&& polygon.intersects(&point)
+			})
+			.min_by(|(_, (_, a)), (_, (_, b))| a.partial_cmp(b).unwrap_or(core::cmp::Ordering::Equal))
+			.map(|(idx, _)| idx);
+
+		if let Some(idx) = container {
+			polygons[idx].0.interiors_push(hole);
+		}
+	}
+
+	MultiPolygon(polygons.into_iter().map(|(polygon, _)| polygon).collect())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::dissolve;
+	use geo::{algorithm::area::Area, polygon, MultiPolygon};
+
+	fn square(x: f64, y: f64) -> MultiPolygon<f64> {
+		MultiPolygon(vec![polygon![
+			(x: x, y: y),
+			(x: x + 1.0, y: y),
+			(x: x + 1.0, y: y + 1.0),
+			(x: x, y: y + 1.0),
+			(x: x, y: y),
+		]])
+	}
+
+	#[test]
+	fn neighbors_merge() {
+		let outline = dissolve(&[square(0.0, 0.0), square(1.0, 0.0), square(0.0, 1.0)]);
+
+		assert_eq!(outline.0.len(), 1);
+		assert_eq!(outline.0[0].interiors().len(), 0);
+		assert!((outline.unsigned_area() - 3.0).abs() < 1e-9);
+	}
+
+	#[test]
+	fn separate_pieces_stay_separate() {
+		let outline = dissolve(&[square(0.0, 0.0), square(5.0, 5.0)]);
+
+		assert_eq!(outline.0.len(), 2);
+	}
+
+	#[test]
+	fn holes_are_preserved() {
+		let squares: Vec<MultiPolygon<f64>> = (0..3)
+			.flat_map(|x| (0..3).map(move |y| (x, y)))
+			.filter(|&(x, y)| (x, y) != (1, 1))
+			.map(|(x, y)| square(f64::from(x), f64::from(y)))
+			.collect();
+
+		let outline = dissolve(&squares);
+
+		assert_eq!(outline.0.len(), 1);
+		assert_eq!(outline.0[0].interiors().len(), 1);
+		assert!((outline.unsigned_area() - 8.0).abs() < 1e-9);
+	}
+}
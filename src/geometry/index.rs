@@ -0,0 +1,182 @@
+use geo::{
+	algorithm::{bounding_rect::BoundingRect, intersects::Intersects},
+	Coordinate, Point, Rect,
+};
+use rstar::{RTree, RTreeObject, AABB};
+
+use super::Region;
+
+/// The bounding box of a feature, by the feature's index
+struct Entry {
+	idx: usize,
+	envelope: AABB<[f64; 2]>,
+}
+
+impl RTreeObject for Entry {
+	type Envelope = AABB<[f64; 2]>;
+
+	fn envelope(&self) -> Self::Envelope {
+		self.envelope
+	}
+}
+
+/// An R-tree of the bounding boxes of some features, for finding those in an
+/// area without looking at all of them
+pub struct SpatialIndex {
+	tree: RTree<Entry>,
+}
+
+impl core::fmt::Debug for SpatialIndex {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("SpatialIndex")
+			.field("len", &self.tree.size())
+			.finish()
+	}
+}
+
+impl SpatialIndex {
+	pub fn new(regions: &[Region]) -> Self {
+		let entries: Vec<Entry> = regions
+			.iter()
+			.enumerate()
+			.filter_map(|(idx, (_, polygons))| {
+				let bounds: Rect<f64> = polygons.bounding_rect()?;
+
+				Some(Entry {
+					idx,
+					envelope: AABB::from_corners(
+						[bounds.min().x, bounds.min().y],
+						[bounds.max().x, bounds.max().y],
+					),
+				})
+			})
+			.collect();
+
+		Self {
+			tree: RTree::bulk_load(entries),
+		}
+	}
+
+	/// Roughly how many bytes the index takes up: its entries, and about as
+	/// much again for the nodes of the tree
+	pub fn size(&self) -> usize {
+		2 * self.tree.size() * core::mem::size_of::<Entry>()
+	}
+
+	/// The indices of the features whose bounding boxes intersect `bounds`, in
+	/// no particular order
+	pub fn candidates(&self, bounds: Rect<f64>) -> impl Iterator<Item = usize> + '_ {
+		let envelope = AABB::from_corners(
+			[bounds.min().x, bounds.min().y],
+			[bounds.max().x, bounds.max().y],
+		);
+
+		self
+			.tree
+			.locate_in_envelope_intersecting(&envelope)
+			.map(|entry| entry.idx)
+	}
+
+	/// The indices of the features that intersect `bounds`, in order
+	///
+	/// `regions` must be the features the index was built from.
+	pub fn intersecting(&self, regions: &[Region], bounds: Rect<f64>) -> Vec<usize> {
+		let mut indices: Vec<usize> = self
+			.candidates(bounds)
+			.filter(|idx| regions[*idx].1.intersects(&bounds))
+			.collect();
+
+		indices.sort_unstable();
+		indices
+	}
+
+	/// The index of the feature containing a point
+	///
+	/// Points on a boundary between features are in all of them; the first
+	/// such feature is chosen.  `regions` must be the features the index was
+	/// built from.
+	pub fn containing(&self, regions: &[Region], c: Coordinate<f64>) -> Option<usize> {
+		let point: Point<f64> = Point(c);
+
+		self
+			.candidates(Rect::new(c, c))
+			.filter(|idx| regions[*idx].1.intersects(&point))
+			.min()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::SpatialIndex;
+	use geo::{polygon, Coordinate, MultiPolygon, Rect};
+
+	fn regions() -> Vec<(String, MultiPolygon<f64>)> {
+		vec![
+			(
+				"square".to_string(),
+				MultiPolygon(vec![polygon![
+					(x: 0.0, y: 0.0),
+					(x: 1.0, y: 0.0),
+					(x: 1.0, y: 1.0),
+					(x: 0.0, y: 1.0),
+					(x: 0.0, y: 0.0),
+				]]),
+			),
+			(
+				"triangle".to_string(),
+				MultiPolygon(vec![polygon![
+					(x: 2.0, y: 0.0),
+					(x: 4.0, y: 0.0),
+					(x: 2.0, y: 2.0),
+					(x: 2.0, y: 0.0),
+				]]),
+			),
+		]
+	}
+
+	fn rect(min: (f64, f64), max: (f64, f64)) -> Rect<f64> {
+		Rect::new(
+			Coordinate { x: min.0, y: min.1 },
+			Coordinate { x: max.0, y: max.1 },
+		)
+	}
+
+	#[test]
+	fn finds_intersecting_features() {
+		let regions = regions();
+		let index = SpatialIndex::new(&regions);
+
+		assert_eq!(
+			index.intersecting(&regions, rect((0.5, 0.5), (2.5, 0.5))),
+			vec![0, 1]
+		);
+		assert_eq!(
+			index.intersecting(&regions, rect((5.0, 5.0), (6.0, 6.0))),
+			Vec::<usize>::new()
+		);
+	}
+
+	#[test]
+	fn finds_containing_feature() {
+		let regions = regions();
+		let index = SpatialIndex::new(&regions);
+		let at = |x: f64, y: f64| index.containing(&regions, Coordinate { x, y });
+
+		assert_eq!(at(0.5, 0.5), Some(0));
+		assert_eq!(at(2.5, 0.5), Some(1));
+		assert_eq!(at(3.5, 1.5), None);
+		assert_eq!(at(-1.0, 0.5), None);
+	}
+
+	#[test]
+	fn bounding_boxes_are_only_candidates() {
+		let regions = regions();
+		let index = SpatialIndex::new(&regions);
+
+		// Inside the triangle's bounding box, but outside the triangle
+		let corner = rect((3.5, 1.5), (3.9, 1.9));
+
+		assert_eq!(index.candidates(corner).collect::<Vec<_>>(), vec![1]);
+		assert!(index.intersecting(&regions, corner).is_empty());
+	}
+}
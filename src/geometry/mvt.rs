@@ -0,0 +1,270 @@
+//! A minimal encoder for [Mapbox Vector Tiles][spec] containing polygons
+//!
+//! [spec]: https://github.com/mapbox/vector-tile-spec/tree/master/2.1
+
+use fnv::FnvHashMap;
+
+/// The number of units along each side of a tile
+pub const EXTENT: u32 = 4096;
+
+const VERSION: u32 = 2;
+
+const WIRE_VARINT: u32 = 0;
+const WIRE_FIXED64: u32 = 1;
+const WIRE_LENGTH_DELIMITED: u32 = 2;
+
+const GEOMETRY_POLYGON: u32 = 3;
+
+const COMMAND_MOVE_TO: u32 = 1;
+const COMMAND_LINE_TO: u32 = 2;
+const COMMAND_CLOSE_PATH: u32 = 7;
+
+/// The value of a feature's property
+#[derive(Clone, Debug, PartialEq)]
+pub enum PropertyValue {
+	String(String),
+	Double(f64),
+	Uint(u64),
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+	while value >= 0x80 {
+		buf.push((value as u8 & 0x7f) | 0x80);
+		value >>= 7;
+	}
+	buf.push(value as u8);
+}
+
+fn write_key(buf: &mut Vec<u8>, field: u32, wire_type: u32) {
+	write_varint(buf, u64::from(field << 3 | wire_type));
+}
+
+fn write_bytes(buf: &mut Vec<u8>, field: u32, bytes: &[u8]) {
+	write_key(buf, field, WIRE_LENGTH_DELIMITED);
+	write_varint(buf, bytes.len() as u64);
+	buf.extend_from_slice(bytes);
+}
+
+fn write_packed(buf: &mut Vec<u8>, field: u32, values: &[u32]) {
+	let mut packed: Vec<u8> = Vec::with_capacity(values.len());
+	for value in values {
+		write_varint(&mut packed, u64::from(*value));
+	}
+	write_bytes(buf, field, &packed);
+}
+
+fn zigzag(value: i32) -> u32 {
+	((value << 1) ^ (value >> 31)) as u32
+}
+
+fn command(id: u32, count: u32) -> u32 {
+	(id & 0x7) | (count << 3)
+}
+
+impl PropertyValue {
+	fn encode(&self) -> Vec<u8> {
+		let mut buf: Vec<u8> = Vec::new();
+
+		match self {
+			Self::String(value) => write_bytes(&mut buf, 1, value.as_bytes()),
+			Self::Double(value) => {
+				write_key(&mut buf, 3, WIRE_FIXED64);
+				buf.extend_from_slice(&value.to_le_bytes());
+			}
+			Self::Uint(value) => {
+				write_key(&mut buf, 5, WIRE_VARINT);
+				write_varint(&mut buf, *value);
+			}
+		}
+
+		buf
+	}
+}
+
+/// A ring of a polygon in tile coordinates, without its closing point
+///
+/// Per the specification, exterior rings must have a positive area and
+/// interior rings a negative one when measured with the y axis pointing down.
+pub type Ring = Vec<(i32, i32)>;
+
+/// A named layer of a tile
+#[derive(Debug)]
+pub struct Layer {
+	name: String,
+	keys: Vec<String>,
+	key_index: FnvHashMap<String, u32>,
+	values: Vec<Vec<u8>>,
+	value_index: FnvHashMap<Vec<u8>, u32>,
+	features: Vec<Vec<u8>>,
+}
+
+impl Layer {
+	pub fn new<S: Into<String>>(name: S) -> Self {
+		Self {
+			name: name.into(),
+			keys: Vec::new(),
+			key_index: FnvHashMap::default(),
+			values: Vec::new(),
+			value_index: FnvHashMap::default(),
+			features: Vec::new(),
+		}
+	}
+
+	pub fn len(&self) -> usize {
+		self.features.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.features.is_empty()
+	}
+
+	fn key(&mut self, key: &str) -> u32 {
+		if let Some(idx) = self.key_index.get(key) {
+			return *idx;
+		}
+
+		let idx: u32 = self.keys.len() as u32;
+		self.keys.push(key.to_string());
+		self.key_index.insert(key.to_string(), idx);
+		idx
+	}
+
+	fn value(&mut self, value: &PropertyValue) -> u32 {
+		let encoded: Vec<u8> = value.encode();
+
+		if let Some(idx) = self.value_index.get(&encoded) {
+			return *idx;
+		}
+
+		let idx: u32 = self.values.len() as u32;
+		self.values.push(encoded.clone());
+		self.value_index.insert(encoded, idx);
+		idx
+	}
+
+	/// Add a polygon feature made of `rings`
+	///
+	/// Each exterior ring is followed by its interior rings.  Rings with fewer
+	/// than three points are skipped.
+	pub fn add_polygon(&mut self, id: u64, rings: &[Ring], properties: &[(&str, PropertyValue)]) {
+		let mut geometry: Vec<u32> = Vec::new();
+		let mut cursor: (i32, i32) = (0, 0);
+
+		for ring in rings.iter().filter(|ring| ring.len() >= 3) {
+			let mut delta = |point: (i32, i32)| -> [u32; 2] {
+				let delta = [zigzag(point.0 - cursor.0), zigzag(point.1 - cursor.1)];
+				cursor = point;
+				delta
+			};
+
+			geometry.push(command(COMMAND_MOVE_TO, 1));
+			geometry.extend_from_slice(&delta(ring[0]));
+			geometry.push(command(COMMAND_LINE_TO, (ring.len() - 1) as u32));
+			for point in &ring[1..] {
+				geometry.extend_from_slice(&delta(*point));
+			}
+			geometry.push(command(COMMAND_CLOSE_PATH, 1));
+		}
+
+		if geometry.is_empty() {
+			return;
+		}
+
+		let mut tags: Vec<u32> = Vec::with_capacity(properties.len() * 2);
+		for (key, value) in properties {
+			tags.push(self.key(key));
+			tags.push(self.value(value));
+		}
+
+		let mut feature: Vec<u8> = Vec::new();
+		write_key(&mut feature, 1, WIRE_VARINT);
+		write_varint(&mut feature, id);
+		write_packed(&mut feature, 2, &tags);
+		write_key(&mut feature, 3, WIRE_VARINT);
+		write_varint(&mut feature, u64::from(GEOMETRY_POLYGON));
+		write_packed(&mut feature, 4, &geometry);
+
+		self.features.push(feature);
+	}
+
+	fn encode(&self) -> Vec<u8> {
+		let mut buf: Vec<u8> = Vec::new();
+
+		write_key(&mut buf, 15, WIRE_VARINT);
+		write_varint(&mut buf, u64::from(VERSION));
+		write_bytes(&mut buf, 1, self.name.as_bytes());
+		for feature in &self.features {
+			write_bytes(&mut buf, 2, feature);
+		}
+		for key in &self.keys {
+			write_bytes(&mut buf, 3, key.as_bytes());
+		}
+		for value in &self.values {
+			write_bytes(&mut buf, 4, value);
+		}
+		write_key(&mut buf, 5, WIRE_VARINT);
+		write_varint(&mut buf, u64::from(EXTENT));
+
+		buf
+	}
+}
+
+/// Encode a tile made of some layers
+pub fn encode(layers: &[Layer]) -> Vec<u8> {
+	let mut buf: Vec<u8> = Vec::new();
+
+	for layer in layers.iter().filter(|layer| !layer.is_empty()) {
+		write_bytes(&mut buf, 3, &layer.encode());
+	}
+
+	buf
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{encode, zigzag, Layer, PropertyValue};
+
+	#[test]
+	fn zigzag_interleaves_signs() {
+		assert_eq!(zigzag(0), 0);
+		assert_eq!(zigzag(-1), 1);
+		assert_eq!(zigzag(1), 2);
+		assert_eq!(zigzag(-2), 3);
+	}
+
+	#[test]
+	fn polygon_matches_specification() {
+		// The polygon example from section 4.3.5.2 of the specification
+		let mut layer = Layer::new("blocks");
+		layer.add_polygon(
+			1,
+			&[vec![(3, 6), (8, 12), (20, 34)]],
+			&[("GEOID", PropertyValue::String("a".to_string()))],
+		);
+
+		let tile: Vec<u8> = encode(&[layer]);
+
+		let geometry: &[u8] = &[9, 6, 12, 18, 10, 12, 24, 44, 15];
+		assert!(tile
+			.windows(geometry.len())
+			.any(|window| window == geometry));
+	}
+
+	#[test]
+	fn empty_layers_are_omitted() {
+		assert!(encode(&[Layer::new("blocks")]).is_empty());
+	}
+
+	#[test]
+	fn keys_and_values_are_shared() {
+		let mut layer = Layer::new("blocks");
+		let rings = [vec![(0, 0), (1, 0), (1, 1)]];
+
+		layer.add_polygon(1, &rings, &[("population", PropertyValue::Uint(5))]);
+		layer.add_polygon(2, &rings, &[("population", PropertyValue::Uint(5))]);
+
+		assert_eq!(layer.len(), 2);
+		assert_eq!(layer.keys.len(), 1);
+		assert_eq!(layer.values.len(), 1);
+	}
+}
@@ -0,0 +1,454 @@
+use fnv::FnvHashMap;
+use geo::{algorithm::simplify::Simplify, Coordinate, LineString, MultiPolygon};
+
+use super::{
+	mvt::{self, Layer, PropertyValue, Ring, EXTENT},
+	Region,
+};
+
+/// The deepest zoom level tiles are served at
+pub const MAX_ZOOM: u8 = 22;
+
+/// The deepest zoom level of the cells features are indexed by
+const INDEX_ZOOM: u8 = 12;
+
+/// Features covering more of a level's cells than this are checked for every
+/// tile at that level and deeper
+const LARGE_FEATURE_CELLS: u64 = 64;
+
+/// How far geometry extends past the edges of a tile, in tile units, so that
+/// strokes along the edges render cleanly
+const BUFFER: f64 = 64.0;
+
+/// How far simplification may move a line, in tile units
+const TOLERANCE: f64 = 1.0;
+
+/// The latitude at which Web Mercator is cut off, making the world square
+const MAX_LATITUDE: f64 = 85.051_128_779_806_59;
+
+/// Project a coordinate to Web Mercator, scaled so the world spans `[0, 1]`
+/// on both axes with `y` increasing southwards
+pub fn mercator(c: Coordinate<f64>) -> (f64, f64) {
+	let latitude: f64 = c.y.clamp(-MAX_LATITUDE, MAX_LATITUDE).to_radians();
+
+	let x: f64 = (c.x + 180.0) / 360.0;
+	let y: f64 = (1.0 - (latitude.tan() + 1.0 / latitude.cos()).ln() / core::f64::consts::PI) / 2.0;
+
+	(x, y)
+}
+
+/// A tile in the standard `z/x/y` scheme
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TileId {
+	z: u8,
+	x: u32,
+	y: u32,
+}
+
+impl TileId {
+	/// Returns `None` if the tile doesn't exist
+	pub fn new(z: u8, x: u32, y: u32) -> Option<Self> {
+		let tiles: u64 = 1 << z.min(63);
+
+		if z <= MAX_ZOOM && u64::from(x) < tiles && u64::from(y) < tiles {
+			Some(Self { z, x, y })
+		} else {
+			None
+		}
+	}
+
+	fn scale(&self) -> f64 {
+		f64::from(1_u32 << self.z)
+	}
+
+	/// The area covered by the tile in projected units, including its buffer
+	fn bounds(&self) -> Bounds {
+		let buffer: f64 = BUFFER / f64::from(EXTENT) / self.scale();
+
+		Bounds {
+			min: (
+				f64::from(self.x) / self.scale() - buffer,
+				f64::from(self.y) / self.scale() - buffer,
+			),
+			max: (
+				f64::from(self.x + 1) / self.scale() + buffer,
+				f64::from(self.y + 1) / self.scale() + buffer,
+			),
+		}
+	}
+
+	/// Convert projected units to tile units
+	fn project(self, (x, y): (f64, f64)) -> (f64, f64) {
+		(
+			(x * self.scale() - f64::from(self.x)) * f64::from(EXTENT),
+			(y * self.scale() - f64::from(self.y)) * f64::from(EXTENT),
+		)
+	}
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Bounds {
+	min: (f64, f64),
+	max: (f64, f64),
+}
+
+impl Bounds {
+	fn of(polygons: &MultiPolygon<f64>) -> Option<Self> {
+		polygons
+			.iter()
+			.flat_map(|polygon| polygon.exterior().0.iter())
+			.map(|c| mercator(*c))
+			.fold(None, |bounds: Option<Bounds>, (x, y)| {
+				Some(match bounds {
+					Some(bounds) => Bounds {
+						min: (bounds.min.0.min(x), bounds.min.1.min(y)),
+						max: (bounds.max.0.max(x), bounds.max.1.max(y)),
+					},
+					None => Bounds {
+						min: (x, y),
+						max: (x, y),
+					},
+				})
+			})
+	}
+
+	fn intersects(&self, other: &Bounds) -> bool {
+		self.min.0 <= other.max.0
+			&& self.max.0 >= other.min.0
+			&& self.min.1 <= other.max.1
+			&& self.max.1 >= other.min.1
+	}
+
+	/// The range of index cells covered at a zoom level, inclusive
+	fn cells(&self, zoom: u8) -> ((u32, u32), (u32, u32)) {
+		let cells: f64 = f64::from(1_u32 << zoom);
+		let cell = |v: f64| (v * cells).max(0.0).min(cells - 1.0) as u32;
+
+		(
+			(cell(self.min.0), cell(self.min.1)),
+			(cell(self.max.0), cell(self.max.1)),
+		)
+	}
+}
+
+/// A pyramid of grid indices of where features are, for finding those in a
+/// tile
+///
+/// Each zoom level down to [`INDEX_ZOOM`] has its own grid of cells the size
+/// of its tiles, so a tile only looks at the features in the few cells it
+/// covers.  Deeper tiles use the deepest grid.
+#[derive(Debug)]
+pub struct TileIndex {
+	bounds: Vec<Option<Bounds>>,
+	/// The cells of each zoom level, from 0 to [`INDEX_ZOOM`]
+	levels: Vec<FnvHashMap<(u32, u32), Vec<usize>>>,
+	/// Features too large to be indexed from some zoom level on, with that
+	/// level
+	large: Vec<(u8, usize)>,
+}
+
+impl TileIndex {
+	pub fn new(regions: &[Region]) -> Self {
+		let bounds: Vec<Option<Bounds>> = regions
+			.iter()
+			.map(|(_, polygons)| Bounds::of(polygons))
+			.collect();
+
+		let mut levels: Vec<FnvHashMap<(u32, u32), Vec<usize>>> =
+			(0..=INDEX_ZOOM).map(|_| FnvHashMap::default()).collect();
+		let mut large: Vec<(u8, usize)> = Vec::new();
+
+		for (idx, bounds) in bounds.iter().enumerate() {
+			let bounds: &Bounds = match bounds {
+				Some(bounds) => bounds,
+				None => continue,
+			};
+
+			for (zoom, cells) in (0..=INDEX_ZOOM).zip(levels.iter_mut()) {
+				let ((x0, y0), (x1, y1)) = bounds.cells(zoom);
+
+				if u64::from(x1 - x0 + 1) * u64::from(y1 - y0 + 1) > LARGE_FEATURE_CELLS {
+					large.push((zoom, idx));
+					break;
+				}
+
+				for x in x0..=x1 {
+					for y in y0..=y1 {
+						cells.entry((x, y)).or_default().push(idx);
+					}
+				}
+			}
+		}
+
+		Self {
+			bounds,
+			levels,
+			large,
+		}
+	}
+
+	/// Roughly how many bytes the index takes up
+	pub fn size(&self) -> usize {
+		let cells: usize = self
+			.levels
+			.iter()
+			.map(|cells| {
+				cells.len() * core::mem::size_of::<((u32, u32), Vec<usize>)>()
+					+ cells.values().map(Vec::len).sum::<usize>() * core::mem::size_of::<usize>()
+			})
+			.sum();
+
+		self.bounds.len() * core::mem::size_of::<Option<Bounds>>()
+			+ self.large.len() * core::mem::size_of::<(u8, usize)>()
+			+ cells
+	}
+
+	/// The features that may appear in a tile, in ascending order
+	pub fn features_in(&self, tile: TileId) -> Vec<usize> {
+		let bounds: Bounds = tile.bounds();
+		let overlaps = |idx: &usize| {
+			self.bounds[*idx]
+				.as_ref()
+				.is_some_and(|feature| feature.intersects(&bounds))
+		};
+
+		let zoom: u8 = tile.z.min(INDEX_ZOOM);
+		let cells: &FnvHashMap<(u32, u32), Vec<usize>> = &self.levels[usize::from(zoom)];
+		let ((x0, y0), (x1, y1)) = bounds.cells(zoom);
+
+		let mut features: Vec<usize> = (x0..=x1)
+			.flat_map(|x| (y0..=y1).map(move |y| (x, y)))
+			.filter_map(|cell| cells.get(&cell))
+			.flatten()
+			.copied()
+			.chain(
+				self
+					.large
+					.iter()
+					.filter(|(from, _)| *from <= zoom)
+					.map(|(_, idx)| *idx),
+			)
+			.filter(overlaps)
+			.collect();
+
+		features.sort_unstable();
+		features.dedup();
+		features
+	}
+}
+
+/// Clip a ring against an axis-aligned edge (Sutherland-Hodgman)
+fn clip_edge<F: Fn((f64, f64)) -> bool, G: Fn((f64, f64), (f64, f64)) -> (f64, f64)>(
+	points: &[(f64, f64)],
+	inside: F,
+	intersection: G,
+) -> Vec<(f64, f64)> {
+	let mut clipped: Vec<(f64, f64)> = Vec::with_capacity(points.len());
+
+	for (i, current) in points.iter().enumerate() {
+		let previous = points[(i + points.len() - 1) % points.len()];
+
+		match (inside(previous), inside(*current)) {
+			(true, true) => clipped.push(*current),
+			(true, false) => clipped.push(intersection(previous, *current)),
+			(false, true) => {
+				clipped.push(intersection(previous, *current));
+				clipped.push(*current);
+			}
+			(false, false) => {}
+		}
+	}
+
+	clipped
+}
+
+/// Clip an open ring (no repeated closing point) to `[min, max]` squared
+fn clip(points: Vec<(f64, f64)>, min: f64, max: f64) -> Vec<(f64, f64)> {
+	let at_x =
+		|x: f64| move |a: (f64, f64), b: (f64, f64)| (x, a.1 + (b.1 - a.1) * (x - a.0) / (b.0 - a.0));
+	let at_y =
+		|y: f64| move |a: (f64, f64), b: (f64, f64)| (a.0 + (b.0 - a.0) * (y - a.1) / (b.1 - a.1), y);
+
+	let points = clip_edge(&points, |p| p.0 >= min, at_x(min));
+	let points = clip_edge(&points, |p| p.0 <= max, at_x(max));
+	let points = clip_edge(&points, |p| p.1 >= min, at_y(min));
+	clip_edge(&points, |p| p.1 <= max, at_y(max))
+}
+
+/// Twice the signed area of a ring in tile units, positive if clockwise on
+/// screen (as exterior rings must be)
+fn winding(ring: &[(i32, i32)]) -> i64 {
+	ring
+		.iter()
+		.zip(ring.iter().cycle().skip(1))
+		.map(|(a, b)| i64::from(a.0) * i64::from(b.1) - i64::from(b.0) * i64::from(a.1))
+		.sum()
+}
+
+/// Project, clip, simplify and quantize a ring into a tile
+fn tile_ring(ring: &LineString<f64>, tile: TileId, exterior: bool) -> Option<Ring> {
+	let mut points: Vec<(f64, f64)> = ring.0.iter().map(|c| tile.project(mercator(*c))).collect();
+	if points.len() > 1 && points.first() == points.last() {
+		points.pop();
+	}
+
+	let mut points: Vec<(f64, f64)> = clip(points, -BUFFER, f64::from(EXTENT) + BUFFER);
+	if points.len() < 3 {
+		return None;
+	}
+	points.push(points[0]);
+
+	let simplified: LineString<f64> = LineString::from(points).simplify(&TOLERANCE);
+
+	let mut quantized: Ring = Vec::with_capacity(simplified.0.len());
+	for c in simplified.0 {
+		let point: (i32, i32) = (c.x.round() as i32, c.y.round() as i32);
+		if quantized.last() != Some(&point) {
+			quantized.push(point);
+		}
+	}
+	if quantized.len() > 1 && quantized.first() == quantized.last() {
+		quantized.pop();
+	}
+
+	let winding: i64 = winding(&quantized);
+	if quantized.len() < 3 || winding == 0 {
+		return None;
+	}
+
+	if (winding > 0) != exterior {
+		quantized.reverse();
+	}
+
+	Some(quantized)
+}
+
+/// Encode the features of some regions that appear in a tile
+///
+/// Each feature gets its GeoID as a `GEOID` property, plus whatever
+/// `properties` gives for it.
+pub fn render<F>(
+	regions: &[Region],
+	index: &TileIndex,
+	tile: TileId,
+	layer: &str,
+	mut properties: F,
+) -> Vec<u8>
+where
+	F: FnMut(&str) -> Vec<(&'static str, PropertyValue)>,
+{
+	let mut layer = Layer::new(layer);
+
+	for idx in index.features_in(tile) {
+		let (geoid, polygons) = &regions[idx];
+
+		let mut rings: Vec<Ring> = Vec::new();
+		for polygon in polygons {
+			let exterior: Ring = match tile_ring(polygon.exterior(), tile, true) {
+				Some(ring) => ring,
+				None => continue,
+			};
+
+			rings.push(exterior);
+			rings.extend(
+				polygon
+					.interiors()
+					.iter()
+					.filter_map(|interior| tile_ring(interior, tile, false)),
+			);
+		}
+
+		if rings.is_empty() {
+			continue;
+		}
+
+		let mut feature_properties: Vec<(&str, PropertyValue)> =
+			vec![("GEOID", PropertyValue::String(geoid.clone()))];
+		feature_properties.extend(properties(geoid));
+
+		layer.add_polygon(idx as u64 + 1, &rings, &feature_properties);
+	}
+
+	mvt::encode(&[layer])
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{mercator, render, TileId, TileIndex};
+	use crate::geometry::mvt::PropertyValue;
+	use geo::{polygon, Coordinate, MultiPolygon};
+
+	fn regions() -> Vec<(String, MultiPolygon<f64>)> {
+		vec![
+			(
+				"west".to_string(),
+				MultiPolygon(vec![polygon![
+					(x: -10.0, y: 10.0), (x: -5.0, y: 10.0), (x: -5.0, y: 5.0), (x: -10.0, y: 5.0)
+				]]),
+			),
+			(
+				"east".to_string(),
+				MultiPolygon(vec![polygon![
+					(x: 5.0, y: 5.0), (x: 10.0, y: 5.0), (x: 10.0, y: 10.0), (x: 5.0, y: 10.0)
+				]]),
+			),
+		]
+	}
+
+	#[test]
+	fn mercator_centers_the_origin() {
+		assert_eq!(mercator(Coordinate { x: 0.0, y: 0.0 }), (0.5, 0.5));
+
+		let (_, north) = mercator(Coordinate { x: 0.0, y: 89.0 });
+		assert!(north.abs() < 1e-9);
+	}
+
+	#[test]
+	fn tiles_must_exist() {
+		assert!(TileId::new(0, 0, 0).is_some());
+		assert!(TileId::new(1, 2, 0).is_none());
+		assert!(TileId::new(23, 0, 0).is_none());
+	}
+
+	#[test]
+	fn features_are_found_by_tile() {
+		let regions = regions();
+		let index = TileIndex::new(&regions);
+
+		assert_eq!(index.features_in(TileId::new(0, 0, 0).unwrap()), vec![0, 1]);
+		assert_eq!(index.features_in(TileId::new(1, 0, 0).unwrap()), vec![0]);
+		assert_eq!(index.features_in(TileId::new(1, 1, 0).unwrap()), vec![1]);
+		assert_eq!(
+			index.features_in(TileId::new(1, 1, 1).unwrap()),
+			Vec::<usize>::new()
+		);
+
+		// (7, 7) is in tile 5/16/15 and 12/2127/1968
+		assert_eq!(index.features_in(TileId::new(5, 16, 15).unwrap()), vec![1]);
+		assert_eq!(
+			index.features_in(TileId::new(12, 2127, 1968).unwrap()),
+			vec![1]
+		);
+	}
+
+	#[test]
+	fn rendered_tiles_carry_properties() {
+		let regions = regions();
+		let index = TileIndex::new(&regions);
+
+		let tile: Vec<u8> = render(
+			&regions,
+			&index,
+			TileId::new(1, 1, 0).unwrap(),
+			"blocks",
+			|_| vec![("population", PropertyValue::Uint(7))],
+		);
+
+		let contains = |needle: &[u8]| tile.windows(needle.len()).any(|window| window == needle);
+		assert!(contains(b"blocks"));
+		assert!(contains(b"GEOID"));
+		assert!(contains(b"east"));
+		assert!(!contains(b"west"));
+		assert!(contains(b"population"));
+	}
+}
@@ -0,0 +1,291 @@
+//! An encoder for quantized [TopoJSON][spec] topologies made of polygons
+//!
+//! [spec]: https://github.com/topojson/topojson-specification
+
+use serde::Serialize;
+
+use super::{ArcPolygon, Arcs};
+
+/// A quantized TopoJSON topology
+#[derive(Debug, Serialize)]
+pub struct Topology {
+	#[serde(rename = "type")]
+	ty: &'static str,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	bbox: Option<[f64; 4]>,
+	transform: Transform,
+	objects: std::collections::BTreeMap<String, GeometryCollection>,
+	arcs: Vec<Vec<[i64; 2]>>,
+}
+
+/// The transform that maps quantized positions back to coordinates
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Transform {
+	pub scale: [f64; 2],
+	pub translate: [f64; 2],
+}
+
+#[derive(Debug, Serialize)]
+struct GeometryCollection {
+	#[serde(rename = "type")]
+	ty: &'static str,
+	geometries: Vec<Geometry>,
+}
+
+#[derive(Debug, Serialize)]
+struct Geometry {
+	#[serde(rename = "type")]
+	ty: &'static str,
+	id: String,
+	arcs: Vec<ArcPolygon>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	properties: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+impl Transform {
+	/// A transform onto a `quantization` by `quantization` grid spanning
+	/// `bbox`, in the order `[min x, min y, max x, max y]`
+	fn new(bbox: [f64; 4], quantization: u32) -> Self {
+		let steps: f64 = f64::from(quantization.max(2) - 1);
+		let scale = |min: f64, max: f64| if max > min { (max - min) / steps } else { 1.0 };
+
+		Self {
+			scale: [scale(bbox[0], bbox[2]), scale(bbox[1], bbox[3])],
+			translate: [bbox[0], bbox[1]],
+		}
+	}
+
+	fn quantize(&self, x: f64, y: f64) -> [i64; 2] {
+		[
+			((x - self.translate[0]) / self.scale[0]).round() as i64,
+			((y - self.translate[1]) / self.scale[1]).round() as i64,
+		]
+	}
+}
+
+impl Topology {
+	/// Encode the features of `arcs` as a single object named `object`
+	///
+	/// The arcs are simplified to `tolerance` as they're encoded, if it's
+	/// given.  Arc positions are snapped to a grid `quantization` steps to a
+	/// side and delta-encoded.  Features that have no polygons left are
+	/// omitted.  `properties` is called with the index of each feature.
+	pub fn new<F>(
+		arcs: &Arcs,
+		tolerance: Option<f64>,
+		object: &str,
+		quantization: u32,
+		mut properties: F,
+	) -> Self
+	where
+		F: FnMut(usize) -> Option<serde_json::Map<String, serde_json::Value>>,
+	{
+		let simplified = |idx: usize| arcs.simplified_arc(idx, tolerance);
+
+		let bbox: Option<[f64; 4]> =
+			(0..arcs.arcs().len())
+				.flat_map(simplified)
+				.fold(None, |bbox, c| {
+					Some(match bbox {
+						None => [c.x, c.y, c.x, c.y],
+						Some([x0, y0, x1, y1]) => [x0.min(c.x), y0.min(c.y), x1.max(c.x), y1.max(c.y)],
+					})
+				});
+
+		let transform: Transform = Transform::new(bbox.unwrap_or([0.0; 4]), quantization);
+
+		let encoded: Vec<Vec<[i64; 2]>> = (0..arcs.arcs().len())
+			.map(|idx| {
+				let arc: Vec<&geo::Coordinate<f64>> = simplified(idx).collect();
+				let mut previous: Option<[i64; 2]> = None;
+				let mut deltas: Vec<[i64; 2]> = Vec::with_capacity(arc.len());
+
+				for (idx, c) in arc.iter().enumerate() {
+					let point: [i64; 2] = transform.quantize(c.x, c.y);
+
+					// Drop points that snap onto the one before, but keep both ends.
+					let delta: [i64; 2] = match previous {
+						Some(previous) => [point[0] - previous[0], point[1] - previous[1]],
+						None => point,
+					};
+					if previous.is_some() && delta == [0, 0] && idx + 1 < arc.len() {
+						continue;
+					}
+
+					deltas.push(delta);
+					previous = Some(point);
+				}
+
+				deltas
+			})
+			.collect();
+
+		let geometries: Vec<Geometry> = arcs
+			.geoids()
+			.iter()
+			.enumerate()
+			.filter_map(|(idx, geoid)| {
+				let polygons: Vec<ArcPolygon> = arcs.simplified_polygons(idx, tolerance).collect();
+
+				if polygons.is_empty() {
+					None
+				} else {
+					Some(Geometry {
+						ty: "MultiPolygon",
+						id: geoid.clone(),
+						arcs: polygons,
+						properties: properties(idx),
+					})
+				}
+			})
+			.collect();
+
+		let mut objects = std::collections::BTreeMap::new();
+		objects.insert(
+			object.to_string(),
+			GeometryCollection {
+				ty: "GeometryCollection",
+				geometries,
+			},
+		);
+
+		Self {
+			ty: "Topology",
+			bbox,
+			transform,
+			objects,
+			arcs: encoded,
+		}
+	}
+
+	pub fn transform(&self) -> Transform {
+		self.transform
+	}
+
+	/// The quantized, delta-encoded arcs
+	pub fn arcs(&self) -> &[Vec<[i64; 2]>] {
+		&self.arcs
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Topology;
+	use crate::geometry::Arcs;
+	use geo::{polygon, MultiPolygon};
+
+	fn arcs() -> Arcs {
+		Arcs::new(&[
+			(
+				"west".to_string(),
+				MultiPolygon(vec![polygon![
+					(x: 0.0, y: 0.0),
+					(x: 1.0, y: 0.0),
+					(x: 1.0, y: 1.0),
+					(x: 0.0, y: 1.0),
+					(x: 0.0, y: 0.0),
+				]]),
+			),
+			(
+				"east".to_string(),
+				MultiPolygon(vec![polygon![
+					(x: 1.0, y: 0.0),
+					(x: 2.0, y: 0.0),
+					(x: 2.0, y: 1.0),
+					(x: 1.0, y: 1.0),
+					(x: 1.0, y: 0.0),
+				]]),
+			),
+		])
+	}
+
+	#[test]
+	fn arcs_are_quantized_and_delta_encoded() {
+		let topology = Topology::new(&arcs(), None, "blocks", 3, |_| None);
+
+		assert_eq!(topology.transform().translate, [0.0, 0.0]);
+		assert_eq!(topology.transform().scale, [1.0, 0.5]);
+
+		for arc in topology.arcs() {
+			let end: [i64; 2] = arc
+				.iter()
+				.fold([0, 0], |sum, delta| [sum[0] + delta[0], sum[1] + delta[1]]);
+
+			assert!(end[0] >= 0 && end[0] <= 2);
+			assert!(end[1] >= 0 && end[1] <= 2);
+			assert!(arc[1..].iter().all(|delta| *delta != [0, 0]));
+		}
+	}
+
+	#[test]
+	fn geometries_refer_to_shared_arcs() {
+		let topology = Topology::new(&arcs(), None, "blocks", 10_000, |idx| {
+			let mut properties = serde_json::Map::new();
+			properties.insert("index".to_string(), idx.into());
+			Some(properties)
+		});
+
+		let geometries = &topology.objects["blocks"].geometries;
+		assert_eq!(geometries.len(), 2);
+		assert_eq!(geometries[1].id, "east");
+		assert_eq!(
+			geometries[1].properties.as_ref().unwrap()["index"],
+			serde_json::Value::from(1)
+		);
+
+		let west = &geometries[0].arcs[0][0];
+		let east = &geometries[1].arcs[0][0];
+		assert!(west.iter().any(|arc| east.contains(&!arc)));
+	}
+
+	#[test]
+	fn snapped_points_are_dropped() {
+		let arcs = Arcs::new(&[(
+			"jagged".to_string(),
+			MultiPolygon(vec![polygon![
+				(x: 0.0, y: 0.0),
+				(x: 1.0, y: 0.0),
+				(x: 1.0001, y: 0.1),
+				(x: 1.0, y: 1.0),
+				(x: 0.0, y: 1.0),
+				(x: 0.0, y: 0.0),
+			]]),
+		)]);
+
+		let topology = Topology::new(&arcs, None, "blocks", 3, |_| None);
+
+		assert_eq!(topology.arcs().iter().map(Vec::len).sum::<usize>(), 5);
+	}
+
+	#[test]
+	fn arcs_can_be_simplified_as_they_are_encoded() {
+		let arcs = Arcs::new(&[(
+			"jagged".to_string(),
+			MultiPolygon(vec![polygon![
+				(x: 0.0, y: 0.0),
+				(x: 1.0, y: 0.0),
+				(x: 1.01, y: 0.5),
+				(x: 1.0, y: 1.0),
+				(x: 0.0, y: 1.0),
+				(x: 0.0, y: 0.0),
+			]]),
+		)]);
+
+		let encode = |topology: Topology| serde_json::to_string(&topology).unwrap();
+
+		assert_eq!(
+			encode(Topology::new(&arcs, Some(0.1), "blocks", 100, |_| None)),
+			encode(Topology::new(
+				&arcs.simplify(0.1),
+				None,
+				"blocks",
+				100,
+				|_| None
+			))
+		);
+		assert_ne!(
+			encode(Topology::new(&arcs, Some(0.1), "blocks", 100, |_| None)),
+			encode(Topology::new(&arcs, None, "blocks", 100, |_| None))
+		);
+	}
+}
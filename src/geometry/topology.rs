@@ -0,0 +1,296 @@
+use fnv::FnvHashMap;
+
+use super::{nested_size, vertex_key, Equirectangular, Region, VertexKey};
+use crate::GeoId;
+
+/// A segment of a ring, along with every feature whose boundary includes it
+#[derive(Debug)]
+struct Edge {
+	length: f64,
+	owners: Vec<usize>,
+}
+
+/// The shape of a group of features
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PartSummary {
+	/// Total area, in square meters
+	pub area: f64,
+	/// Length of the outer boundary of the group, in meters
+	pub perimeter: f64,
+	/// The number of contiguous pieces the group is split into
+	pub pieces: usize,
+}
+
+impl PartSummary {
+	/// The Polsby-Popper compactness score, `4πA / P²`
+	///
+	/// This is `1` for a circle and approaches `0` for less compact shapes.
+	pub fn polsby_popper(&self) -> f64 {
+		if self.perimeter > 0.0 {
+			4.0 * core::f64::consts::PI * self.area / (self.perimeter * self.perimeter)
+		} else {
+			0.0
+		}
+	}
+}
+
+/// Adjacency and boundary information for a set of polygonal features
+///
+/// Features are considered adjacent if their boundaries share a segment, which
+/// holds for topologically-integrated data like the TIGER/Line shapefiles.
+/// Lengths and areas are measured on an equirectangular projection centered on
+/// the features, which is accurate enough at the scale of a state.
+#[derive(Debug)]
+pub struct Topology {
+	geoids: Vec<GeoId>,
+	index: FnvHashMap<GeoId, usize>,
+	areas: Vec<f64>,
+	edges: Vec<Edge>,
+	neighbors: Vec<Vec<usize>>,
+}
+
+impl Topology {
+	pub fn new(features: &[Region]) -> Self {
+		let projection = Equirectangular::centered_on(features.iter().map(|(_, polygons)| polygons));
+
+		let mut geoids: Vec<GeoId> = Vec::with_capacity(features.len());
+		let mut areas: Vec<f64> = Vec::with_capacity(features.len());
+		let mut edges: Vec<Edge> = Vec::new();
+		let mut edge_index: FnvHashMap<(VertexKey, VertexKey), usize> = FnvHashMap::default();
+
+		for (idx, (geoid, polygons)) in features.iter().enumerate() {
+			let mut area: f64 = 0.0;
+
+			for polygon in polygons {
+				area += projection.polygon_area(polygon);
+
+				for ring in core::iter::once(polygon.exterior()).chain(polygon.interiors()) {
+					for line in ring.lines() {
+						let (a, b): (VertexKey, VertexKey) = (vertex_key(line.start), vertex_key(line.end));
+						if a == b {
+							continue;
+						}
+
+						let key = if a < b { (a, b) } else { (b, a) };
+						let edge: usize = *edge_index.entry(key).or_insert_with(|| {
+							edges.push(Edge {
+								length: projection.distance(line.start, line.end),
+								owners: Vec::with_capacity(2),
+							});
+							edges.len() - 1
+						});
+
+						if !edges[edge].owners.contains(&idx) {
+							edges[edge].owners.push(idx);
+						}
+					}
+				}
+			}
+
+			geoids.push(geoid.clone());
+			areas.push(area);
+		}
+
+		let mut neighbors: Vec<Vec<usize>> = vec![Vec::new(); geoids.len()];
+		for edge in edges.iter().filter(|edge| edge.owners.len() > 1) {
+			for &a in &edge.owners {
+				for &b in &edge.owners {
+					if a != b {
+						neighbors[a].push(b);
+					}
+				}
+			}
+		}
+		for list in &mut neighbors {
+			list.sort_unstable();
+			list.dedup();
+		}
+
+		let index: FnvHashMap<GeoId, usize> = geoids
+			.iter()
+			.enumerate()
+			.map(|(idx, geoid)| (geoid.clone(), idx))
+			.collect();
+
+		Self {
+			geoids,
+			index,
+			areas,
+			edges,
+			neighbors,
+		}
+	}
+
+	pub fn len(&self) -> usize {
+		self.geoids.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.geoids.is_empty()
+	}
+
+	pub fn geoids(&self) -> &[GeoId] {
+		&self.geoids
+	}
+
+	/// Roughly how many bytes the topology takes up
+	pub fn size(&self) -> usize {
+		// Each GeoID is held twice, once by the index.
+		let geoids: usize = self
+			.geoids
+			.iter()
+			.map(|geoid| {
+				2 * (core::mem::size_of::<GeoId>() + geoid.len()) + core::mem::size_of::<usize>()
+			})
+			.sum();
+		let owners: usize = self
+			.edges
+			.iter()
+			.map(|edge| core::mem::size_of::<Edge>() + edge.owners.len() * core::mem::size_of::<usize>())
+			.sum();
+
+		geoids + self.areas.len() * core::mem::size_of::<f64>() + owners + nested_size(&self.neighbors)
+	}
+
+	pub fn index_of(&self, geoid: &str) -> Option<usize> {
+		self.index.get(geoid).copied()
+	}
+
+	/// The features sharing a boundary segment with feature `idx`
+	pub fn neighbors(&self, idx: usize) -> &[usize] {
+		&self.neighbors[idx]
+	}
+
+	/// The area of feature `idx`, in square meters
+	pub fn area(&self, idx: usize) -> f64 {
+		self.areas[idx]
+	}
+
+	/// Summarize the shape of each part of a partition of the features
+	///
+	/// `parts[idx]` is the part feature `idx` belongs to, if any; parts are
+	/// numbered from `0` to `count - 1`.
+	pub fn summarize(&self, parts: &[Option<usize>], count: usize) -> Vec<PartSummary> {
+		debug_assert_eq!(parts.len(), self.len());
+
+		let mut summaries: Vec<PartSummary> = vec![PartSummary::default(); count];
+		let part_of = |idx: usize| parts[idx].filter(|part| *part < count);
+
+		for (idx, area) in self.areas.iter().enumerate() {
+			if let Some(part) = part_of(idx) {
+				summaries[part].area += area;
+			}
+		}
+
+		// A segment is on the boundary of a part if it belongs to the part but
+		// isn't shared with any other feature in the same part.
+		for edge in &self.edges {
+			for (i, &owner) in edge.owners.iter().enumerate() {
+				let part = match part_of(owner) {
+					Some(part) => part,
+					None => continue,
+				};
+
+				let counted_already = edge.owners[..i]
+					.iter()
+					.any(|&other| part_of(other) == Some(part));
+				let shared = edge
+					.owners
+					.iter()
+					.any(|&other| other != owner && part_of(other) == Some(part));
+
+				if !counted_already && !shared {
+					summaries[part].perimeter += edge.length;
+				}
+			}
+		}
+
+		let mut visited: Vec<bool> = vec![false; self.len()];
+		let mut stack: Vec<usize> = Vec::new();
+
+		for start in 0..self.len() {
+			let part = match part_of(start) {
+				Some(part) if !visited[start] => part,
+				_ => continue,
+			};
+
+			summaries[part].pieces += 1;
+			visited[start] = true;
+			stack.push(start);
+
+			while let Some(idx) = stack.pop() {
+				for &neighbor in &self.neighbors[idx] {
+					if !visited[neighbor] && part_of(neighbor) == Some(part) {
+						visited[neighbor] = true;
+						stack.push(neighbor);
+					}
+				}
+			}
+		}
+
+		summaries
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{PartSummary, Topology};
+	use geo::{polygon, MultiPolygon};
+
+	/// A unit square (in degrees) with its lower-left corner at `(x, y)`
+	fn square(geoid: &str, x: f64, y: f64) -> (String, MultiPolygon<f64>) {
+		(
+			geoid.to_string(),
+			MultiPolygon(vec![polygon![
+				(x: x, y: y),
+				(x: x + 1.0, y: y),
+				(x: x + 1.0, y: y + 1.0),
+				(x: x, y: y + 1.0),
+				(x: x, y: y),
+			]]),
+		)
+	}
+
+	fn topology() -> Topology {
+		Topology::new(&[
+			square("a", 0.0, 0.0),
+			square("b", 1.0, 0.0),
+			square("c", 0.0, 1.0),
+			square("d", 5.0, 5.0),
+		])
+	}
+
+	#[test]
+	fn shared_edges_make_neighbors() {
+		let topology = topology();
+
+		assert_eq!(topology.neighbors(0), &[1, 2]);
+		assert_eq!(topology.neighbors(1), &[0]);
+		assert_eq!(topology.neighbors(3), &[] as &[usize]);
+		assert_eq!(topology.index_of("c"), Some(2));
+	}
+
+	#[test]
+	fn summaries_count_pieces_and_boundaries() {
+		let topology = topology();
+		let side: f64 = topology.area(0).sqrt();
+
+		let summaries: Vec<PartSummary> = topology.summarize(&[Some(0), Some(0), Some(1), Some(0)], 2);
+
+		assert_eq!(summaries[0].pieces, 2);
+		assert_eq!(summaries[1].pieces, 1);
+
+		// Two squares side by side have a perimeter of six sides, plus four for
+		// the separate one.  Sides aren't exactly equal on the projection, so
+		// compare loosely.
+		assert!((summaries[0].perimeter / side - 10.0).abs() < 0.05);
+		assert!((summaries[1].perimeter / side - 4.0).abs() < 0.05);
+		assert!((summaries[1].polsby_popper() - core::f64::consts::PI / 4.0).abs() < 0.01);
+	}
+
+	#[test]
+	fn unassigned_features_are_ignored() {
+		let summaries = topology().summarize(&[None, None, None, None], 1);
+		assert_eq!(summaries, vec![PartSummary::default()]);
+	}
+}
@@ -15,6 +15,11 @@ impl LogicalRecordPositionIndex {
 		let idx: usize = logrecno as usize;
 		self.inner[idx] = offset;
 	}
+
+	/// The number of bytes the positions take up
+	pub fn size(&self) -> usize {
+		self.inner.len() * core::mem::size_of::<u64>()
+	}
 }
 
 impl Extend<(LogicalRecordNumber, u64)> for LogicalRecordPositionIndex {
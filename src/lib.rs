@@ -21,6 +21,9 @@ pub trait LogicalRecord {
 mod dataset;
 pub use dataset::*;
 
+mod dictionary;
+pub use dictionary::*;
+
 pub struct FileBackedLogicalRecord {
 	number: LogicalRecordNumber,
 	raw_records: FnvHashMap<u32, csv::StringRecord>,
@@ -43,17 +46,58 @@ impl FileBackedLogicalRecord {
 	pub fn raw_records(&self) -> &FnvHashMap<u32, csv::StringRecord> {
 		&self.raw_records
 	}
+
+	/// Collect the fields of a table from the raw records
+	///
+	/// Tables can span several files, so the segments are concatenated in the
+	/// order they are given.  Returns `None` if any segment is missing.
+	pub fn table(&self, locations: &[TableSegmentLocation]) -> Option<Vec<&str>> {
+		let mut fields: Vec<&str> = Vec::new();
+
+		for location in locations {
+			let record: &csv::StringRecord = self.raw_records.get(&location.file)?;
+
+			for idx in location.range.clone() {
+				fields.push(record.get(idx)?);
+			}
+		}
+
+		Some(fields)
+	}
 }
 
 /// A geographical header
 pub trait GeographicalHeader {
 	fn name(&self) -> &str;
 	fn logrecno(&self) -> LogicalRecordNumber;
+
+	/// The total population count (`POP100`) of the area
+	fn population(&self) -> Result<u64>;
+
+	/// The summary level (`SUMLEV`), e.g. `750` for a block
+	fn summary_level(&self) -> &str;
+
+	/// The geographic component (`GEOCOMP`); `00` is the whole of the area
+	fn geographic_component(&self) -> &str;
+
+	fn state(&self) -> &str;
+	fn county(&self) -> &str;
+	fn tract(&self) -> &str;
+	fn block_group(&self) -> &str;
+	fn block(&self) -> &str;
+	fn voting_district(&self) -> &str;
+	fn place(&self) -> &str;
 }
 
+pub mod acs;
+pub mod census2000;
 pub mod census2010;
 pub mod census2020;
 
+pub mod crosswalk;
+
+pub mod geometry;
+
 mod schema;
 pub use schema::*;
 
@@ -68,7 +112,7 @@ impl core::str::FromStr for TableSegmentSpecifier {
 	fn from_str(s: &str) -> Result<Self> {
 		let components: Vec<&str> = s.split(':').collect();
 		let file: u32 = components
-			.get(0)
+			.first()
 			.expect("missing file identifier")
 			.parse()
 			.expect("couldn't parse file identifier");
@@ -88,6 +132,13 @@ pub struct TableSegmentLocation {
 	range: core::ops::Range<usize>,
 }
 
+impl TableSegmentLocation {
+	/// The number of columns in this segment
+	pub fn columns(&self) -> usize {
+		self.range.len()
+	}
+}
+
 pub type TableName = String;
 pub type TableLocationSpecifier = Vec<TableSegmentSpecifier>;
 pub type TableLocations = Vec<TableSegmentLocation>;
@@ -1,16 +1,214 @@
+use serde::{Deserialize, Serialize};
+
+use crate::census2000;
 use crate::census2010;
 use crate::census2020;
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
 #[non_exhaustive]
 pub enum Schema {
+	Census2000(census2000::Schema),
 	Census2010(census2010::Schema),
 	Census2020(census2020::Schema),
 }
 
+impl Schema {
+	/// Look up a table in this schema by its (case-insensitive) name
+	///
+	/// Names are those used by the packing lists, e.g. `p1` or `h1`.
+	pub fn table(&self, name: &str) -> Option<Table> {
+		match self {
+			Schema::Census2000(schema) => schema.table(name).map(Table::Census2000),
+			Schema::Census2010(schema) => schema.table(name).map(Table::Census2010),
+			Schema::Census2020(schema) => schema.table(name).map(Table::Census2020),
+		}
+	}
+
+	/// The summary level of block records, e.g. `750`
+	pub fn block_summary_level(&self) -> &'static str {
+		match self {
+			Schema::Census2000(schema) => schema.block_summary_level(),
+			Schema::Census2010(schema) => schema.block_summary_level(),
+			Schema::Census2020(schema) => schema.block_summary_level(),
+		}
+	}
+}
+
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 #[non_exhaustive]
 pub enum Table {
+	Census2000(census2000::Table),
 	Census2010(census2010::Table),
 	Census2020(census2020::Table),
 }
+
+impl core::fmt::Display for Table {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		match self {
+			Table::Census2000(table) => table.fmt(f),
+			Table::Census2010(table) => table.fmt(f),
+			Table::Census2020(table) => table.fmt(f),
+		}
+	}
+}
+
+/// The constructor of the tables of one subject, from their number and
+/// iteration
+pub(crate) type TableConstructor<T> = fn(u16, Option<char>) -> T;
+
+/// Parse a table name made of a subject, a number and an optional iteration
+/// letter, e.g. `p12a`
+///
+/// Each subject comes with the constructor of its tables.  A subject that
+/// prefixes another, like `p` does `pct`, must come after it.
+pub(crate) fn parse_table_name<T>(
+	name: &str,
+	subjects: &[(&str, TableConstructor<T>)],
+) -> crate::Result<T> {
+	let name: String = name.to_lowercase();
+
+	let (constructor, rest) = subjects
+		.iter()
+		.find_map(|(subject, constructor)| name.strip_prefix(subject).map(|rest| (constructor, rest)))
+		.ok_or(crate::Error::InvalidTableName)?;
+
+	let digits: usize = rest.bytes().take_while(u8::is_ascii_digit).count();
+	let (number, iteration) = rest.split_at(digits);
+
+	let number: u16 = number.parse().map_err(|_| crate::Error::InvalidTableName)?;
+	let mut iteration = iteration.chars();
+
+	match (number, iteration.next(), iteration.next()) {
+		(0, _, _) => Err(crate::Error::InvalidTableName),
+		(number, None, _) => Ok(constructor(number, None)),
+		(number, Some(letter), None) if letter.is_ascii_lowercase() => {
+			Ok(constructor(number, Some(letter)))
+		}
+		_ => Err(crate::Error::InvalidTableName),
+	}
+}
+
+/// A table of a summary file identified by its subject, number and iteration
+///
+/// SF1 and DHC have hundreds of tables, so rather than naming each one they're
+/// identified this way, the iteration being the letter that repeats a table
+/// for a race or Hispanic origin group.  For example, `p12a` is
+/// `SegmentedTable::P(12, Some('a'))`.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SegmentedTable {
+	/// Population tables, down to the block
+	P(u16, Option<char>),
+	/// Population tables, down to the census tract
+	Pct(u16, Option<char>),
+	/// Group quarters population tables, down to the county
+	Pco(u16, Option<char>),
+	/// Housing tables, down to the block
+	H(u16, Option<char>),
+	/// Housing tables, down to the census tract
+	Hct(u16, Option<char>),
+}
+
+impl SegmentedTable {
+	fn parts(&self) -> (&'static str, u16, Option<char>) {
+		match *self {
+			SegmentedTable::P(number, iteration) => ("p", number, iteration),
+			SegmentedTable::Pct(number, iteration) => ("pct", number, iteration),
+			SegmentedTable::Pco(number, iteration) => ("pco", number, iteration),
+			SegmentedTable::H(number, iteration) => ("h", number, iteration),
+			SegmentedTable::Hct(number, iteration) => ("hct", number, iteration),
+		}
+	}
+
+	pub fn number(&self) -> u16 {
+		self.parts().1
+	}
+
+	pub fn iteration(&self) -> Option<char> {
+		self.parts().2
+	}
+}
+
+impl core::fmt::Display for SegmentedTable {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		let (subject, number, iteration) = self.parts();
+
+		write!(f, "{}{}", subject, number)?;
+		match iteration {
+			Some(iteration) => write!(f, "{}", iteration),
+			None => Ok(()),
+		}
+	}
+}
+
+impl core::str::FromStr for SegmentedTable {
+	type Err = crate::Error;
+
+	fn from_str(s: &str) -> crate::Result<Self> {
+		// Longer subjects first, as `p` and `h` prefix the others.
+		parse_table_name(
+			s,
+			&[
+				(
+					"pct",
+					SegmentedTable::Pct as TableConstructor<SegmentedTable>,
+				),
+				("pco", SegmentedTable::Pco),
+				("hct", SegmentedTable::Hct),
+				("p", SegmentedTable::P),
+				("h", SegmentedTable::H),
+			],
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Schema, SegmentedTable};
+
+	#[test]
+	fn segmented_table_names_round_trip() {
+		for (name, table) in &[
+			("p1", SegmentedTable::P(1, None)),
+			("p12a", SegmentedTable::P(12, Some('a'))),
+			("p12i", SegmentedTable::P(12, Some('i'))),
+			("pct12a", SegmentedTable::Pct(12, Some('a'))),
+			("pct12o", SegmentedTable::Pct(12, Some('o'))),
+			("pco1", SegmentedTable::Pco(1, None)),
+			("pco10", SegmentedTable::Pco(10, None)),
+			("h1", SegmentedTable::H(1, None)),
+			("h11", SegmentedTable::H(11, None)),
+			("hct1", SegmentedTable::Hct(1, None)),
+			("hct4", SegmentedTable::Hct(4, None)),
+		] {
+			assert_eq!(name.parse::<SegmentedTable>().unwrap(), *table);
+			assert_eq!(table.to_string(), *name);
+		}
+
+		assert_eq!(
+			"PCT12A".parse::<SegmentedTable>().unwrap(),
+			SegmentedTable::Pct(12, Some('a'))
+		);
+
+		for name in &[
+			"", "p", "p0", "q1", "p12ab", "p12-", "pct", "pco", "pco0", "pco1ab",
+		] {
+			assert!(name.parse::<SegmentedTable>().is_err(), "{} parsed", name);
+		}
+	}
+
+	#[test]
+	fn blocks_are_found_at_each_schemas_summary_level() {
+		use crate::{census2000, census2010, census2020};
+
+		for (schema, level) in &[
+			(Schema::Census2000(census2000::Schema::Pl94_171), "101"),
+			(Schema::Census2010(census2010::Schema::Pl94_171), "750"),
+			(Schema::Census2010(census2010::Schema::Sf1), "101"),
+			(Schema::Census2020(census2020::Schema::Pl94_171), "750"),
+			(Schema::Census2020(census2020::Schema::Dhc), "750"),
+		] {
+			assert_eq!(schema.block_summary_level(), *level, "{:?}", schema);
+		}
+	}
+}
@@ -53,12 +53,22 @@ fn main() -> distringo::Result<()> {
 		vec![&rec_a, &rec_b]
 	);
 
+	let p1 = distringo::Table::Census2010(distringo::census2010::Table::Pl94_171(
+		distringo::census2010::pl94_171::P1,
+	));
+	let p1_locations = ds.table_locations(&p1).expect("missing locations for P1");
+	assert_eq!(
+		record.table(p1_locations),
+		Some(rec_a.iter().skip(5).take(71).collect::<Vec<&str>>())
+	);
+
 	let logrecno = ds.get_logical_record_number_for_geoid("181570052001013")?;
 	assert_eq!(logrecno, 335_180);
 
 	let header = ds.get_header_for_geoid("181570052001013")?;
 	assert_eq!(header.name(), "Block 1013");
 	assert_eq!(header.logrecno(), 335_180);
+	assert_eq!(header.population()?, 53);
 
 	Ok(())
 }
@@ -152,6 +152,7 @@ fn main() -> distringo::Result<()> {
 	let header = ds.get_header_for_geoid("440070184001012")?;
 	assert_eq!(header.name(), "Block 1012");
 	assert_eq!(header.logrecno(), 19_200);
+	assert_eq!(header.population()?, 25);
 
 	Ok(())
 }